@@ -6,7 +6,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { LpStaking } from "./target/types/lp_staking";
-import { PublicKey, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { PublicKey, LAMPORTS_PER_SOL } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
   getOrCreateAssociatedTokenAccount,
@@ -80,13 +80,9 @@ async function main() {
   
   if (vaultBalance < 5 * LAMPORTS_PER_SOL) {
     console.log("⏳ 充值 Reward Vault (10 SOL)...");
-    const transferIx = SystemProgram.transfer({
-      fromPubkey: payer.publicKey,
-      toPubkey: rewardVault,
-      lamports: 10 * LAMPORTS_PER_SOL,
-    });
-    const transferTx = new anchor.web3.Transaction().add(transferIx);
-    await provider.sendAndConfirm(transferTx);
+    await program.methods
+      .fundRewards(new anchor.BN(10 * LAMPORTS_PER_SOL))
+      .rpc();
     vaultBalance = await provider.connection.getBalance(rewardVault);
     console.log("✅ 充值完成，新余额:", vaultBalance / LAMPORTS_PER_SOL, "SOL\n");
   } else {
@@ -102,7 +98,7 @@ async function main() {
   console.log("⏳ 质押 5 LP...");
   
   try {
-    const stakeTx = await program.methods.stake(stakeAmount).rpc();
+    const stakeTx = await program.methods.stake(stakeAmount, null).rpc();
     console.log("✅ 质押成功! 交易:", stakeTx);
     
     const userPos = await program.account.userPosition.fetch(userPosition);
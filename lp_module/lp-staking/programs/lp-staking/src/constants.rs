@@ -1,10 +1,25 @@
-/// 程序常量定义
+//! 程序常量定义
 
 /// PDA Seeds
 pub const POOL_STATE_SEED: &[u8] = b"pool_state";
 pub const USER_POSITION_SEED: &[u8] = b"user_position";
 pub const REWARD_CONFIG_SEED: &[u8] = b"reward_config";
 pub const REWARD_VAULT_SEED: &[u8] = b"reward_vault";
+pub const STRATEGY_STATE_SEED: &[u8] = b"strategy_state";
+pub const TOKENIZED_POSITION_SEED: &[u8] = b"tokenized_position";
+pub const LP_VAULT_SEED: &[u8] = b"lp_vault";
+pub const VOTE_ESCROW_SEED: &[u8] = b"vote_escrow";
+pub const VOTE_ESCROW_GLOBAL_SEED: &[u8] = b"vote_escrow_global";
+pub const VOTE_ESCROW_PAGE_SEED: &[u8] = b"vote_escrow_page";
+pub const STAKE_HISTORY_SEED: &[u8] = b"stake_history";
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+pub const STABLE_SWAP_SEED: &[u8] = b"stable_swap";
+pub const STABLE_VAULT_SEED: &[u8] = b"stable_vault";
+pub const STABLE_LP_MINT_SEED: &[u8] = b"stable_lp_mint";
+pub const STABLE_STAKE_SEED: &[u8] = b"stable_stake";
+pub const STABLE_STAKE_VAULT_SEED: &[u8] = b"stable_stake_vault";
+pub const STABLE_REWARD_VAULT_SEED: &[u8] = b"stable_reward_vault";
+pub const OPERATOR_APPROVAL_SEED: &[u8] = b"operator_approval";
 
 /// 最小存入金额（1 USDC，假设 6 位小数）
 pub const MIN_DEPOSIT_AMOUNT: u64 = 1_000_000;
@@ -14,3 +29,21 @@ pub const MIN_STAKE_AMOUNT: u64 = 100_000;
 
 /// 基点基数（用于百分比计算）
 pub const BASIS_POINTS: u64 = 10_000;
+
+/// 闪电贷手续费上限（1%）
+pub const MAX_FLASH_LOAN_FEE_BPS: u64 = 100;
+
+/// 忠诚度加成上限（100%，即最多 2 倍奖励权重）
+pub const MAX_LOYALTY_BONUS_BPS: u64 = 10_000;
+
+/// 全局质押量历史每个分页的检查点数量
+pub const STAKE_HISTORY_PAGE_SIZE: usize = 256;
+
+/// 最长锁仓时长（约 4 年，按 400ms/slot 计算）
+pub const VE_MAX_LOCK_SLOTS: u64 = 315_360_000;
+
+/// 锁仓到期 slot 的对齐粒度（约 1 周），限制全局斜率变化的条目数量
+pub const VE_SLOT_GRANULARITY: u64 = 1_512_000;
+
+/// 全局账户保留的检查点数量，超出时将较早的检查点归档到分页账户
+pub const VE_GLOBAL_PAGE_SIZE: usize = 64;
@@ -37,4 +37,124 @@ pub enum LpStakingError {
     
     #[msg("No reward to claim")]
     NoRewardToClaim,
+    
+    #[msg("Invalid allocation: must be <= 10000 basis points")]
+    InvalidAllocation,
+    
+    #[msg("Strategy allocation limit exceeded")]
+    AllocationLimitExceeded,
+    
+    #[msg("Insufficient idle liquidity in pool")]
+    InsufficientLiquidity,
+    
+    #[msg("Invalid strategy program")]
+    InvalidStrategyProgram,
+    
+    #[msg("Invalid response from strategy program")]
+    InvalidStrategyResponse,
+    
+    #[msg("Slippage exceeded: output below minimum")]
+    SlippageExceeded,
+    
+    #[msg("Transaction expired")]
+    TransactionExpired,
+    
+    #[msg("Position is not empty")]
+    PositionNotEmpty,
+    
+    #[msg("Unsupported account version or layout")]
+    UnsupportedAccountVersion,
+    
+    #[msg("Invalid harvest accounts")]
+    InvalidHarvestAccounts,
+    
+    #[msg("Signer does not hold the position token")]
+    NotPositionHolder,
+    
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    
+    #[msg("Referrer account does not match the recorded referrer")]
+    ReferrerMismatch,
+    
+    #[msg("Invalid referral share")]
+    InvalidReferralBps,
+    
+    #[msg("Staked LP is locked")]
+    StakeLocked,
+    
+    #[msg("Invalid lock amount or duration")]
+    InvalidLock,
+    
+    #[msg("Treasury is not set or does not match")]
+    InvalidTreasury,
+    
+    #[msg("Insufficient undistributed rewards")]
+    InsufficientUndistributed,
+    
+    #[msg("Reward accounting invariant violated")]
+    RewardInvariantViolated,
+    
+    #[msg("Invalid protocol fee")]
+    InvalidProtocolFeeBps,
+    
+    #[msg("Withdrawal exceeds the outflow limit for the current window")]
+    OutflowLimitExceeded,
+    
+    #[msg("Invalid outflow limit")]
+    InvalidOutflowLimit,
+    
+    #[msg("Pool is paused")]
+    PoolPaused,
+    
+    #[msg("Invalid price feed")]
+    InvalidPriceFeed,
+    
+    #[msg("Price deviates from peg beyond the configured band")]
+    PriceOutOfPeg,
+    
+    #[msg("Price is stale")]
+    StalePrice,
+    
+    #[msg("Invalid StableSwap parameters")]
+    InvalidStableSwapParams,
+    
+    #[msg("StableSwap invariant did not converge")]
+    StableSwapNotConverged,
+    
+    #[msg("Invalid flash loan fee")]
+    InvalidFlashLoanFee,
+    
+    #[msg("A flash loan is outstanding")]
+    FlashLoanActive,
+    
+    #[msg("No flash loan is outstanding")]
+    NoFlashLoanOutstanding,
+    
+    #[msg("Flash loan must be repaid by a later flash_repay instruction in the same transaction")]
+    MissingFlashRepay,
+    
+    #[msg("Flash loan cannot be invoked via CPI")]
+    FlashLoanCpiNotAllowed,
+    
+    #[msg("Invalid loyalty curve")]
+    InvalidLoyaltyCurve,
+    
+    #[msg("Beneficiary must differ from the payer")]
+    InvalidBeneficiary,
+    
+    #[msg("Removing the full StableSwap LP supply requires a balanced withdrawal")]
+    StableSwapFullExitOneSided,
+    
+    #[msg("Vote escrow accounts are required to tokenize locked stake")]
+    VoteEscrowRequired,
+    
+    #[msg("Positions with a recorded referrer cannot be closed")]
+    ReferredPositionNotClosable,
+    
+    #[msg("Stake history page does not cover the requested slot")]
+    StakeHistoryPageMismatch,
+    
+    #[msg("Vote escrow history page does not cover the requested slot")]
+    VoteEscrowPageMismatch,
 }
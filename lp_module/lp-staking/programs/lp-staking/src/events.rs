@@ -0,0 +1,377 @@
+use anchor_lang::prelude::*;
+use crate::state::StableToken;
+
+/// 存入并质押事件
+#[event]
+pub struct DepositAndStakeEvent {
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 存入的 wrappedUSDC 数量
+    pub usdc_amount: u64,
+    /// 铸造并质押的 LP Token 数量
+    pub lp_amount: u64,
+    /// 用户当前质押总量
+    pub staked_amount: u64,
+    /// 池子当前质押总量
+    pub pool_total_staked: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 解除质押并提取事件
+#[event]
+pub struct UnstakeAndWithdrawEvent {
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 解除质押并销毁的 LP Token 数量
+    pub lp_amount: u64,
+    /// 提取的 wrappedUSDC 数量
+    pub usdc_amount: u64,
+    /// 用户剩余质押量
+    pub staked_amount: u64,
+    /// 用户待领取奖励
+    pub pending_reward: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 仓位关闭事件
+#[event]
+pub struct PositionClosedEvent {
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 被关闭的仓位账户
+    pub position: Pubkey,
+    /// 退还的租金（lamports）
+    pub rent_refunded: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 账户布局迁移事件
+#[event]
+pub struct AccountMigratedEvent {
+    /// 被迁移的账户
+    pub account: Pubkey,
+    /// 迁移前版本（0 表示无版本字段的旧布局）
+    pub from_version: u8,
+    /// 迁移后版本
+    pub to_version: u8,
+}
+
+/// 奖励池更新事件（update_pool crank）
+#[event]
+pub struct PoolUpdatedEvent {
+    /// 调用者
+    pub caller: Pubkey,
+    /// 更新前的累计每份奖励
+    pub acc_reward_per_share_before: u128,
+    /// 更新后的累计每份奖励
+    pub acc_reward_per_share_after: u128,
+    /// 更新前的 last_update_slot
+    pub last_update_slot_before: u64,
+    /// 更新后的 last_update_slot
+    pub last_update_slot_after: u64,
+    /// 池子总质押量
+    pub total_staked: u64,
+}
+
+/// 奖励领取事件
+#[event]
+pub struct RewardClaimedEvent {
+    /// 仓位所有者
+    pub owner: Pubkey,
+    /// 奖励接收地址
+    pub recipient: Pubkey,
+    /// 发起领取的签名者（所有者或收获代理）
+    pub caller: Pubkey,
+    /// 领取数量（lamports）
+    pub amount: u64,
+    /// 推荐人（Pubkey::default() 表示无推荐人）
+    pub referrer: Pubkey,
+    /// 额外支付给推荐人的奖励（lamports）
+    pub referral_reward: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 质押锁仓事件
+#[event]
+pub struct StakeLockedEvent {
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 锁仓后的总锁仓数量
+    pub locked_amount: u64,
+    /// 锁仓到期 slot
+    pub lock_end_slot: u64,
+    /// 锁仓后的当前投票权
+    pub voting_power: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 质押快照事件
+#[event]
+pub struct SnapshotTakenEvent {
+    /// 快照编号
+    pub snapshot_id: u64,
+    /// 快照 slot
+    pub slot: u64,
+    /// 创建快照时的池子总质押量
+    pub total_staked: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 快照质押证明事件
+#[event]
+pub struct StakeProvenEvent {
+    /// 快照编号
+    pub snapshot_id: u64,
+    /// 快照 slot
+    pub slot: u64,
+    /// 用户钱包地址
+    pub owner: Pubkey,
+    /// 用户在快照时的质押量
+    pub staked_amount: u64,
+    /// 池子在快照时的总质押量
+    pub total_staked: u64,
+}
+
+/// 仓位代币化事件
+#[event]
+pub struct PositionTokenizedEvent {
+    /// 质押者钱包地址
+    pub owner: Pubkey,
+    /// 仓位代币 mint
+    pub mint: Pubkey,
+    /// 代币化仓位账户
+    pub position: Pubkey,
+    /// 质押的 LP Token 数量
+    pub amount: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 代币化仓位退出事件
+#[event]
+pub struct TokenizedPositionExitedEvent {
+    /// 销毁仓位代币的持有者
+    pub holder: Pubkey,
+    /// 仓位代币 mint
+    pub mint: Pubkey,
+    /// 被关闭的代币化仓位账户
+    pub position: Pubkey,
+    /// 退回的 LP Token 数量
+    pub amount: u64,
+    /// 记入持有者仓位的待领取奖励
+    pub reward_credited: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 未分配奖励提取事件
+#[event]
+pub struct UndistributedWithdrawnEvent {
+    /// 国库地址
+    pub treasury: Pubkey,
+    /// 提取数量（lamports）
+    pub amount: u64,
+    /// 剩余未分配奖励（lamports）
+    pub remaining: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 奖励金库不足事件（部分领取）
+#[event]
+pub struct RewardShortfallEvent {
+    /// 仓位所有者
+    pub owner: Pubkey,
+    /// 应领取的奖励数量
+    pub requested: u64,
+    /// 实际支付的数量
+    pub paid: u64,
+    /// 未支付、保留为待领取的数量
+    pub shortfall: u64,
+    /// 支付前金库可用余额（超出免租最低余额的部分）
+    pub vault_available: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 奖励注资事件
+#[event]
+pub struct RewardsFundedEvent {
+    /// 注资账户
+    pub funder: Pubkey,
+    /// 本次转入的数量（lamports）
+    pub amount: u64,
+    /// 同步记账的金库既有余额（lamports）
+    pub unrecorded: u64,
+    /// 累计注入的资金（lamports）
+    pub total_funded: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 协议奖励提取事件
+#[event]
+pub struct ProtocolRewardsCollectedEvent {
+    /// 国库地址
+    pub treasury: Pubkey,
+    /// 提取数量（lamports）
+    pub amount: u64,
+    /// 剩余已计提的协议奖励（lamports）
+    pub remaining: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 提取限额更新事件（设置或重置窗口）
+#[event]
+pub struct OutflowLimitUpdatedEvent {
+    /// 操作者（管理员或守护者）
+    pub updated_by: Pubkey,
+    /// 每个窗口允许提取的 TVL 比例（基点）
+    pub limit_bps: u64,
+    /// 窗口长度（秒）
+    pub window_secs: i64,
+    /// 新窗口的基准 TVL
+    pub window_tvl: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 池子暂停状态变更事件
+#[event]
+pub struct PoolPauseUpdatedEvent {
+    /// 操作者（管理员、守护者或触发自动暂停的 keeper）
+    pub updated_by: Pubkey,
+    /// 是否暂停
+    pub paused: bool,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 脱锚检测事件
+#[event]
+pub struct DepegDetectedEvent {
+    /// 调用者
+    pub caller: Pubkey,
+    /// 报价（实际价格 = price * 10^expo）
+    pub price: i64,
+    /// 价格指数
+    pub expo: i32,
+    /// 报价发布时间
+    pub publish_time: i64,
+    /// 是否因报价过期触发
+    pub stale: bool,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// StableSwap 兑换事件
+#[event]
+pub struct StableSwapEvent {
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 输入币种
+    pub token_in: StableToken,
+    /// 输入数量
+    pub amount_in: u64,
+    /// 输出数量
+    pub amount_out: u64,
+    /// 手续费（输出币种，留在池中）
+    pub fee: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// StableSwap 流动性变化事件（正数为存入/铸造，负数为取出/销毁）
+#[event]
+pub struct StableLiquidityEvent {
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 币种 A 变化量
+    pub amount_a: i64,
+    /// 币种 B 变化量
+    pub amount_b: i64,
+    /// LP Token 变化量
+    pub lp_amount: i64,
+    /// 操作后币种 A 余额
+    pub reserve_a: u64,
+    /// 操作后币种 B 余额
+    pub reserve_b: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// StableSwap LP 质押变化事件（正数为质押，负数为解除质押）
+#[event]
+pub struct StableStakeEvent {
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 质押数量变化
+    pub amount: i64,
+    /// 用户当前质押数量
+    pub staked_amount: u64,
+    /// 池子当前质押总量
+    pub total_staked: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// StableSwap LP 质押奖励领取事件
+#[event]
+pub struct StableRewardClaimedEvent {
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 领取数量（lamports）
+    pub amount: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 闪电贷归还事件
+#[event]
+pub struct FlashLoanEvent {
+    /// 归还者地址
+    pub repayer: Pubkey,
+    /// 借款本金
+    pub amount: u64,
+    /// 手续费（计入池子资产）
+    pub fee: u64,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 代存/代质押事件
+#[event]
+pub struct StakeForEvent {
+    /// 出资者地址
+    pub payer: Pubkey,
+    /// 受益人（仓位所有者）地址
+    pub beneficiary: Pubkey,
+    /// 存入的 wrappedUSDC 数量（仅代质押时为 0）
+    pub usdc_amount: u64,
+    /// 记入受益人仓位的 LP Token 数量
+    pub lp_amount: u64,
+    /// 是否直接质押
+    pub staked: bool,
+    /// 时间戳
+    pub timestamp: i64,
+}
+
+/// 操作员授权变更事件
+#[event]
+pub struct OperatorApprovalEvent {
+    /// 仓位所有者
+    pub owner: Pubkey,
+    /// 操作员地址
+    pub operator: Pubkey,
+    /// true 为授权，false 为撤销
+    pub approved: bool,
+    /// 时间戳
+    pub timestamp: i64,
+}
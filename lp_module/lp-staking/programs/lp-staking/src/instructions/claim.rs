@@ -2,106 +2,247 @@ use anchor_lang::prelude::*;
 use anchor_lang::system_program::System;
 use crate::constants::*;
 use crate::errors::LpStakingError;
+use crate::events::{RewardClaimedEvent, RewardShortfallEvent};
 use crate::state::{PoolState, UserPosition, RewardConfig};
-use crate::utils::reward_calculator;
+use crate::utils::{reward_calculator, RewardVault};
 
 /// 领取质押奖励（SOL）
 pub fn claim_handler(ctx: Context<Claim>) -> Result<()> {
-    let user_position = &mut ctx.accounts.user_position;
-    let pool_state = &ctx.accounts.pool_state;
-    let reward_config = &mut ctx.accounts.reward_config;
     let clock = Clock::get()?;
-    
+    let user = ctx.accounts.user.to_account_info();
+    let referrer = ctx.accounts.referrer.as_ref().map(|r| r.to_account_info());
+    let accounts = &mut *ctx.accounts;
+    let reward_vault = RewardVault {
+        vault: &accounts.reward_vault,
+        pool_state: accounts.pool_state.key(),
+        bump: ctx.bumps.reward_vault,
+        system_program: accounts.system_program.as_ref(),
+    };
+
+    let result = process_claim(
+        &mut accounts.pool_state,
+        &mut accounts.reward_config,
+        &mut accounts.user_position,
+        &reward_vault,
+        &user,
+        referrer.as_ref(),
+        &clock,
+    )?;
+    require!(result.amount > 0 || result.shortfall > 0, LpStakingError::NoRewardToClaim);
+    if result.amount == 0 {
+        return Ok(());
+    }
+
+    emit!(RewardClaimedEvent {
+        owner: user.key(),
+        recipient: user.key(),
+        caller: user.key(),
+        amount: result.amount,
+        referrer: result.referrer,
+        referral_reward: result.referral_reward,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// 领取质押奖励到指定地址（需仓位所有者签名）
+pub fn claim_to_handler(ctx: Context<ClaimTo>) -> Result<()> {
+    let clock = Clock::get()?;
+    let recipient = ctx.accounts.recipient.to_account_info();
+    let referrer = ctx.accounts.claim.referrer.as_ref().map(|r| r.to_account_info());
+    let claim = &mut ctx.accounts.claim;
+    let reward_vault = RewardVault {
+        vault: &claim.reward_vault,
+        pool_state: claim.pool_state.key(),
+        bump: ctx.bumps.claim.reward_vault,
+        system_program: claim.system_program.as_ref(),
+    };
+
+    let result = process_claim(
+        &mut claim.pool_state,
+        &mut claim.reward_config,
+        &mut claim.user_position,
+        &reward_vault,
+        &recipient,
+        referrer.as_ref(),
+        &clock,
+    )?;
+    require!(result.amount > 0 || result.shortfall > 0, LpStakingError::NoRewardToClaim);
+    if result.amount == 0 {
+        return Ok(());
+    }
+
+    emit!(RewardClaimedEvent {
+        owner: claim.user.key(),
+        recipient: recipient.key(),
+        caller: claim.user.key(),
+        amount: result.amount,
+        referrer: result.referrer,
+        referral_reward: result.referral_reward,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// 领取结果
+pub struct ClaimResult {
+    /// 转给接收方的奖励数量
+    pub amount: u64,
+    /// 仓位记录的推荐人
+    pub referrer: Pubkey,
+    /// 额外支付给推荐人的奖励数量
+    pub referral_reward: u64,
+    /// 金库不足而未支付、保留在 pending_reward 中的数量
+    pub shortfall: u64,
+}
+
+/// 领取核心逻辑：更新奖励池、结算奖励并从金库转账给 recipient
+///
+/// 仓位记录了推荐人且配置了推荐比例时，按比例额外支付推荐奖励给 `referrer`，
+/// 不从 recipient 的奖励中扣除。没有可领取奖励时返回数量 0 且不做任何转账。
+/// 金库可用余额（超出免租最低余额的部分）不足时只支付可用部分，
+/// 剩余部分保留在 pending_reward 中并发出 RewardShortfallEvent；金库为空时支付数量为 0
+pub fn process_claim<'info>(
+    pool_state: &mut PoolState,
+    reward_config: &mut RewardConfig,
+    user_position: &mut UserPosition,
+    reward_vault: &RewardVault<'_, 'info>,
+    recipient: &AccountInfo<'info>,
+    referrer: Option<&AccountInfo<'info>>,
+    clock: &Clock,
+) -> Result<ClaimResult> {
     // 1. 更新奖励池状态
     reward_calculator::update_pool_reward(
-        &pool_state,
+        pool_state,
         reward_config,
         clock.slot,
     )?;
-    
-    // 2. 计算当前质押的待领取奖励
-    let pending_from_staked = if user_position.staked_amount > 0 {
-        reward_calculator::calculate_pending_reward(
-            user_position.staked_amount,
-            reward_config.acc_reward_per_share,
-            user_position.reward_debt,
-        )?
-    } else {
-        0
-    };
-    
+
+    // 2. 结算当前质押的待领取奖励，刷新忠诚度加成并更新 reward_debt
+    let pending_from_staked = reward_calculator::settle_pending_reward(
+        reward_config,
+        user_position.reward_weight()?,
+        user_position.reward_debt,
+    )?;
+    reward_calculator::refresh_loyalty_bonus(
+        pool_state,
+        reward_config,
+        user_position,
+        clock.unix_timestamp,
+    )?;
+    user_position.reward_debt = (user_position.reward_weight()? as u128)
+        .checked_mul(reward_config.acc_reward_per_share)
+        .ok_or(LpStakingError::MathOverflow)?;
+
     // 3. 计算总待领取奖励（历史累积 + 当前质押）
     let total_pending = user_position.pending_reward
         .checked_add(pending_from_staked)
         .ok_or(LpStakingError::MathOverflow)?;
-    
-    require!(total_pending > 0, LpStakingError::NoRewardToClaim);
-    
-    // 4. 检查奖励金库余额
-    let reward_vault_balance = ctx.accounts.reward_vault.lamports();
-    require!(
-        reward_vault_balance >= total_pending,
-        LpStakingError::InsufficientRewardVault
-    );
-    
-    // 5. 从奖励金库转账 SOL 给用户
-    // 使用 PDA seeds 签名从 reward_vault 转账到用户
-    let pool_state_key = ctx.accounts.pool_state.key();
-    let seeds = &[
-        REWARD_VAULT_SEED,
-        pool_state_key.as_ref(),
-        &[ctx.bumps.reward_vault],
-    ];
-    let signer_seeds = &[&seeds[..]];
-    
-    let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
-        &ctx.accounts.reward_vault.key(),
-        &ctx.accounts.user.key(),
+    user_position.pending_reward = total_pending;
+
+    if total_pending == 0 {
+        return Ok(ClaimResult {
+            amount: 0,
+            referrer: user_position.referrer,
+            referral_reward: 0,
+            shortfall: 0,
+        });
+    }
+
+    // 4. 按金库可用余额确定本次支付数量（为推荐奖励预留比例）
+    let referral_bps = if user_position.referrer != Pubkey::default() {
+        reward_config.referral_bps
+    } else {
+        0
+    };
+    let available = reward_vault.available()?;
+    let plan = reward_calculator::plan_claim(
         total_pending,
-    );
-    
-    anchor_lang::solana_program::program::invoke_signed(
-        &transfer_ix,
-        &[
-            ctx.accounts.reward_vault.to_account_info(),
-            ctx.accounts.user.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-        ],
-        signer_seeds,
+        available,
+        referral_bps,
+        reward_config.unemitted(),
     )?;
-    
-    // 6. 清零待领取奖励
-    user_position.pending_reward = 0;
-    
-    // 7. 更新 reward_debt（如果还有质押）
-    if user_position.staked_amount > 0 {
-        user_position.reward_debt = (user_position.staked_amount as u128)
-            .checked_mul(reward_config.acc_reward_per_share)
-            .ok_or(LpStakingError::MathOverflow)?;
+    let (amount, referral_reward, shortfall) = (plan.amount, plan.referral_reward, plan.shortfall);
+
+    if shortfall > 0 {
+        emit!(RewardShortfallEvent {
+            owner: user_position.owner,
+            requested: total_pending,
+            paid: amount,
+            shortfall,
+            vault_available: available,
+            timestamp: clock.unix_timestamp,
+        });
+        msg!("Reward vault underfunded, {} lamports left pending", shortfall);
     }
-    
-    // 8. 更新领取时间
+
+    // 金库为空：奖励全部保留在 pending_reward 中
+    if amount == 0 {
+        return Ok(ClaimResult {
+            amount: 0,
+            referrer: user_position.referrer,
+            referral_reward: 0,
+            shortfall,
+        });
+    }
+
+    // 5. 推荐奖励额外从排放中支付
+    reward_config.record_emission(referral_reward)?;
+    let total_payout = amount
+        .checked_add(referral_reward)
+        .ok_or(LpStakingError::MathOverflow)?;
+
+    // 6. 从奖励金库转账 SOL 给接收方与推荐人
+    reward_vault.transfer_to(recipient, amount)?;
+    if referral_reward > 0 {
+        let referrer = referrer.ok_or(LpStakingError::ReferrerMismatch)?;
+        require_keys_eq!(referrer.key(), user_position.referrer, LpStakingError::ReferrerMismatch);
+        reward_vault.transfer_to(referrer, referral_reward)?;
+    }
+
+    // 7. 记录支付并校验奖励不变量，未支付部分保留为待领取奖励
+    reward_config.record_payout(total_payout)?;
+    user_position.pending_reward = shortfall;
+    pool_state.total_rewards_claimed = pool_state.total_rewards_claimed
+        .checked_add(total_payout)
+        .ok_or(LpStakingError::MathOverflow)?;
+    pool_state.record_activity(clock.slot);
+
+    // 8. 更新领取时间与累计统计
     user_position.last_claim_time = clock.unix_timestamp;
-    
+    user_position.record_claim(amount)?;
+
     msg!("Claim successful!");
-    msg!("User: {}", ctx.accounts.user.key());
-    msg!("Reward claimed: {} lamports", total_pending);
-    msg!("Reward vault remaining: {} lamports", reward_vault_balance - total_pending);
-    
-    Ok(())
+    msg!("User: {}", user_position.owner);
+    msg!("Recipient: {}", recipient.key());
+    msg!("Reward claimed: {} lamports", amount);
+    if referral_reward > 0 {
+        msg!("Referral reward: {} lamports to {}", referral_reward, user_position.referrer);
+    }
+    msg!("Reward vault available: {} lamports", available - total_payout);
+
+    Ok(ClaimResult {
+        amount,
+        referrer: user_position.referrer,
+        referral_reward,
+        shortfall,
+    })
 }
 
 #[derive(Accounts)]
 pub struct Claim<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    
+
     #[account(
+        mut,
         seeds = [POOL_STATE_SEED],
         bump = pool_state.bump,
     )]
     pub pool_state: Account<'info, PoolState>,
-    
+
     #[account(
         mut,
         seeds = [
@@ -113,14 +254,14 @@ pub struct Claim<'info> {
         constraint = user_position.owner == user.key() @ LpStakingError::Unauthorized,
     )]
     pub user_position: Account<'info, UserPosition>,
-    
+
     #[account(
         mut,
         seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
         bump = reward_config.bump,
     )]
     pub reward_config: Account<'info, RewardConfig>,
-    
+
     /// 奖励金库（PDA，存放 SOL 奖励）
     /// CHECK: 这是一个 PDA，用于存放奖励 SOL
     #[account(
@@ -129,6 +270,24 @@ pub struct Claim<'info> {
         bump,
     )]
     pub reward_vault: AccountInfo<'info>,
-    
+
+    /// 推荐人钱包（仓位记录了推荐人且推荐比例非零时必须传入）
+    /// CHECK: 地址必须与仓位记录的推荐人一致，仅接收 SOL
+    #[account(
+        mut,
+        address = user_position.referrer @ LpStakingError::ReferrerMismatch,
+    )]
+    pub referrer: Option<UncheckedAccount<'info>>,
+
     pub system_program: Program<'info, System>,
 }
+
+#[derive(Accounts)]
+pub struct ClaimTo<'info> {
+    pub claim: Claim<'info>,
+
+    /// 奖励接收地址（例如冷钱包）
+    /// CHECK: 仅接收 SOL，无需校验
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+}
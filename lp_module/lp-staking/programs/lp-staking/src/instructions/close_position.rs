@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::PositionClosedEvent;
+use crate::state::{PoolState, UserPosition};
+
+/// 关闭空仓位，退还租金给用户
+///
+/// 仅当 lp_balance、staked_amount 与 pending_reward 均为零时允许关闭。
+/// Anchor 的 `close` 会转出全部 lamports、将账户归还给 System Program 并清空数据，
+/// 之后即使有人在同一交易中重新注入 lamports，该地址也不再是本程序的账户，
+/// 下一次 deposit 只能通过 init_if_needed 从零重新初始化仓位。
+/// 记录了推荐人的仓位不允许关闭，避免关闭后重新创建来更换或清除推荐人。
+pub fn close_position_handler(ctx: Context<ClosePosition>) -> Result<()> {
+    let user_position = &ctx.accounts.user_position;
+    
+    require!(
+        user_position.lp_balance == 0
+            && user_position.staked_amount == 0
+            && user_position.pending_reward == 0,
+        LpStakingError::PositionNotEmpty
+    );
+    require!(
+        user_position.referrer == Pubkey::default(),
+        LpStakingError::ReferredPositionNotClosable
+    );
+    
+    let rent_refunded = user_position.to_account_info().lamports();
+    
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.active_positions = pool_state.active_positions
+        .checked_sub(1)
+        .ok_or(LpStakingError::MathOverflow)?;
+    pool_state.record_activity(Clock::get()?.slot);
+    
+    emit!(PositionClosedEvent {
+        user: ctx.accounts.user.key(),
+        position: user_position.key(),
+        rent_refunded,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Position closed!");
+    msg!("User: {}", ctx.accounts.user.key());
+    msg!("Rent refunded: {} lamports", rent_refunded);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key() @ LpStakingError::Unauthorized,
+        close = user,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
@@ -3,29 +3,99 @@ use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer, MintTo};
 use crate::constants::*;
 use crate::errors::LpStakingError;
 use crate::state::{PoolState, UserPosition};
+use crate::utils::require_pegged;
 
 /// 存入 wrappedUSDC，获得 LP Token
-pub fn deposit_handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+///
+/// * `min_lp_out` - 最少应获得的 LP Token 数量（滑点保护）
+/// * `expiry_ts` - 交易截止时间（Unix 时间戳），超时则拒绝
+/// * `referrer` - 可选推荐人，仅在仓位首次记录时生效
+pub fn deposit_handler(
+    ctx: Context<Deposit>,
+    amount: u64,
+    min_lp_out: u64,
+    expiry_ts: i64,
+    referrer: Option<Pubkey>,
+) -> Result<()> {
+    let user_position_bump = ctx.bumps.user_position;
+    let accounts = ctx.accounts;
+    
+    process_deposit(
+        DepositAccounts {
+            user: &accounts.user,
+            owner: accounts.user.key(),
+            pool_state: &mut accounts.pool_state,
+            user_position: &mut accounts.user_position,
+            user_usdc_account: &accounts.user_usdc_account,
+            pool_usdc_account: &accounts.pool_usdc_account,
+            lp_token_mint: &accounts.lp_token_mint,
+            user_lp_account: &accounts.user_lp_account,
+            token_program: &accounts.token_program,
+            price_feed: accounts.price_feed.as_ref().map(|feed| feed.as_ref()),
+        },
+        user_position_bump,
+        amount,
+        min_lp_out,
+        expiry_ts,
+        referrer,
+    )?;
+    
+    Ok(())
+}
+
+/// 存入流程涉及的账户（deposit、deposit_and_stake 与 deposit_for 共用）
+pub struct DepositAccounts<'a, 'info> {
+    /// 出资并签名转出 USDC 的账户
+    pub user: &'a Signer<'info>,
+    /// 仓位所有者（代存时为受益人）
+    pub owner: Pubkey,
+    pub pool_state: &'a mut Account<'info, PoolState>,
+    pub user_position: &'a mut Account<'info, UserPosition>,
+    pub user_usdc_account: &'a Account<'info, TokenAccount>,
+    pub pool_usdc_account: &'a Account<'info, TokenAccount>,
+    pub lp_token_mint: &'a Account<'info, Mint>,
+    pub user_lp_account: &'a Account<'info, TokenAccount>,
+    pub token_program: &'a Program<'info, Token>,
+    pub price_feed: Option<&'a AccountInfo<'info>>,
+}
+
+/// 存入核心逻辑：转入 USDC、铸造 LP Token 并更新池子与仓位
+///
+/// 返回铸造的 LP Token 数量
+pub fn process_deposit(
+    accounts: DepositAccounts,
+    user_position_bump: u8,
+    amount: u64,
+    min_lp_out: u64,
+    expiry_ts: i64,
+    referrer: Option<Pubkey>,
+) -> Result<u64> {
     // 初始化用户仓位（如果是首次）
-    let user_position = &mut ctx.accounts.user_position;
-    
-    // 只在首次创建时初始化
-    if user_position.owner == Pubkey::default() {
-        user_position.owner = ctx.accounts.user.key();
-        user_position.pool = ctx.accounts.pool_state.key();
-        user_position.lp_balance = 0;
-        user_position.staked_amount = 0;
-        user_position.reward_debt = 0;
-        user_position.pending_reward = 0;
-        user_position.last_stake_time = 0;
-        user_position.last_claim_time = 0;
-        user_position.bump = ctx.bumps.user_position;
+    let user_position = accounts.user_position;
+    
+    // 只在首次创建时初始化（包括 close_position 之后重新创建的仓位）
+    let new_position = user_position.owner == Pubkey::default();
+    if new_position {
+        user_position.reset(
+            accounts.owner,
+            accounts.pool_state.key(),
+            user_position_bump,
+        );
     }
+    user_position.record_referrer(referrer)?;
     
     // 参数验证
     require!(amount >= MIN_DEPOSIT_AMOUNT, LpStakingError::InvalidAmount);
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    require!(now <= expiry_ts, LpStakingError::TransactionExpired);
+    
+    let pool_state = accounts.pool_state;
     
-    let pool_state = &mut ctx.accounts.pool_state;
+    // 暂停与脱锚检查
+    require!(!pool_state.paused, LpStakingError::PoolPaused);
+    require!(pool_state.flash_loan_amount == 0, LpStakingError::FlashLoanActive);
+    require_pegged(pool_state, accounts.price_feed, now)?;
     
     // 计算应该铸造的 LP Token 数量
     let lp_amount = calculate_lp_amount(
@@ -33,14 +103,15 @@ pub fn deposit_handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         pool_state.total_deposited,
         pool_state.total_lp_supply,
     )?;
+    require!(lp_amount >= min_lp_out, LpStakingError::SlippageExceeded);
     
     // 1. 将用户的 wrappedUSDC 转入池子账户
     let transfer_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
+        accounts.token_program.to_account_info(),
         Transfer {
-            from: ctx.accounts.user_usdc_account.to_account_info(),
-            to: ctx.accounts.pool_usdc_account.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
+            from: accounts.user_usdc_account.to_account_info(),
+            to: accounts.pool_usdc_account.to_account_info(),
+            authority: accounts.user.to_account_info(),
         },
     );
     token::transfer(transfer_ctx, amount)?;
@@ -53,10 +124,10 @@ pub fn deposit_handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
     let signer = &[&seeds[..]];
     
     let mint_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
+        accounts.token_program.to_account_info(),
         MintTo {
-            mint: ctx.accounts.lp_token_mint.to_account_info(),
-            to: ctx.accounts.user_lp_account.to_account_info(),
+            mint: accounts.lp_token_mint.to_account_info(),
+            to: accounts.user_lp_account.to_account_info(),
             authority: pool_state.to_account_info(),
         },
         signer,
@@ -71,21 +142,31 @@ pub fn deposit_handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         .checked_add(lp_amount)
         .ok_or(LpStakingError::MathOverflow)?;
     
-    // 4. 更新用户仓位
+    // 4. 更新统计数据
+    pool_state.cumulative_deposited = pool_state.cumulative_deposited
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    if new_position {
+        pool_state.open_position()?;
+    }
+    pool_state.record_activity(clock.slot);
+    
+    // 5. 更新用户仓位
     user_position.lp_balance = user_position.lp_balance
         .checked_add(lp_amount)
         .ok_or(LpStakingError::MathOverflow)?;
+    user_position.record_deposit(amount, now)?;
     
     msg!("Deposit successful!");
-    msg!("User: {}", ctx.accounts.user.key());
+    msg!("User: {}", accounts.owner);
     msg!("Deposited USDC: {}", amount);
     msg!("Minted LP Token: {}", lp_amount);
     
-    Ok(())
+    Ok(lp_amount)
 }
 
 /// 计算应该铸造的 LP Token 数量
-fn calculate_lp_amount(
+pub fn calculate_lp_amount(
     deposit_amount: u64,
     total_deposited: u64,
     total_lp_supply: u64,
@@ -126,7 +207,9 @@ pub struct Deposit<'info> {
             user.key().as_ref(),
             pool_state.key().as_ref()
         ],
-        bump
+        bump,
+        constraint = user_position.owner == Pubkey::default()
+            || user_position.owner == user.key() @ LpStakingError::Unauthorized,
     )]
     pub user_position: Account<'info, UserPosition>,
     
@@ -158,5 +241,9 @@ pub struct Deposit<'info> {
     
     pub token_program: Program<'info, Token>,
     
+    /// 价格账户（池子启用脱锚保护时必须传入）
+    /// CHECK: 地址必须与池子配置一致，布局在 handler 中校验
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    
     pub system_program: Program<'info, System>,
 }
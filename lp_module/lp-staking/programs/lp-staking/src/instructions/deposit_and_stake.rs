@@ -0,0 +1,176 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::DepositAndStakeEvent;
+use crate::instructions::deposit::{process_deposit, DepositAccounts};
+use crate::instructions::stake::process_stake;
+use crate::state::{PoolState, UserPosition, RewardConfig, StakeHistory};
+use crate::utils::StakeHistoryRecorder;
+
+/// 存入 wrappedUSDC 并将铸造的 LP Token 全部质押（原子操作）
+pub fn deposit_and_stake_handler(
+    ctx: Context<DepositAndStake>,
+    amount: u64,
+    min_lp_out: u64,
+    expiry_ts: i64,
+    referrer: Option<Pubkey>,
+) -> Result<()> {
+    let user_position_bump = ctx.bumps.user_position;
+    let accounts = ctx.accounts;
+    let clock = Clock::get()?;
+    
+    // 1. 存入并铸造 LP Token
+    let lp_amount = process_deposit(
+        DepositAccounts {
+            user: &accounts.user,
+            owner: accounts.user.key(),
+            pool_state: &mut accounts.pool_state,
+            user_position: &mut accounts.user_position,
+            user_usdc_account: &accounts.user_usdc_account,
+            pool_usdc_account: &accounts.pool_usdc_account,
+            lp_token_mint: &accounts.lp_token_mint,
+            user_lp_account: &accounts.user_lp_account,
+            token_program: &accounts.token_program,
+            price_feed: accounts.price_feed.as_ref().map(|feed| feed.as_ref()),
+        },
+        user_position_bump,
+        amount,
+        min_lp_out,
+        expiry_ts,
+        referrer,
+    )?;
+    
+    // 2. 质押新铸造的 LP Token
+    process_stake(
+        &mut accounts.pool_state,
+        &mut accounts.user_position,
+        &mut accounts.reward_config,
+        lp_amount,
+        None,
+        &clock,
+    )?;
+    
+    StakeHistoryRecorder {
+        payer: &accounts.user.to_account_info(),
+        system_program: accounts.system_program.as_ref(),
+        pool: accounts.pool_state.key(),
+        slot: clock.slot,
+    }
+    .record_stake_change(
+        &mut accounts.user_stake_history,
+        ctx.bumps.user_stake_history,
+        &mut accounts.global_stake_history,
+        ctx.bumps.global_stake_history,
+        &accounts.user_position,
+        &mut accounts.pool_state,
+    )?;
+    
+    emit!(DepositAndStakeEvent {
+        user: accounts.user.key(),
+        usdc_amount: amount,
+        lp_amount,
+        staked_amount: accounts.user_position.staked_amount,
+        pool_total_staked: accounts.pool_state.total_staked,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositAndStake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [
+            USER_POSITION_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+        constraint = user_position.owner == Pubkey::default()
+            || user_position.owner == user.key() @ LpStakingError::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 用户质押量历史
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub user_stake_history: Account<'info, StakeHistory>,
+    
+    /// 全局质押量历史的当前分页
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &pool_state.stake_history_page.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub global_stake_history: Account<'info, StakeHistory>,
+    
+    #[account(
+        mut,
+        token::mint = pool_state.wrapped_usdc_mint,
+        token::authority = user,
+    )]
+    pub user_usdc_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool_state.pool_usdc_account,
+    )]
+    pub pool_usdc_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool_state.lp_token_mint,
+    )]
+    pub lp_token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = lp_token_mint,
+        token::authority = user,
+    )]
+    pub user_lp_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    /// 价格账户（池子启用脱锚保护时必须传入）
+    /// CHECK: 地址必须与池子配置一致，布局在 handler 中校验
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,231 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar,
+    load_current_index_checked,
+    load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::FlashLoanEvent;
+use crate::state::PoolState;
+
+/// 设置闪电贷手续费（仅管理员）
+pub fn set_flash_loan_fee_handler(ctx: Context<SetFlashLoanFee>, fee_bps: u64) -> Result<()> {
+    require!(
+        fee_bps <= MAX_FLASH_LOAN_FEE_BPS,
+        LpStakingError::InvalidFlashLoanFee
+    );
+    
+    let pool_state = &mut ctx.accounts.pool_state;
+    let previous = pool_state.flash_loan_fee_bps;
+    pool_state.flash_loan_fee_bps = fee_bps;
+    
+    msg!("Flash loan fee updated!");
+    msg!("Fee: {} -> {} bps", previous, fee_bps);
+    
+    Ok(())
+}
+
+/// 闪电贷：从池子借出 wrappedUSDC
+///
+/// 同一交易中后续必须存在针对本池子的 flash_repay 指令，归还本金与手续费；
+/// 借款未归还期间禁止存入与提取
+pub fn flash_loan_handler(ctx: Context<FlashLoan>, amount: u64) -> Result<()> {
+    require!(amount > 0, LpStakingError::InvalidAmount);
+    
+    let accounts = ctx.accounts;
+    let pool_state = &mut accounts.pool_state;
+    require!(!pool_state.paused, LpStakingError::PoolPaused);
+    require!(pool_state.flash_loan_amount == 0, LpStakingError::FlashLoanActive);
+    require!(
+        accounts.pool_usdc_account.amount >= amount,
+        LpStakingError::InsufficientLiquidity
+    );
+    
+    // 1. 检查同一交易中后续存在匹配的 flash_repay
+    require_flash_repay(&accounts.instructions, pool_state.key())?;
+    
+    // 2. 计算手续费并记录借款
+    let fee = calculate_flash_loan_fee(amount, pool_state.flash_loan_fee_bps)?;
+    pool_state.flash_loan_amount = amount;
+    pool_state.flash_loan_fee = fee;
+    
+    // 3. 从池子转出借款
+    let seeds = &[
+        POOL_STATE_SEED,
+        &[pool_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+    
+    let transfer_ctx = CpiContext::new_with_signer(
+        accounts.token_program.to_account_info(),
+        Transfer {
+            from: accounts.pool_usdc_account.to_account_info(),
+            to: accounts.borrower_usdc_account.to_account_info(),
+            authority: pool_state.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)?;
+    
+    msg!("Flash loan issued!");
+    msg!("Borrower: {}", accounts.borrower.key());
+    msg!("Amount: {}, fee: {}", amount, fee);
+    
+    Ok(())
+}
+
+/// 归还闪电贷：转回本金与手续费，手续费计入 total_deposited 归 LP 所有
+pub fn flash_repay_handler(ctx: Context<FlashRepay>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let pool_state = &mut accounts.pool_state;
+    
+    let amount = pool_state.flash_loan_amount;
+    let fee = pool_state.flash_loan_fee;
+    require!(amount > 0, LpStakingError::NoFlashLoanOutstanding);
+    
+    let repay_amount = amount
+        .checked_add(fee)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    // 1. 转回本金与手续费
+    let transfer_ctx = CpiContext::new(
+        accounts.token_program.to_account_info(),
+        Transfer {
+            from: accounts.repayer_usdc_account.to_account_info(),
+            to: accounts.pool_usdc_account.to_account_info(),
+            authority: accounts.repayer.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, repay_amount)?;
+    
+    // 2. 手续费计入池子资产，清除借款记录
+    pool_state.total_deposited = pool_state.total_deposited
+        .checked_add(fee)
+        .ok_or(LpStakingError::MathOverflow)?;
+    pool_state.flash_loan_amount = 0;
+    pool_state.flash_loan_fee = 0;
+    pool_state.record_activity(Clock::get()?.slot);
+    
+    emit!(FlashLoanEvent {
+        repayer: accounts.repayer.key(),
+        amount,
+        fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Flash loan repaid!");
+    msg!("Principal: {}, fee: {}", amount, fee);
+    
+    Ok(())
+}
+
+/// 计算闪电贷手续费（向上取整）
+pub fn calculate_flash_loan_fee(amount: u64, fee_bps: u64) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(LpStakingError::MathOverflow)?
+        .div_ceil(BASIS_POINTS as u128);
+    
+    u64::try_from(fee).map_err(|_| LpStakingError::MathOverflow.into())
+}
+
+/// 通过 instructions sysvar 检查：当前指令为顶层调用，且其后存在针对同一池子的 flash_repay
+fn require_flash_repay(instructions: &AccountInfo, pool_state: Pubkey) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)? as usize;
+    
+    // 禁止通过 CPI 调用，否则无法可靠检查后续指令
+    let current = load_instruction_at_checked(current_index, instructions)?;
+    require_keys_eq!(current.program_id, crate::ID, LpStakingError::FlashLoanCpiNotAllowed);
+    
+    let mut index = current_index + 1;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        if ix.program_id == crate::ID {
+            if ix.data.starts_with(crate::instruction::FlashLoan::DISCRIMINATOR) {
+                break;
+            }
+            // FlashRepay 账户顺序：repayer, pool_state, ...
+            if ix.data.starts_with(crate::instruction::FlashRepay::DISCRIMINATOR)
+                && ix.accounts.get(1).map(|meta| meta.pubkey) == Some(pool_state)
+            {
+                return Ok(());
+            }
+        }
+        index += 1;
+    }
+    
+    err!(LpStakingError::MissingFlashRepay)
+}
+
+#[derive(Accounts)]
+pub struct SetFlashLoanFee<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+}
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    pub borrower: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        address = pool_state.pool_usdc_account,
+    )]
+    pub pool_usdc_account: Account<'info, TokenAccount>,
+    
+    /// 接收借款的账户
+    #[account(
+        mut,
+        token::mint = pool_state.wrapped_usdc_mint,
+    )]
+    pub borrower_usdc_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: instructions sysvar，由 address 约束校验
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+    pub repayer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        address = pool_state.pool_usdc_account,
+    )]
+    pub pool_usdc_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = pool_state.wrapped_usdc_mint,
+        token::authority = repayer,
+    )]
+    pub repayer_usdc_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, System, Transfer};
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::RewardsFundedEvent;
+use crate::state::{PoolState, RewardConfig};
+use crate::utils::{ensure_rent_exempt, reward_calculator, RewardVault};
+
+/// 向奖励金库注入资金（任何人都可调用）
+///
+/// 排放量以 total_funded 为上限，只有通过本指令记账的资金才会被排放。
+/// 直接转入金库的 lamports 会在下一次调用时一并记账（升级前已有的余额由 migrate_pool 记账）
+pub fn fund_rewards_handler(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let reward_config = &mut ctx.accounts.reward_config;
+    
+    // 1. 先按原有资金结算排放，新资金不追溯之前的区间
+    reward_calculator::update_pool_reward(
+        &ctx.accounts.pool_state,
+        reward_config,
+        clock.slot,
+    )?;
+    
+    // 2. 金库低于免租最低余额时（升级前创建的池子）由注资者补足，不计入注资
+    let funder = ctx.accounts.funder.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    ensure_rent_exempt(&ctx.accounts.reward_vault, &funder, &system_program, 0)?;
+    
+    // 3. 识别未记账的余额（可用余额超出 已注入 - 已支付 的部分）
+    let reward_vault = RewardVault {
+        vault: &ctx.accounts.reward_vault,
+        pool_state: ctx.accounts.pool_state.key(),
+        bump: ctx.bumps.reward_vault,
+        system_program: &system_program,
+    };
+    let outstanding = reward_config.total_funded
+        .checked_sub(reward_config.total_claimed)
+        .ok_or(LpStakingError::RewardInvariantViolated)?;
+    let unrecorded = reward_vault.available()?.saturating_sub(outstanding);
+    
+    // 4. 转入新资金
+    if amount > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: funder,
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+    
+    // 5. 记账
+    let funded = amount
+        .checked_add(unrecorded)
+        .ok_or(LpStakingError::MathOverflow)?;
+    require!(funded > 0, LpStakingError::InvalidAmount);
+    reward_config.total_funded = reward_config.total_funded
+        .checked_add(funded)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    emit!(RewardsFundedEvent {
+        funder: ctx.accounts.funder.key(),
+        amount,
+        unrecorded,
+        total_funded: reward_config.total_funded,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Rewards funded!");
+    msg!("Funder: {}", ctx.accounts.funder.key());
+    msg!("Amount: {} lamports", amount);
+    msg!("Unrecorded balance: {} lamports", unrecorded);
+    msg!("Total funded: {} lamports", reward_config.total_funded);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 奖励金库（PDA，存放 SOL 奖励）
+    /// CHECK: 这是一个 PDA，用于存放奖励 SOL
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
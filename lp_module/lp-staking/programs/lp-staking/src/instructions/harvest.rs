@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::System;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::RewardClaimedEvent;
+use crate::instructions::claim::process_claim;
+use crate::state::{PoolState, UserPosition, RewardConfig};
+use crate::utils::RewardVault;
+
+/// 设置或清除收获代理（仅仓位所有者）
+///
+/// 代理只能把奖励领取到仓位所有者的钱包，不能改变接收地址
+pub fn set_harvest_delegate_handler(
+    ctx: Context<SetHarvestDelegate>,
+    delegate: Option<Pubkey>,
+) -> Result<()> {
+    let user_position = &mut ctx.accounts.user_position;
+    user_position.harvest_delegate = delegate.unwrap_or_default();
+    
+    msg!("Harvest delegate updated!");
+    msg!("User: {}", user_position.owner);
+    msg!("Delegate: {}", user_position.harvest_delegate);
+    
+    Ok(())
+}
+
+/// 收获代理批量领取奖励
+///
+/// remaining_accounts 按 `[user_position(w), owner(w), referrer(w)]` 三个一组传入，
+/// 奖励转入各自的 owner 钱包。仓位没有推荐人时 referrer 可传任意账户（例如 owner）。
+/// 没有可领取奖励的仓位会被跳过。
+pub fn harvest_many_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, HarvestMany<'info>>,
+) -> Result<()> {
+    let remaining = ctx.remaining_accounts;
+    require!(
+        !remaining.is_empty() && remaining.len() % 3 == 0,
+        LpStakingError::InvalidHarvestAccounts
+    );
+    
+    let clock = Clock::get()?;
+    let delegate = ctx.accounts.delegate.key();
+    let pool_key = ctx.accounts.pool_state.key();
+    let reward_vault = RewardVault {
+        vault: &ctx.accounts.reward_vault,
+        pool_state: pool_key,
+        bump: ctx.bumps.reward_vault,
+        system_program: ctx.accounts.system_program.as_ref(),
+    };
+    
+    let mut total_claimed: u64 = 0;
+    let mut positions_harvested: u32 = 0;
+    
+    for group in remaining.chunks(3) {
+        let (position_info, owner_info, referrer_info) = (&group[0], &group[1], &group[2]);
+        
+        // 1. 校验仓位账户（owner 程序、discriminator、PDA 地址）
+        let mut user_position: Account<'info, UserPosition> = Account::try_from(position_info)?;
+        let expected = Pubkey::create_program_address(
+            &[
+                USER_POSITION_SEED,
+                user_position.owner.as_ref(),
+                pool_key.as_ref(),
+                &[user_position.bump],
+            ],
+            &crate::ID,
+        ).map_err(|_| LpStakingError::InvalidHarvestAccounts)?;
+        require_keys_eq!(expected, position_info.key(), LpStakingError::InvalidHarvestAccounts);
+        require_keys_eq!(user_position.owner, owner_info.key(), LpStakingError::InvalidHarvestAccounts);
+        require!(position_info.is_writable, LpStakingError::InvalidHarvestAccounts);
+        
+        // 2. 校验代理授权
+        require!(
+            user_position.harvest_delegate != Pubkey::default()
+                && user_position.harvest_delegate == delegate,
+            LpStakingError::Unauthorized
+        );
+        
+        // 3. 领取到 owner 钱包
+        let result = process_claim(
+            &mut ctx.accounts.pool_state,
+            &mut ctx.accounts.reward_config,
+            &mut user_position,
+            &reward_vault,
+            owner_info,
+            Some(referrer_info),
+            &clock,
+        )?;
+        
+        // 4. 写回仓位数据（无奖励时 reward_debt 也可能已更新）
+        user_position.exit(&crate::ID)?;
+        if result.amount == 0 {
+            continue;
+        }
+        
+        emit!(RewardClaimedEvent {
+            owner: owner_info.key(),
+            recipient: owner_info.key(),
+            caller: delegate,
+            amount: result.amount,
+            referrer: result.referrer,
+            referral_reward: result.referral_reward,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        total_claimed = total_claimed
+            .checked_add(result.amount)
+            .ok_or(LpStakingError::MathOverflow)?;
+        positions_harvested += 1;
+    }
+    
+    msg!("Harvest many successful!");
+    msg!("Delegate: {}", delegate);
+    msg!("Positions harvested: {}", positions_harvested);
+    msg!("Total claimed: {} lamports", total_claimed);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetHarvestDelegate<'info> {
+    pub user: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key() @ LpStakingError::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestMany<'info> {
+    pub delegate: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 奖励金库（PDA，存放 SOL 奖励）
+    /// CHECK: 这是一个 PDA，用于存放奖励 SOL
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
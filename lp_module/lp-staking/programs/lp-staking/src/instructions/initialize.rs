@@ -3,6 +3,7 @@ use anchor_spl::token::{Mint, Token, TokenAccount};
 use crate::constants::*;
 use crate::errors::LpStakingError;
 use crate::state::{EmissionType, PoolState, RewardConfig};
+use crate::utils::ensure_rent_exempt;
 
 /// 初始化流动性池和质押系统
 /// 
@@ -16,22 +17,14 @@ pub fn initialize_handler(
     blocks_per_period: u64,
 ) -> Result<()> {
     // 验证参数
-    if emission_type == EmissionType::BlockBased {
-        require!(
-            decay_factor <= BASIS_POINTS,
-            LpStakingError::InvalidDecayFactor
-        );
-        require!(
-            blocks_per_period > 0,
-            LpStakingError::InvalidBlocksPerPeriod
-        );
-    }
+    validate_emission_params(emission_type, decay_factor, blocks_per_period)?;
     
     let pool_state = &mut ctx.accounts.pool_state;
     let reward_config = &mut ctx.accounts.reward_config;
     let clock = Clock::get()?;
     
     // 初始化池子状态
+    pool_state.version = PoolState::VERSION;
     pool_state.authority = ctx.accounts.authority.key();
     pool_state.wrapped_usdc_mint = ctx.accounts.wrapped_usdc_mint.key();
     pool_state.lp_token_mint = ctx.accounts.lp_token_mint.key();
@@ -41,8 +34,29 @@ pub fn initialize_handler(
     pool_state.total_staked = 0;
     pool_state.reward_vault = ctx.accounts.reward_vault.key();
     pool_state.bump = ctx.bumps.pool_state;
+    pool_state.snapshot_count = 0;
+    pool_state.treasury = Pubkey::default();
+    pool_state.guardian = Pubkey::default();
+    pool_state.outflow_limit_bps = 0;
+    pool_state.outflow_window_secs = 0;
+    pool_state.reset_outflow_window(clock.unix_timestamp);
+    pool_state.price_feed = Pubkey::default();
+    pool_state.peg_band_bps = 0;
+    pool_state.max_price_age_secs = 0;
+    pool_state.paused = false;
+    pool_state.flash_loan_fee_bps = 0;
+    pool_state.flash_loan_amount = 0;
+    pool_state.flash_loan_fee = 0;
+    pool_state.cumulative_deposited = 0;
+    pool_state.cumulative_withdrawn = 0;
+    pool_state.active_positions = 0;
+    pool_state.staker_count = 0;
+    pool_state.total_rewards_claimed = 0;
+    pool_state.last_activity_slot = clock.slot;
+    pool_state.total_loyalty_bonus = 0;
     
     // 初始化奖励配置
+    reward_config.version = RewardConfig::VERSION;
     reward_config.pool = pool_state.key();
     reward_config.emission_type = emission_type;
     reward_config.emission_rate = emission_rate;
@@ -52,6 +66,25 @@ pub fn initialize_handler(
     reward_config.last_update_slot = clock.slot;
     reward_config.acc_reward_per_share = 0;
     reward_config.bump = ctx.bumps.reward_config;
+    reward_config.referral_bps = 0;
+    reward_config.undistributed = 0;
+    reward_config.carry_over_undistributed = false;
+    reward_config.total_funded = 0;
+    reward_config.total_emitted = 0;
+    reward_config.total_claimed = 0;
+    reward_config.reward_dust = 0;
+    reward_config.protocol_fee_bps = 0;
+    reward_config.protocol_fees_accrued = 0;
+    reward_config.loyalty_max_bonus_bps = 0;
+    reward_config.loyalty_ramp_secs = 0;
+    
+    // 预先存入免租最低余额，金库 PDA 不会因余额归零被回收
+    ensure_rent_exempt(
+        &ctx.accounts.reward_vault,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        0,
+    )?;
     
     msg!("Liquidity Pool initialized!");
     msg!("Pool State: {}", pool_state.key());
@@ -61,6 +94,25 @@ pub fn initialize_handler(
     Ok(())
 }
 
+/// 校验排放参数（initialize 与 migrate_pool 共用）
+pub fn validate_emission_params(
+    emission_type: EmissionType,
+    decay_factor: u64,
+    blocks_per_period: u64,
+) -> Result<()> {
+    if emission_type == EmissionType::BlockBased {
+        require!(
+            decay_factor <= BASIS_POINTS,
+            LpStakingError::InvalidDecayFactor
+        );
+        require!(
+            blocks_per_period > 0,
+            LpStakingError::InvalidBlocksPerPeriod
+        );
+    }
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     /// 池子管理员（支付账户创建费用）
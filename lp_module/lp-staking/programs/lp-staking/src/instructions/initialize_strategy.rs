@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::state::{PoolState, StrategyState};
+
+/// 配置收益策略（仅管理员）
+///
+/// 绑定外部借贷程序及池子在其中的仓位账户，并设置最大分配比例
+pub fn initialize_strategy_handler(
+    ctx: Context<InitializeStrategy>,
+    max_allocation_bps: u64,
+) -> Result<()> {
+    require!(
+        max_allocation_bps <= BASIS_POINTS,
+        LpStakingError::InvalidAllocation
+    );
+    require!(
+        ctx.accounts.strategy_program.executable,
+        LpStakingError::InvalidStrategyProgram
+    );
+
+    let strategy_state = &mut ctx.accounts.strategy_state;
+
+    strategy_state.version = StrategyState::VERSION;
+    strategy_state.pool = ctx.accounts.pool_state.key();
+    strategy_state.strategy_program = ctx.accounts.strategy_program.key();
+    strategy_state.market = ctx.accounts.strategy_market.key();
+    strategy_state.market_vault = ctx.accounts.strategy_vault.key();
+    strategy_state.position = ctx.accounts.strategy_position.key();
+    strategy_state.max_allocation_bps = max_allocation_bps;
+    strategy_state.allocated = 0;
+    strategy_state.total_harvested = 0;
+    strategy_state.total_loss = 0;
+    strategy_state.last_harvest_slot = Clock::get()?.slot;
+    strategy_state.bump = ctx.bumps.strategy_state;
+
+    msg!("Strategy initialized!");
+    msg!("Strategy program: {}", strategy_state.strategy_program);
+    msg!("Max allocation: {} bps", max_allocation_bps);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeStrategy<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = StrategyState::LEN,
+        seeds = [STRATEGY_STATE_SEED, pool_state.key().as_ref()],
+        bump
+    )]
+    pub strategy_state: Account<'info, StrategyState>,
+
+    /// CHECK: 外部策略程序，需实现策略接口（见 StrategyAdapter）
+    pub strategy_program: UncheckedAccount<'info>,
+
+    /// CHECK: 外部程序的市场账户，由策略程序自行校验
+    #[account(owner = strategy_program.key() @ LpStakingError::InvalidStrategyProgram)]
+    pub strategy_market: UncheckedAccount<'info>,
+
+    /// CHECK: 外部程序的资金金库，由策略程序自行校验
+    pub strategy_vault: UncheckedAccount<'info>,
+
+    /// CHECK: 池子在外部程序中的仓位，由策略程序自行校验
+    #[account(owner = strategy_program.key() @ LpStakingError::InvalidStrategyProgram)]
+    pub strategy_position: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
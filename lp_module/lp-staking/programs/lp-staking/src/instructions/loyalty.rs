@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::state::{PoolState, RewardConfig, UserPosition};
+use crate::utils::reward_calculator;
+
+/// 设置忠诚度加成曲线（仅管理员）
+///
+/// 奖励权重 = 质押量 * (1 + max_bonus_bps * min(连续质押时长, ramp_secs) / ramp_secs / 10000)。
+/// max_bonus_bps 为 0 表示关闭；已记录的加成在仓位下次结算或 refresh_loyalty 时更新
+pub fn set_loyalty_curve_handler(
+    ctx: Context<SetLoyaltyCurve>,
+    max_bonus_bps: u64,
+    ramp_secs: i64,
+) -> Result<()> {
+    require!(
+        max_bonus_bps <= MAX_LOYALTY_BONUS_BPS && (max_bonus_bps == 0 || ramp_secs > 0),
+        LpStakingError::InvalidLoyaltyCurve
+    );
+    
+    // 先按旧权重结算此前的排放
+    let reward_config = &mut ctx.accounts.reward_config;
+    reward_calculator::update_pool_reward(
+        &ctx.accounts.pool_state,
+        reward_config,
+        Clock::get()?.slot,
+    )?;
+    
+    reward_config.loyalty_max_bonus_bps = max_bonus_bps;
+    reward_config.loyalty_ramp_secs = ramp_secs;
+    
+    msg!("Loyalty curve updated!");
+    msg!("Max bonus: {} bps, ramp: {} secs", max_bonus_bps, ramp_secs);
+    
+    Ok(())
+}
+
+/// 刷新仓位的忠诚度加成（无需权限）
+///
+/// 按原权重结算奖励到 pending_reward，再按当前连续质押时长与曲线重新计算加成
+pub fn refresh_loyalty_handler(ctx: Context<RefreshLoyalty>) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = &mut *ctx.accounts;
+    let pool_state = &mut accounts.pool_state;
+    let reward_config = &mut accounts.reward_config;
+    let user_position = &mut accounts.user_position;
+    
+    // 1. 更新奖励池并按原权重结算
+    reward_calculator::update_pool_reward(pool_state, reward_config, clock.slot)?;
+    let pending = reward_calculator::settle_pending_reward(
+        reward_config,
+        user_position.reward_weight()?,
+        user_position.reward_debt,
+    )?;
+    user_position.pending_reward = user_position.pending_reward
+        .checked_add(pending)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    // 2. 刷新加成并按新权重重置 reward_debt
+    let previous = user_position.loyalty_bonus;
+    reward_calculator::refresh_loyalty_bonus(
+        pool_state,
+        reward_config,
+        user_position,
+        clock.unix_timestamp,
+    )?;
+    user_position.reward_debt = (user_position.reward_weight()? as u128)
+        .checked_mul(reward_config.acc_reward_per_share)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    msg!("Loyalty bonus refreshed!");
+    msg!("User: {}", user_position.owner);
+    msg!("Bonus: {} -> {}", previous, user_position.loyalty_bonus);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetLoyaltyCurve<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshLoyalty<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            user_position.owner.as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::state::{PoolState, StrategyState};
+use crate::utils::StrategyAdapter;
+
+/// 将池子闲置资金分配给外部策略（仅管理员）
+pub fn allocate_to_strategy_handler(ctx: Context<ManageStrategy>, amount: u64) -> Result<()> {
+    require!(amount > 0, LpStakingError::InvalidAmount);
+
+    let strategy_state = &ctx.accounts.strategy_state;
+    let new_allocated = strategy_state.allocated
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+
+    // 分配上限基于池子总资产（闲置余额 + 策略头寸）
+    require!(
+        new_allocated <= strategy_state.max_allocation(ctx.accounts.pool_state.total_deposited),
+        LpStakingError::AllocationLimitExceeded
+    );
+    require!(
+        ctx.accounts.pool_usdc_account.amount >= amount,
+        LpStakingError::InsufficientLiquidity
+    );
+
+    let bump = ctx.accounts.pool_state.bump;
+    let seeds = &[POOL_STATE_SEED, &[bump]];
+    let signer = &[&seeds[..]];
+    ctx.accounts.with_adapter(|adapter| adapter.deposit(amount, signer))?;
+
+    ctx.accounts.strategy_state.allocated = new_allocated;
+
+    msg!("Allocated to strategy: {}", amount);
+    msg!("Strategy allocated total: {}", new_allocated);
+
+    Ok(())
+}
+
+/// 收获策略收益（仅管理员）
+///
+/// 收益取回池子并计入 total_deposited（提升 LP 价格）；
+/// 若仓位价值低于本金，则确认损失并下调 total_deposited
+pub fn harvest_strategy_handler(ctx: Context<ManageStrategy>) -> Result<()> {
+    let position_value = ctx.accounts.with_adapter(|adapter| adapter.position_value())?;
+    let allocated = ctx.accounts.strategy_state.allocated;
+    let clock = Clock::get()?;
+
+    if position_value > allocated {
+        let profit = position_value - allocated;
+
+        let bump = ctx.accounts.pool_state.bump;
+        let seeds = &[POOL_STATE_SEED, &[bump]];
+        let signer = &[&seeds[..]];
+        ctx.accounts.with_adapter(|adapter| adapter.withdraw(profit, signer))?;
+
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.total_deposited = pool_state.total_deposited
+            .checked_add(profit)
+            .ok_or(LpStakingError::MathOverflow)?;
+
+        let strategy_state = &mut ctx.accounts.strategy_state;
+        strategy_state.total_harvested = strategy_state.total_harvested
+            .checked_add(profit)
+            .ok_or(LpStakingError::MathOverflow)?;
+
+        msg!("Harvested profit: {}", profit);
+    } else if position_value < allocated {
+        let loss = allocated - position_value;
+
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.total_deposited = pool_state.total_deposited
+            .checked_sub(loss)
+            .ok_or(LpStakingError::MathOverflow)?;
+
+        let strategy_state = &mut ctx.accounts.strategy_state;
+        strategy_state.allocated = position_value;
+        strategy_state.total_loss = strategy_state.total_loss
+            .checked_add(loss)
+            .ok_or(LpStakingError::MathOverflow)?;
+
+        msg!("Realized strategy loss: {}", loss);
+    }
+
+    ctx.accounts.strategy_state.last_harvest_slot = clock.slot;
+
+    msg!("Strategy position value: {}", position_value);
+    msg!("Pool total assets: {}", ctx.accounts.pool_state.total_deposited);
+
+    Ok(())
+}
+
+/// 从外部策略召回本金到池子（仅管理员）
+pub fn recall_from_strategy_handler(ctx: Context<ManageStrategy>, amount: u64) -> Result<()> {
+    require!(amount > 0, LpStakingError::InvalidAmount);
+    require!(
+        ctx.accounts.strategy_state.allocated >= amount,
+        LpStakingError::InsufficientBalance
+    );
+
+    let bump = ctx.accounts.pool_state.bump;
+    let seeds = &[POOL_STATE_SEED, &[bump]];
+    let signer = &[&seeds[..]];
+    ctx.accounts.with_adapter(|adapter| adapter.withdraw(amount, signer))?;
+
+    let strategy_state = &mut ctx.accounts.strategy_state;
+    strategy_state.allocated = strategy_state.allocated
+        .checked_sub(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+
+    msg!("Recalled from strategy: {}", amount);
+    msg!("Strategy allocated remaining: {}", strategy_state.allocated);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ManageStrategy<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [STRATEGY_STATE_SEED, pool_state.key().as_ref()],
+        bump = strategy_state.bump,
+    )]
+    pub strategy_state: Account<'info, StrategyState>,
+
+    #[account(
+        mut,
+        address = pool_state.pool_usdc_account,
+    )]
+    pub pool_usdc_account: Account<'info, TokenAccount>,
+
+    /// CHECK: 地址由 strategy_state 约束
+    #[account(
+        address = strategy_state.strategy_program @ LpStakingError::InvalidStrategyProgram,
+        executable,
+    )]
+    pub strategy_program: UncheckedAccount<'info>,
+
+    /// CHECK: 地址由 strategy_state 约束
+    #[account(mut, address = strategy_state.market @ LpStakingError::InvalidStrategyProgram)]
+    pub strategy_market: UncheckedAccount<'info>,
+
+    /// CHECK: 地址由 strategy_state 约束
+    #[account(mut, address = strategy_state.market_vault @ LpStakingError::InvalidStrategyProgram)]
+    pub strategy_vault: UncheckedAccount<'info>,
+
+    /// CHECK: 地址由 strategy_state 约束
+    #[account(mut, address = strategy_state.position @ LpStakingError::InvalidStrategyProgram)]
+    pub strategy_position: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ManageStrategy<'info> {
+    fn with_adapter<T>(&self, f: impl FnOnce(&StrategyAdapter<'_, 'info>) -> Result<T>) -> Result<T> {
+        let strategy_program = self.strategy_program.to_account_info();
+        let market = self.strategy_market.to_account_info();
+        let market_vault = self.strategy_vault.to_account_info();
+        let position = self.strategy_position.to_account_info();
+        let pool_state = self.pool_state.to_account_info();
+        let pool_usdc_account = self.pool_usdc_account.to_account_info();
+        let token_program = self.token_program.to_account_info();
+
+        f(&StrategyAdapter {
+            strategy_program: &strategy_program,
+            market: &market,
+            market_vault: &market_vault,
+            position: &position,
+            pool_state: &pool_state,
+            pool_usdc_account: &pool_usdc_account,
+            token_program: &token_program,
+        })
+    }
+}
@@ -0,0 +1,421 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::AccountMigratedEvent;
+use crate::instructions::initialize::validate_emission_params;
+use crate::state::{
+    EmissionType, PoolState, PoolStateV0, RewardConfig, RewardConfigV0, StakeHistory,
+    UserPosition, UserPositionV0,
+};
+use crate::utils::{
+    create_pda_account, resize_account, write_account, RewardVault, StakeHistoryRecorder,
+};
+
+/// 账户数据布局
+enum Layout {
+    /// version 0：无版本字段的旧布局
+    Legacy,
+    /// 账户大小与当前版本一致的旧版本（新字段占用原预留空间，只需更新版本号）
+    ///
+    /// 新字段从预留空间划分时一律提升版本号，预留空间在旧版本中始终为零，
+    /// 即新字段的默认值；需要初始化的字段在对应的迁移分支中处理
+    Previous(u8),
+    /// 当前版本
+    Current,
+}
+
+/// 升级 v2 之前的池子时写入的初始统计（链下遍历现有 UserPosition 得到）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct LegacyStats {
+    /// 现有仓位数量
+    pub active_positions: u64,
+    /// 质押量大于零的仓位数量
+    pub staker_count: u64,
+    /// 已累计但尚未领取的奖励（lamports，各仓位 pending_reward 与未结算奖励之和）
+    pub rewards_owed: u64,
+}
+
+/// 根据 owner、discriminator 与数据长度识别账户布局
+fn detect_layout(
+    info: &AccountInfo,
+    discriminator: &[u8],
+    legacy_len: usize,
+    current_len: usize,
+    current_version: u8,
+) -> Result<Layout> {
+    require_keys_eq!(*info.owner, crate::ID, LpStakingError::UnsupportedAccountVersion);
+
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() > 8 && &data[..8] == discriminator,
+        LpStakingError::UnsupportedAccountVersion
+    );
+
+    if data.len() == legacy_len {
+        Ok(Layout::Legacy)
+    } else if data.len() == current_len && data[8] == current_version {
+        Ok(Layout::Current)
+    } else if data.len() == current_len && data[8] > 0 && data[8] < current_version {
+        Ok(Layout::Previous(data[8]))
+    } else {
+        err!(LpStakingError::UnsupportedAccountVersion)
+    }
+}
+
+/// 读取旧布局数据（跳过 discriminator）
+fn read_legacy<T: AnchorDeserialize>(info: &AccountInfo) -> Result<T> {
+    let data = info.try_borrow_data()?;
+    T::try_from_slice(&data[8..]).map_err(|_| LpStakingError::UnsupportedAccountVersion.into())
+}
+
+/// 升级池子账户到当前布局（仅管理员）
+///
+/// 1. PoolState：v0 则 realloc 并写入新布局，v1 则更新版本号
+/// 2. RewardConfig：不存在则按传入参数创建（见文档 12 的事故），v0 则 realloc 升级，v1 则更新版本号；
+///    升级时写入奖励记账初始值，之前的奖励可正常领取，金库余额继续排放
+/// 3. 全局质押量历史：第 0 页尚未创建时以当前总质押量写入第一个检查点
+///
+/// 已是当前版本的账户保持不变，指令可重复执行
+///
+/// * `legacy_stats` - 链下统计的升级前数据，仅在账户从 v2 之前的版本升级时写入：
+///   仓位数写入 PoolState，欠付奖励与金库可用余额写入 RewardConfig 的奖励记账；
+///   累计存取量与领取总额从升级时开始统计
+pub fn migrate_pool_handler(
+    ctx: Context<MigratePool>,
+    emission_type: EmissionType,
+    emission_rate: u64,
+    initial_block_rate: u64,
+    decay_factor: u64,
+    blocks_per_period: u64,
+    legacy_stats: LegacyStats,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let pool_info = ctx.accounts.pool_state.to_account_info();
+    let config_info = ctx.accounts.reward_config.to_account_info();
+    let reward_vault = RewardVault {
+        vault: &ctx.accounts.reward_vault,
+        pool_state: pool_info.key(),
+        bump: ctx.bumps.reward_vault,
+        system_program: &system_program,
+    };
+
+    // 1. 升级 PoolState
+    match detect_layout(
+        &pool_info,
+        PoolState::DISCRIMINATOR,
+        PoolStateV0::LEN,
+        PoolState::LEN,
+        PoolState::VERSION,
+    )? {
+        Layout::Legacy => {
+            let legacy: PoolStateV0 = read_legacy(&pool_info)?;
+            require_keys_eq!(legacy.authority, authority.key(), LpStakingError::Unauthorized);
+
+            let mut pool_state = legacy.upgrade();
+            pool_state.active_positions = legacy_stats.active_positions;
+            pool_state.staker_count = legacy_stats.staker_count;
+
+            resize_account(&pool_info, &authority, &system_program, PoolState::LEN)?;
+            write_account(&pool_info, &pool_state)?;
+
+            emit!(AccountMigratedEvent {
+                account: pool_info.key(),
+                from_version: 0,
+                to_version: PoolState::VERSION,
+            });
+            msg!("PoolState migrated to version {}", PoolState::VERSION);
+        },
+        Layout::Previous(from_version) => {
+            let mut pool_state =
+                PoolState::try_deserialize(&mut &pool_info.try_borrow_data()?[..])?;
+            require_keys_eq!(pool_state.authority, authority.key(), LpStakingError::Unauthorized);
+
+            pool_state.version = PoolState::VERSION;
+            if from_version < 2 {
+                pool_state.active_positions = legacy_stats.active_positions;
+                pool_state.staker_count = legacy_stats.staker_count;
+            }
+            write_account(&pool_info, &pool_state)?;
+
+            emit!(AccountMigratedEvent {
+                account: pool_info.key(),
+                from_version,
+                to_version: PoolState::VERSION,
+            });
+            msg!("PoolState migrated to version {}", PoolState::VERSION);
+        },
+        Layout::Current => {
+            let pool_state = PoolState::try_deserialize(&mut &pool_info.try_borrow_data()?[..])?;
+            require_keys_eq!(pool_state.authority, authority.key(), LpStakingError::Unauthorized);
+        },
+    }
+
+    // 2. 创建或升级 RewardConfig
+    if config_info.data_is_empty() {
+        validate_emission_params(emission_type, decay_factor, blocks_per_period)?;
+
+        let pool_key = pool_info.key();
+        let seeds = &[
+            REWARD_CONFIG_SEED,
+            pool_key.as_ref(),
+            &[ctx.bumps.reward_config],
+        ];
+        create_pda_account(
+            &authority,
+            &config_info,
+            &system_program,
+            RewardConfig::LEN,
+            &[&seeds[..]],
+        )?;
+
+        let mut reward_config = RewardConfig {
+            version: RewardConfig::VERSION,
+            pool: pool_key,
+            emission_type,
+            emission_rate,
+            initial_block_rate,
+            decay_factor,
+            blocks_per_period,
+            last_update_slot: Clock::get()?.slot,
+            acc_reward_per_share: 0,
+            bump: ctx.bumps.reward_config,
+            referral_bps: 0,
+            undistributed: 0,
+            carry_over_undistributed: false,
+            total_funded: 0,
+            total_emitted: 0,
+            total_claimed: 0,
+            reward_dust: 0,
+            protocol_fee_bps: 0,
+            protocol_fees_accrued: 0,
+            loyalty_max_bonus_bps: 0,
+            loyalty_ramp_secs: 0,
+            reserved: [0; 39],
+        };
+        reward_config.seed_accounting(reward_vault.available()?, 0);
+        write_account(&config_info, &reward_config)?;
+
+        msg!("RewardConfig created");
+        msg!("Emission Type: {:?}", emission_type);
+    } else {
+        match detect_layout(
+            &config_info,
+            RewardConfig::DISCRIMINATOR,
+            RewardConfigV0::LEN,
+            RewardConfig::LEN,
+            RewardConfig::VERSION,
+        )? {
+            Layout::Legacy => {
+                let legacy: RewardConfigV0 = read_legacy(&config_info)?;
+                let mut reward_config = legacy.upgrade();
+                reward_config.seed_accounting(reward_vault.available()?, legacy_stats.rewards_owed);
+
+                resize_account(&config_info, &authority, &system_program, RewardConfig::LEN)?;
+                write_account(&config_info, &reward_config)?;
+
+                emit!(AccountMigratedEvent {
+                    account: config_info.key(),
+                    from_version: 0,
+                    to_version: RewardConfig::VERSION,
+                });
+                msg!("RewardConfig migrated to version {}", RewardConfig::VERSION);
+            },
+            Layout::Previous(from_version) => {
+                let mut reward_config =
+                    RewardConfig::try_deserialize(&mut &config_info.try_borrow_data()?[..])?;
+
+                reward_config.version = RewardConfig::VERSION;
+                reward_config.seed_accounting(reward_vault.available()?, legacy_stats.rewards_owed);
+                write_account(&config_info, &reward_config)?;
+
+                emit!(AccountMigratedEvent {
+                    account: config_info.key(),
+                    from_version,
+                    to_version: RewardConfig::VERSION,
+                });
+                msg!("RewardConfig migrated to version {}", RewardConfig::VERSION);
+            },
+            Layout::Current => {},
+        }
+    }
+
+    // 3. 初始化全局质押量历史（已有检查点时保持不变）
+    if ctx.accounts.global_stake_history.version == 0 {
+        let pool_state = PoolState::try_deserialize(&mut &pool_info.try_borrow_data()?[..])?;
+        StakeHistoryRecorder {
+            payer: &authority,
+            system_program: &system_program,
+            pool: pool_info.key(),
+            slot: Clock::get()?.slot,
+        }
+        .record(
+            &mut ctx.accounts.global_stake_history,
+            Pubkey::default(),
+            ctx.bumps.global_stake_history,
+            pool_state.total_staked,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// 升级用户仓位到当前布局（由仓位所有者执行，需先完成 migrate_pool）
+///
+/// * v0：realloc 并写入新布局
+/// * v1：账户大小不变，仅更新版本号
+///
+/// 质押历史尚未创建时以当前质押量写入第一个检查点，升级前已有的质押可以在之后的快照中证明
+pub fn migrate_position_handler(ctx: Context<MigratePosition>) -> Result<()> {
+    let user = ctx.accounts.user.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let position_info = ctx.accounts.user_position.to_account_info();
+
+    match detect_layout(
+        &position_info,
+        UserPosition::DISCRIMINATOR,
+        UserPositionV0::LEN,
+        UserPosition::LEN,
+        UserPosition::VERSION,
+    )? {
+        Layout::Legacy => {
+            let legacy: UserPositionV0 = read_legacy(&position_info)?;
+            require_keys_eq!(legacy.owner, user.key(), LpStakingError::Unauthorized);
+
+            resize_account(&position_info, &user, &system_program, UserPosition::LEN)?;
+            write_account(&position_info, &legacy.upgrade())?;
+
+            emit!(AccountMigratedEvent {
+                account: position_info.key(),
+                from_version: 0,
+                to_version: UserPosition::VERSION,
+            });
+            msg!("UserPosition migrated to version {}", UserPosition::VERSION);
+        },
+        Layout::Previous(from_version) => {
+            let mut user_position =
+                UserPosition::try_deserialize(&mut &position_info.try_borrow_data()?[..])?;
+            require_keys_eq!(user_position.owner, user.key(), LpStakingError::Unauthorized);
+
+            // v1 的预留空间始终为零，累计统计字段从零开始；首次存入时间无法恢复，下次存入时记录
+            user_position.version = UserPosition::VERSION;
+            write_account(&position_info, &user_position)?;
+
+            emit!(AccountMigratedEvent {
+                account: position_info.key(),
+                from_version,
+                to_version: UserPosition::VERSION,
+            });
+            msg!("UserPosition migrated to version {}", UserPosition::VERSION);
+        },
+        Layout::Current => {
+            msg!("UserPosition already at version {}", UserPosition::VERSION);
+        },
+    }
+
+    // 写入升级前质押量的检查点（已有检查点时保持不变）
+    let user_position = UserPosition::try_deserialize(&mut &position_info.try_borrow_data()?[..])?;
+    if ctx.accounts.user_stake_history.version == 0 && user_position.staked_amount > 0 {
+        StakeHistoryRecorder {
+            payer: &user,
+            system_program: &system_program,
+            pool: ctx.accounts.pool_state.key(),
+            slot: Clock::get()?.slot,
+        }
+        .record(
+            &mut ctx.accounts.user_stake_history,
+            user.key(),
+            ctx.bumps.user_stake_history,
+            user_position.staked_amount,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: 旧布局无法按 Account<PoolState> 反序列化，在 handler 中校验 owner、discriminator 与 authority
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump,
+    )]
+    pub pool_state: UncheckedAccount<'info>,
+
+    /// CHECK: 可能尚未创建或为旧布局，在 handler 中处理
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_config: UncheckedAccount<'info>,
+
+    /// 奖励金库（PDA，存放 SOL 奖励），升级时按其可用余额写入奖励记账
+    /// CHECK: 这是一个 PDA，用于存放奖励 SOL
+    #[account(
+        seeds = [REWARD_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: AccountInfo<'info>,
+
+    /// 全局质押量历史的第 0 页
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &0u32.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub global_stake_history: Account<'info, StakeHistory>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+
+    /// CHECK: 旧布局无法按 Account<UserPosition> 反序列化，在 handler 中校验 owner、discriminator 与所有者
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub user_position: UncheckedAccount<'info>,
+
+    /// 用户质押量历史
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub user_stake_history: Account<'info, StakeHistory>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -4,6 +4,31 @@ pub mod withdraw;
 pub mod stake;
 pub mod unstake;
 pub mod claim;
+pub mod harvest;
+pub mod position_nft;
+pub mod vote_escrow;
+pub mod snapshot;
+pub mod deposit_and_stake;
+pub mod unstake_and_withdraw;
+pub mod close_position;
+pub mod migrate;
+pub mod quote;
+pub mod update_pool;
+pub mod fund_rewards;
+pub mod set_referral_bps;
+pub mod set_treasury;
+pub mod undistributed;
+pub mod protocol_fee;
+pub mod outflow_limit;
+pub mod price_guard;
+pub mod stable_swap;
+pub mod stable_stake;
+pub mod flash_loan;
+pub mod loyalty;
+pub mod stake_for;
+pub mod operator;
+pub mod initialize_strategy;
+pub mod manage_strategy;
 
 pub use initialize::*;
 pub use deposit::*;
@@ -11,3 +36,28 @@ pub use withdraw::*;
 pub use stake::*;
 pub use unstake::*;
 pub use claim::*;
+pub use harvest::*;
+pub use position_nft::*;
+pub use vote_escrow::*;
+pub use snapshot::*;
+pub use deposit_and_stake::*;
+pub use unstake_and_withdraw::*;
+pub use close_position::*;
+pub use migrate::*;
+pub use quote::*;
+pub use update_pool::*;
+pub use fund_rewards::*;
+pub use set_referral_bps::*;
+pub use set_treasury::*;
+pub use undistributed::*;
+pub use protocol_fee::*;
+pub use outflow_limit::*;
+pub use price_guard::*;
+pub use stable_swap::*;
+pub use stable_stake::*;
+pub use flash_loan::*;
+pub use loyalty::*;
+pub use stake_for::*;
+pub use operator::*;
+pub use initialize_strategy::*;
+pub use manage_strategy::*;
@@ -0,0 +1,256 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::OperatorApprovalEvent;
+use crate::instructions::stake::process_stake;
+use crate::instructions::unstake::process_unstake;
+use crate::state::{OperatorApproval, PoolState, UserPosition, RewardConfig, StakeHistory};
+use crate::utils::StakeHistoryRecorder;
+
+/// 授权操作员管理仓位（仅仓位所有者）
+///
+/// 操作员可以是其他程序的 PDA，通过 CPI 调用 operator_stake / operator_unstake；
+/// 操作员无法提取 LP Token，奖励领取仍走收获代理
+pub fn approve_operator_handler(ctx: Context<ApproveOperator>, operator: Pubkey) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let approval = &mut ctx.accounts.operator_approval;
+    approval.version = OperatorApproval::VERSION;
+    approval.owner = ctx.accounts.user.key();
+    approval.pool = ctx.accounts.pool_state.key();
+    approval.operator = operator;
+    approval.approved_at = now;
+    approval.bump = ctx.bumps.operator_approval;
+    
+    emit!(OperatorApprovalEvent {
+        owner: approval.owner,
+        operator,
+        approved: true,
+        timestamp: now,
+    });
+    
+    msg!("Operator approved!");
+    msg!("User: {}", approval.owner);
+    msg!("Operator: {}", operator);
+    
+    Ok(())
+}
+
+/// 撤销操作员授权（仅仓位所有者），授权账户租金退还给所有者
+pub fn revoke_operator_handler(ctx: Context<RevokeOperator>) -> Result<()> {
+    let approval = &ctx.accounts.operator_approval;
+    
+    emit!(OperatorApprovalEvent {
+        owner: approval.owner,
+        operator: approval.operator,
+        approved: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Operator revoked!");
+    msg!("User: {}", approval.owner);
+    msg!("Operator: {}", approval.operator);
+    
+    Ok(())
+}
+
+/// 操作员代为质押仓位中未质押的 LP Token
+pub fn operator_stake_handler(ctx: Context<OperatorManage>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = &mut *ctx.accounts;
+    
+    process_stake(
+        &mut accounts.pool_state,
+        &mut accounts.user_position,
+        &mut accounts.reward_config,
+        amount,
+        None,
+        &clock,
+    )?;
+    
+    StakeHistoryRecorder {
+        payer: &accounts.payer.to_account_info(),
+        system_program: accounts.system_program.as_ref(),
+        pool: accounts.pool_state.key(),
+        slot: clock.slot,
+    }
+    .record_stake_change(
+        &mut accounts.user_stake_history,
+        ctx.bumps.user_stake_history,
+        &mut accounts.global_stake_history,
+        ctx.bumps.global_stake_history,
+        &accounts.user_position,
+        &mut accounts.pool_state,
+    )
+}
+
+/// 操作员代为解除质押，LP Token 留在仓位中等待所有者提取
+pub fn operator_unstake_handler(ctx: Context<OperatorManage>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = &mut *ctx.accounts;
+    
+    process_unstake(
+        &mut accounts.pool_state,
+        &mut accounts.user_position,
+        &mut accounts.reward_config,
+        amount,
+        &clock,
+    )?;
+    
+    StakeHistoryRecorder {
+        payer: &accounts.payer.to_account_info(),
+        system_program: accounts.system_program.as_ref(),
+        pool: accounts.pool_state.key(),
+        slot: clock.slot,
+    }
+    .record_stake_change(
+        &mut accounts.user_stake_history,
+        ctx.bumps.user_stake_history,
+        &mut accounts.global_stake_history,
+        ctx.bumps.global_stake_history,
+        &accounts.user_position,
+        &mut accounts.pool_state,
+    )
+}
+
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct ApproveOperator<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [
+            USER_POSITION_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key() @ LpStakingError::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = OperatorApproval::LEN,
+        seeds = [
+            OPERATOR_APPROVAL_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref(),
+            operator.as_ref()
+        ],
+        bump,
+    )]
+    pub operator_approval: Account<'info, OperatorApproval>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeOperator<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            OPERATOR_APPROVAL_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref(),
+            operator_approval.operator.as_ref()
+        ],
+        bump = operator_approval.bump,
+        constraint = operator_approval.owner == user.key() @ LpStakingError::Unauthorized,
+    )]
+    pub operator_approval: Account<'info, OperatorApproval>,
+}
+
+#[derive(Accounts)]
+pub struct OperatorManage<'info> {
+    /// 被授权的操作员（其他程序的 PDA 可通过 invoke_signed 签名）
+    pub operator: Signer<'info>,
+    
+    /// 支付质押历史账户租金
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            user_position.owner.as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    
+    /// 仓位所有者对该操作员的授权
+    #[account(
+        seeds = [
+            OPERATOR_APPROVAL_SEED,
+            user_position.owner.as_ref(),
+            pool_state.key().as_ref(),
+            operator.key().as_ref()
+        ],
+        bump = operator_approval.bump,
+    )]
+    pub operator_approval: Account<'info, OperatorApproval>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 用户质押量历史
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            user_position.owner.as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub user_stake_history: Account<'info, StakeHistory>,
+    
+    /// 全局质押量历史的当前分页
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &pool_state.stake_history_page.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub global_stake_history: Account<'info, StakeHistory>,
+    
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::OutflowLimitUpdatedEvent;
+use crate::state::PoolState;
+
+/// 设置守护者（仅管理员）
+pub fn set_guardian_handler(ctx: Context<SetGuardian>, guardian: Option<Pubkey>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    let previous = pool_state.guardian;
+    pool_state.guardian = guardian.unwrap_or_default();
+    
+    msg!("Guardian updated!");
+    msg!("Guardian: {} -> {}", previous, pool_state.guardian);
+    
+    Ok(())
+}
+
+/// 设置提取限额（管理员或守护者）
+///
+/// * `limit_bps` - 每个窗口允许提取的 TVL 比例（基点），0 表示关闭熔断
+/// * `window_secs` - 窗口长度（秒）
+///
+/// 修改后以当前 TVL 重新开始计算窗口
+pub fn set_outflow_limit_handler(
+    ctx: Context<ManageOutflowLimit>,
+    limit_bps: u64,
+    window_secs: i64,
+) -> Result<()> {
+    require!(limit_bps <= BASIS_POINTS, LpStakingError::InvalidOutflowLimit);
+    require!(limit_bps == 0 || window_secs > 0, LpStakingError::InvalidOutflowLimit);
+    
+    let clock = Clock::get()?;
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.outflow_limit_bps = limit_bps;
+    pool_state.outflow_window_secs = window_secs;
+    pool_state.reset_outflow_window(clock.unix_timestamp);
+    
+    emit!(OutflowLimitUpdatedEvent {
+        updated_by: ctx.accounts.admin.key(),
+        limit_bps,
+        window_secs,
+        window_tvl: pool_state.outflow_window_tvl,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Outflow limit updated!");
+    msg!("Limit: {} bps per {} seconds", limit_bps, window_secs);
+    
+    Ok(())
+}
+
+/// 重置提取限额窗口（管理员或守护者），清空已计入的提取量
+pub fn reset_outflow_window_handler(ctx: Context<ManageOutflowLimit>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.reset_outflow_window(clock.unix_timestamp);
+    
+    emit!(OutflowLimitUpdatedEvent {
+        updated_by: ctx.accounts.admin.key(),
+        limit_bps: pool_state.outflow_limit_bps,
+        window_secs: pool_state.outflow_window_secs,
+        window_tvl: pool_state.outflow_window_tvl,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Outflow window reset!");
+    msg!("Window TVL: {}", pool_state.outflow_window_tvl);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+}
+
+#[derive(Accounts)]
+pub struct ManageOutflowLimit<'info> {
+    /// 管理员或守护者
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        constraint = admin.key() == pool_state.authority
+            || (pool_state.guardian != Pubkey::default() && admin.key() == pool_state.guardian)
+            @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+}
@@ -0,0 +1,972 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Burn, Mint, MintTo, SetAuthority, Token, TokenAccount, Transfer};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::{
+    PositionTokenizedEvent, RewardClaimedEvent, RewardShortfallEvent, TokenizedPositionExitedEvent,
+};
+use crate::instructions::vote_escrow::{apply_lock_change, LockChangeAccounts};
+use crate::state::{
+    PoolState, RewardConfig, StakeHistory, TokenizedPosition, UserPosition, VoteEscrow,
+    VoteEscrowGlobal,
+};
+use crate::utils::{reward_calculator, RewardVault, StakeHistoryRecorder};
+
+/// 质押 LP Token 并铸造 1-of-1 仓位代币
+///
+/// LP Token 转入池子 LP 金库托管，质押权益与奖励归仓位代币的持有者所有，
+/// 仓位代币可以自由转让
+pub fn stake_nft_handler(ctx: Context<StakeNft>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    
+    // 参数验证
+    require!(amount >= MIN_STAKE_AMOUNT, LpStakingError::InvalidAmount);
+    require!(
+        ctx.accounts.user_position.lp_balance >= amount,
+        LpStakingError::InsufficientLpTokens
+    );
+    
+    // 1. 更新奖励池状态（在状态改变前）
+    reward_calculator::update_pool_reward(
+        &ctx.accounts.pool_state,
+        &mut ctx.accounts.reward_config,
+        clock.slot,
+    )?;
+    
+    // 2. 将 LP Token 转入 LP 金库托管并铸造仓位代币
+    escrow_and_mint_position(
+        PositionMintAccounts {
+            user: &ctx.accounts.user,
+            pool_state: &ctx.accounts.pool_state,
+            user_lp_account: &ctx.accounts.user_lp_account,
+            lp_vault: &ctx.accounts.lp_vault,
+            position_mint: &ctx.accounts.position_mint,
+            user_nft_account: &ctx.accounts.user_nft_account,
+            token_program: &ctx.accounts.token_program,
+        },
+        amount,
+    )?;
+    
+    // 3. 更新用户仓位与池子总质押量
+    let user_position = &mut ctx.accounts.user_position;
+    user_position.lp_balance = user_position.lp_balance
+        .checked_sub(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.total_staked = pool_state.total_staked
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    pool_state.update_staker_count(0, amount)?;
+    pool_state.record_activity(clock.slot);
+    
+    // 4. 初始化代币化仓位并记录其质押量历史
+    let tokenized_position = &mut ctx.accounts.tokenized_position;
+    init_tokenized_position(
+        tokenized_position,
+        pool_state.key(),
+        ctx.accounts.position_mint.key(),
+        amount,
+        &ctx.accounts.reward_config,
+        &clock,
+        ctx.bumps.tokenized_position,
+    )?;
+    
+    let recorder = StakeHistoryRecorder {
+        payer: &ctx.accounts.user.to_account_info(),
+        system_program: ctx.accounts.system_program.as_ref(),
+        pool: pool_state.key(),
+        slot: clock.slot,
+    };
+    recorder.record(
+        &mut ctx.accounts.position_stake_history,
+        tokenized_position.key(),
+        ctx.bumps.position_stake_history,
+        amount,
+    )?;
+    recorder.record_total_staked(
+        &mut ctx.accounts.global_stake_history,
+        ctx.bumps.global_stake_history,
+        pool_state,
+    )?;
+    
+    emit!(PositionTokenizedEvent {
+        owner: ctx.accounts.user.key(),
+        mint: tokenized_position.mint,
+        position: tokenized_position.key(),
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Stake NFT successful!");
+    msg!("User: {}", ctx.accounts.user.key());
+    msg!("Position mint: {}", tokenized_position.mint);
+    msg!("Staked LP amount: {}", amount);
+    msg!("Pool total staked: {}", pool_state.total_staked);
+    
+    Ok(())
+}
+
+/// 将已有质押（包括锁仓中的质押）转换为 1-of-1 仓位代币
+///
+/// 先按原权重结算奖励（留在 UserPosition.pending_reward），再将 amount 从质押量转入代币化仓位，
+/// 池子总质押量不变。优先转换未锁仓的部分；涉及锁仓时被转换部分的投票权随之移除，
+/// 仓位代币继承锁仓到期 slot，到期前不可退出
+pub fn tokenize_stake_handler(ctx: Context<TokenizeStake>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let user_stake_history_bump = ctx.bumps.user_stake_history;
+    let position_stake_history_bump = ctx.bumps.position_stake_history;
+    let tokenized_position_bump = ctx.bumps.tokenized_position;
+    let accounts = &mut *ctx.accounts;
+    
+    // 参数验证
+    require!(amount >= MIN_STAKE_AMOUNT, LpStakingError::InvalidAmount);
+    require!(
+        accounts.user_position.staked_amount >= amount,
+        LpStakingError::InsufficientStaked
+    );
+    
+    // 1. 更新奖励池状态并按原权重结算奖励
+    reward_calculator::update_pool_reward(
+        &accounts.pool_state,
+        &mut accounts.reward_config,
+        clock.slot,
+    )?;
+    
+    let user_position = &mut accounts.user_position;
+    let pending = reward_calculator::settle_pending_reward(
+        &mut accounts.reward_config,
+        user_position.reward_weight()?,
+        user_position.reward_debt,
+    )?;
+    user_position.pending_reward = user_position.pending_reward
+        .checked_add(pending)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    // 2. 转换锁仓部分：缩减用户锁仓与投票权，仓位代币继承到期 slot
+    let locked_portion = user_position.locked_portion(amount, clock.slot);
+    let lock_end_slot = if locked_portion > 0 {
+        let (Some(vote_escrow), Some(vote_escrow_global)) = (
+            accounts.vote_escrow.as_mut(),
+            accounts.vote_escrow_global.as_mut(),
+        ) else {
+            return err!(LpStakingError::VoteEscrowRequired);
+        };
+        
+        let old_amount = user_position.active_locked_amount(clock.slot);
+        let new_amount = old_amount - locked_portion;
+        apply_lock_change(
+            LockChangeAccounts {
+                vote_escrow,
+                vote_escrow_global,
+                vote_escrow_page: accounts.vote_escrow_page.as_ref().map(|a| a.as_ref()),
+                payer: &accounts.user.to_account_info(),
+                system_program: &accounts.system_program.to_account_info(),
+            },
+            (old_amount, user_position.lock_end_slot),
+            (new_amount, user_position.lock_end_slot),
+            clock.slot,
+        )?;
+        user_position.locked_amount = new_amount;
+        user_position.lock_end_slot
+    } else {
+        0
+    };
+    
+    // 3. 更新用户质押数量（池子总质押量不变）
+    let staked_before = user_position.staked_amount;
+    user_position.staked_amount = user_position.staked_amount
+        .checked_sub(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    let pool_state = &mut accounts.pool_state;
+    pool_state.update_staker_count(staked_before, user_position.staked_amount)?;
+    pool_state.update_staker_count(0, amount)?;
+    pool_state.record_activity(clock.slot);
+    
+    // 4. 按剩余比例缩减连续质押时长，刷新忠诚度加成并更新 reward_debt
+    user_position.last_stake_time = reward_calculator::adjust_tenure_start(
+        user_position.last_stake_time,
+        staked_before,
+        user_position.staked_amount,
+        clock.unix_timestamp,
+    );
+    reward_calculator::refresh_loyalty_bonus(
+        pool_state,
+        &accounts.reward_config,
+        user_position,
+        clock.unix_timestamp,
+    )?;
+    user_position.reward_debt = (user_position.reward_weight()? as u128)
+        .checked_mul(accounts.reward_config.acc_reward_per_share)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    // 5. 将 LP Token 转入 LP 金库托管并铸造仓位代币
+    escrow_and_mint_position(
+        PositionMintAccounts {
+            user: &accounts.user,
+            pool_state: &accounts.pool_state,
+            user_lp_account: &accounts.user_lp_account,
+            lp_vault: &accounts.lp_vault,
+            position_mint: &accounts.position_mint,
+            user_nft_account: &accounts.user_nft_account,
+            token_program: &accounts.token_program,
+        },
+        amount,
+    )?;
+    
+    // 6. 初始化代币化仓位
+    let tokenized_position = &mut accounts.tokenized_position;
+    init_tokenized_position(
+        tokenized_position,
+        accounts.pool_state.key(),
+        accounts.position_mint.key(),
+        amount,
+        &accounts.reward_config,
+        &clock,
+        tokenized_position_bump,
+    )?;
+    tokenized_position.lock_end_slot = lock_end_slot;
+    
+    let recorder = StakeHistoryRecorder {
+        payer: &accounts.user.to_account_info(),
+        system_program: accounts.system_program.as_ref(),
+        pool: accounts.pool_state.key(),
+        slot: clock.slot,
+    };
+    recorder.record(
+        &mut accounts.user_stake_history,
+        accounts.user.key(),
+        user_stake_history_bump,
+        accounts.user_position.staked_amount,
+    )?;
+    recorder.record(
+        &mut accounts.position_stake_history,
+        accounts.tokenized_position.key(),
+        position_stake_history_bump,
+        amount,
+    )?;
+    
+    emit!(PositionTokenizedEvent {
+        owner: accounts.user.key(),
+        mint: accounts.tokenized_position.mint,
+        position: accounts.tokenized_position.key(),
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Tokenize stake successful!");
+    msg!("User: {}", accounts.user.key());
+    msg!("Position mint: {}", accounts.tokenized_position.mint);
+    msg!("Tokenized LP amount: {} (locked {})", amount, locked_portion);
+    msg!("Remaining staked: {}", accounts.user_position.staked_amount);
+    
+    Ok(())
+}
+
+/// 仓位代币持有者领取奖励
+pub fn claim_nft_handler(ctx: Context<ClaimNft>) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = &mut *ctx.accounts;
+    
+    // 1. 结算奖励
+    let total_pending = settle_tokenized_reward(
+        &accounts.pool_state,
+        &mut accounts.reward_config,
+        &mut accounts.tokenized_position,
+        &clock,
+    )?;
+    require!(total_pending > 0, LpStakingError::NoRewardToClaim);
+    
+    // 2. 按金库可用余额确定支付数量，不足部分保留为待领取
+    let reward_vault = RewardVault {
+        vault: &accounts.reward_vault,
+        pool_state: accounts.pool_state.key(),
+        bump: ctx.bumps.reward_vault,
+        system_program: accounts.system_program.as_ref(),
+    };
+    let available = reward_vault.available()?;
+    let amount = total_pending.min(available);
+    let shortfall = total_pending - amount;
+    
+    if shortfall > 0 {
+        emit!(RewardShortfallEvent {
+            owner: accounts.holder.key(),
+            requested: total_pending,
+            paid: amount,
+            shortfall,
+            vault_available: available,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+    
+    // 金库为空：奖励全部保留在 pending_reward 中
+    if amount == 0 {
+        msg!("Reward vault empty, {} lamports left pending", shortfall);
+        return Ok(());
+    }
+    
+    // 3. 从奖励金库转账 SOL 给持有者
+    reward_vault.transfer_to(&accounts.holder.to_account_info(), amount)?;
+    accounts.reward_config.record_payout(amount)?;
+    
+    let pool_state = &mut accounts.pool_state;
+    pool_state.total_rewards_claimed = pool_state.total_rewards_claimed
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    pool_state.record_activity(clock.slot);
+    
+    let tokenized_position = &mut accounts.tokenized_position;
+    tokenized_position.pending_reward = shortfall;
+    tokenized_position.last_claim_time = clock.unix_timestamp;
+    
+    emit!(RewardClaimedEvent {
+        owner: accounts.holder.key(),
+        recipient: accounts.holder.key(),
+        caller: accounts.holder.key(),
+        amount,
+        referrer: Pubkey::default(),
+        referral_reward: 0,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Claim NFT successful!");
+    msg!("Holder: {}", accounts.holder.key());
+    msg!("Position mint: {}", tokenized_position.mint);
+    msg!("Reward claimed: {} lamports", amount);
+    
+    Ok(())
+}
+
+/// 销毁仓位代币并完全退出
+///
+/// 托管的 LP Token 退回持有者钱包并计入其 UserPosition.lp_balance，
+/// 未领取的奖励转入其 UserPosition.pending_reward，可通过 claim 领取
+pub fn exit_nft_handler(ctx: Context<ExitNft>) -> Result<()> {
+    let clock = Clock::get()?;
+    let holder_position_bump = ctx.bumps.holder_position;
+    let position_stake_history_bump = ctx.bumps.position_stake_history;
+    let global_stake_history_bump = ctx.bumps.global_stake_history;
+    let accounts = &mut *ctx.accounts;
+    
+    // 由锁仓质押转换而来的仓位代币到期前不可退出
+    require!(
+        clock.slot >= accounts.tokenized_position.lock_end_slot,
+        LpStakingError::StakeLocked
+    );
+    
+    // 1. 结算奖励
+    let total_pending = settle_tokenized_reward(
+        &accounts.pool_state,
+        &mut accounts.reward_config,
+        &mut accounts.tokenized_position,
+        &clock,
+    )?;
+    let amount = accounts.tokenized_position.staked_amount;
+    
+    // 2. 销毁仓位代币
+    token::burn(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            Burn {
+                mint: accounts.position_mint.to_account_info(),
+                from: accounts.holder_nft_account.to_account_info(),
+                authority: accounts.holder.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+    
+    // 3. 从 LP 金库退回 LP Token
+    let seeds = &[
+        POOL_STATE_SEED,
+        &[accounts.pool_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+    
+    token::transfer(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.lp_vault.to_account_info(),
+                to: accounts.holder_lp_account.to_account_info(),
+                authority: accounts.pool_state.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+    
+    // 4. 更新池子总质押量并记录代币化仓位的质押量历史
+    let pool_state = &mut accounts.pool_state;
+    pool_state.total_staked = pool_state.total_staked
+        .checked_sub(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    pool_state.update_staker_count(amount, 0)?;
+    pool_state.record_activity(clock.slot);
+    
+    let recorder = StakeHistoryRecorder {
+        payer: &accounts.holder.to_account_info(),
+        system_program: accounts.system_program.as_ref(),
+        pool: pool_state.key(),
+        slot: clock.slot,
+    };
+    recorder.record(
+        &mut accounts.position_stake_history,
+        accounts.tokenized_position.key(),
+        position_stake_history_bump,
+        0,
+    )?;
+    recorder.record_total_staked(
+        &mut accounts.global_stake_history,
+        global_stake_history_bump,
+        pool_state,
+    )?;
+    
+    // 5. 记入持有者仓位
+    let holder_position = &mut accounts.holder_position;
+    if holder_position.owner == Pubkey::default() {
+        holder_position.reset(
+            accounts.holder.key(),
+            pool_state.key(),
+            holder_position_bump,
+        );
+        pool_state.open_position()?;
+    }
+    holder_position.lp_balance = holder_position.lp_balance
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    holder_position.pending_reward = holder_position.pending_reward
+        .checked_add(total_pending)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    emit!(TokenizedPositionExitedEvent {
+        holder: accounts.holder.key(),
+        mint: accounts.position_mint.key(),
+        position: accounts.tokenized_position.key(),
+        amount,
+        reward_credited: total_pending,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Exit NFT successful!");
+    msg!("Holder: {}", accounts.holder.key());
+    msg!("Returned LP amount: {}", amount);
+    msg!("Reward credited: {} lamports", total_pending);
+    
+    Ok(())
+}
+
+/// 铸造仓位代币涉及的账户（stake_nft 与 tokenize_stake 共用）
+struct PositionMintAccounts<'a, 'info> {
+    user: &'a Signer<'info>,
+    pool_state: &'a Account<'info, PoolState>,
+    user_lp_account: &'a Account<'info, TokenAccount>,
+    lp_vault: &'a Account<'info, TokenAccount>,
+    position_mint: &'a Account<'info, Mint>,
+    user_nft_account: &'a Account<'info, TokenAccount>,
+    token_program: &'a Program<'info, Token>,
+}
+
+/// 将 LP Token 转入 LP 金库托管，铸造仓位代币并移除 mint 权限，保证供应量固定为 1
+fn escrow_and_mint_position(accounts: PositionMintAccounts, amount: u64) -> Result<()> {
+    token::transfer(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.user_lp_account.to_account_info(),
+                to: accounts.lp_vault.to_account_info(),
+                authority: accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    
+    let seeds = &[
+        POOL_STATE_SEED,
+        &[accounts.pool_state.bump],
+    ];
+    let signer = &[&seeds[..]];
+    
+    token::mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            MintTo {
+                mint: accounts.position_mint.to_account_info(),
+                to: accounts.user_nft_account.to_account_info(),
+                authority: accounts.pool_state.to_account_info(),
+            },
+            signer,
+        ),
+        1,
+    )?;
+    token::set_authority(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: accounts.pool_state.to_account_info(),
+                account_or_mint: accounts.position_mint.to_account_info(),
+            },
+            signer,
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )
+}
+
+/// 初始化代币化仓位，从当前累计奖励开始计算奖励（无锁仓）
+fn init_tokenized_position(
+    tokenized_position: &mut TokenizedPosition,
+    pool: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+    reward_config: &RewardConfig,
+    clock: &Clock,
+    bump: u8,
+) -> Result<()> {
+    tokenized_position.version = TokenizedPosition::VERSION;
+    tokenized_position.pool = pool;
+    tokenized_position.mint = mint;
+    tokenized_position.staked_amount = amount;
+    tokenized_position.reward_debt = (amount as u128)
+        .checked_mul(reward_config.acc_reward_per_share)
+        .ok_or(LpStakingError::MathOverflow)?;
+    tokenized_position.pending_reward = 0;
+    tokenized_position.last_stake_time = clock.unix_timestamp;
+    tokenized_position.last_claim_time = 0;
+    tokenized_position.bump = bump;
+    tokenized_position.lock_end_slot = 0;
+    Ok(())
+}
+
+/// 更新奖励池并将代币化仓位的新增奖励累加到 pending_reward
+///
+/// 返回结算后的总待领取奖励
+fn settle_tokenized_reward(
+    pool_state: &PoolState,
+    reward_config: &mut RewardConfig,
+    tokenized_position: &mut TokenizedPosition,
+    clock: &Clock,
+) -> Result<u64> {
+    reward_calculator::update_pool_reward(
+        pool_state,
+        reward_config,
+        clock.slot,
+    )?;
+    
+    let pending = reward_calculator::settle_pending_reward(
+        reward_config,
+        tokenized_position.staked_amount,
+        tokenized_position.reward_debt,
+    )?;
+    
+    tokenized_position.pending_reward = tokenized_position.pending_reward
+        .checked_add(pending)
+        .ok_or(LpStakingError::MathOverflow)?;
+    tokenized_position.reward_debt = (tokenized_position.staked_amount as u128)
+        .checked_mul(reward_config.acc_reward_per_share)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    Ok(tokenized_position.pending_reward)
+}
+
+#[derive(Accounts)]
+pub struct StakeNft<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key() @ LpStakingError::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    #[account(address = pool_state.lp_token_mint)]
+    pub lp_token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = lp_token_mint,
+        token::authority = user,
+    )]
+    pub user_lp_account: Account<'info, TokenAccount>,
+    
+    /// 池子 LP 金库（托管代币化仓位的 LP Token）
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [LP_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+        token::mint = lp_token_mint,
+        token::authority = pool_state,
+    )]
+    pub lp_vault: Account<'info, TokenAccount>,
+    
+    /// 新的仓位代币 mint（由客户端生成的密钥对）
+    #[account(
+        init,
+        payer = user,
+        mint::decimals = 0,
+        mint::authority = pool_state,
+    )]
+    pub position_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = TokenizedPosition::LEN,
+        seeds = [TOKENIZED_POSITION_SEED, position_mint.key().as_ref()],
+        bump,
+    )]
+    pub tokenized_position: Account<'info, TokenizedPosition>,
+    
+    /// 代币化仓位的质押量历史
+    #[account(
+        init,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            tokenized_position.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub position_stake_history: Box<Account<'info, StakeHistory>>,
+    
+    /// 全局质押量历史的当前分页
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &pool_state.stake_history_page.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub global_stake_history: Box<Account<'info, StakeHistory>>,
+    
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = position_mint,
+        associated_token::authority = user,
+    )]
+    pub user_nft_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TokenizeStake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key() @ LpStakingError::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 用户质押量历史
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub user_stake_history: Account<'info, StakeHistory>,
+    
+    /// 用户投票托管账户（转换锁仓中的质押时必须提供）
+    #[account(
+        mut,
+        seeds = [
+            VOTE_ESCROW_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = vote_escrow.bump,
+    )]
+    pub vote_escrow: Option<Account<'info, VoteEscrow>>,
+    
+    /// 全局投票托管账户（转换锁仓中的质押时必须提供）
+    #[account(
+        mut,
+        seeds = [VOTE_ESCROW_GLOBAL_SEED, pool_state.key().as_ref()],
+        bump = vote_escrow_global.bump,
+    )]
+    pub vote_escrow_global: Option<Account<'info, VoteEscrowGlobal>>,
+    
+    /// CHECK: 下一个全局检查点归档分页（全局检查点需要归档时必须提供，地址在归档时校验）
+    #[account(mut)]
+    pub vote_escrow_page: Option<UncheckedAccount<'info>>,
+    
+    #[account(address = pool_state.lp_token_mint)]
+    pub lp_token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = lp_token_mint,
+        token::authority = user,
+    )]
+    pub user_lp_account: Account<'info, TokenAccount>,
+    
+    /// 池子 LP 金库（托管代币化仓位的 LP Token）
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [LP_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+        token::mint = lp_token_mint,
+        token::authority = pool_state,
+    )]
+    pub lp_vault: Account<'info, TokenAccount>,
+    
+    /// 新的仓位代币 mint（由客户端生成的密钥对）
+    #[account(
+        init,
+        payer = user,
+        mint::decimals = 0,
+        mint::authority = pool_state,
+    )]
+    pub position_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = TokenizedPosition::LEN,
+        seeds = [TOKENIZED_POSITION_SEED, position_mint.key().as_ref()],
+        bump,
+    )]
+    pub tokenized_position: Account<'info, TokenizedPosition>,
+    
+    /// 代币化仓位的质押量历史
+    #[account(
+        init,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            tokenized_position.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub position_stake_history: Box<Account<'info, StakeHistory>>,
+    
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = position_mint,
+        associated_token::authority = user,
+    )]
+    pub user_nft_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimNft<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    #[account(
+        mut,
+        seeds = [TOKENIZED_POSITION_SEED, tokenized_position.mint.as_ref()],
+        bump = tokenized_position.bump,
+        constraint = tokenized_position.pool == pool_state.key() @ LpStakingError::Unauthorized,
+    )]
+    pub tokenized_position: Account<'info, TokenizedPosition>,
+    
+    /// 持有者的仓位代币账户（必须持有该仓位代币）
+    #[account(
+        token::mint = tokenized_position.mint,
+        token::authority = holder,
+        constraint = holder_nft_account.amount == 1 @ LpStakingError::NotPositionHolder,
+    )]
+    pub holder_nft_account: Account<'info, TokenAccount>,
+    
+    /// 奖励金库（PDA，存放 SOL 奖励）
+    /// CHECK: 这是一个 PDA，用于存放奖励 SOL
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExitNft<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    #[account(
+        mut,
+        close = holder,
+        seeds = [TOKENIZED_POSITION_SEED, position_mint.key().as_ref()],
+        bump = tokenized_position.bump,
+        constraint = tokenized_position.pool == pool_state.key() @ LpStakingError::Unauthorized,
+    )]
+    pub tokenized_position: Account<'info, TokenizedPosition>,
+    
+    #[account(mut)]
+    pub position_mint: Account<'info, Mint>,
+    
+    /// 持有者的仓位代币账户（必须持有该仓位代币）
+    #[account(
+        mut,
+        token::mint = position_mint,
+        token::authority = holder,
+        constraint = holder_nft_account.amount == 1 @ LpStakingError::NotPositionHolder,
+    )]
+    pub holder_nft_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = UserPosition::LEN,
+        seeds = [
+            USER_POSITION_SEED,
+            holder.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+        constraint = holder_position.owner == Pubkey::default()
+            || holder_position.owner == holder.key() @ LpStakingError::Unauthorized,
+    )]
+    pub holder_position: Account<'info, UserPosition>,
+    
+    /// 代币化仓位的质押量历史（早于历史记录创建的仓位在退出时补建）
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            tokenized_position.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub position_stake_history: Box<Account<'info, StakeHistory>>,
+    
+    /// 全局质押量历史的当前分页
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &pool_state.stake_history_page.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub global_stake_history: Box<Account<'info, StakeHistory>>,
+    
+    #[account(
+        mut,
+        seeds = [LP_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub lp_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = pool_state.lp_token_mint,
+        token::authority = holder,
+    )]
+    pub holder_lp_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::{DepegDetectedEvent, PoolPauseUpdatedEvent};
+use crate::state::PoolState;
+use crate::utils::{peg_status, read_price_feed, PegStatus};
+
+/// 配置脱锚保护（仅管理员）
+///
+/// 传入 price_feed 账户时启用，不传时关闭。启用后存入需要传入该账户，
+/// 价格偏离 $1 超过 peg_band_bps 或报价超过 max_price_age_secs 未更新时拒绝存入
+pub fn configure_price_guard_handler(
+    ctx: Context<ConfigurePriceGuard>,
+    peg_band_bps: u64,
+    max_price_age_secs: i64,
+) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    
+    match &ctx.accounts.price_feed {
+        Some(price_feed) => {
+            require!(peg_band_bps <= BASIS_POINTS, LpStakingError::InvalidPriceFeed);
+            require!(max_price_age_secs > 0, LpStakingError::InvalidPriceFeed);
+            read_price_feed(price_feed)?;
+            
+            pool_state.price_feed = price_feed.key();
+            pool_state.peg_band_bps = peg_band_bps;
+            pool_state.max_price_age_secs = max_price_age_secs;
+        },
+        None => {
+            pool_state.price_feed = Pubkey::default();
+            pool_state.peg_band_bps = 0;
+            pool_state.max_price_age_secs = 0;
+        },
+    }
+    
+    msg!("Price guard updated!");
+    msg!("Price feed: {}", pool_state.price_feed);
+    msg!("Peg band: {} bps, max age: {} seconds", peg_band_bps, max_price_age_secs);
+    
+    Ok(())
+}
+
+/// 暂停或恢复存取（管理员或守护者）
+pub fn set_paused_handler(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.paused = paused;
+    
+    emit!(PoolPauseUpdatedEvent {
+        updated_by: ctx.accounts.admin.key(),
+        paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("Pool {}", if paused { "paused" } else { "unpaused" });
+    
+    Ok(())
+}
+
+/// 检查锚定状态（无需权限，供 keeper 调用）
+///
+/// 价格脱锚或过期时自动暂停池子，恢复需管理员或守护者调用 set_paused
+pub fn check_peg_handler(ctx: Context<CheckPeg>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pool_state = &mut ctx.accounts.pool_state;
+    let price = read_price_feed(&ctx.accounts.price_feed)?;
+    
+    let status = peg_status(pool_state, &price, clock.unix_timestamp)?;
+    if status == PegStatus::Healthy {
+        msg!("Price within peg band");
+        return Ok(());
+    }
+    
+    emit!(DepegDetectedEvent {
+        caller: ctx.accounts.caller.key(),
+        price: price.price,
+        expo: price.expo,
+        publish_time: price.publish_time,
+        stale: matches!(status, PegStatus::Stale(_)),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    if !pool_state.paused {
+        pool_state.paused = true;
+        emit!(PoolPauseUpdatedEvent {
+            updated_by: ctx.accounts.caller.key(),
+            paused: true,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+    
+    msg!("Depeg detected: {:?}, pool paused", status);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigurePriceGuard<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    /// 价格账户（不传则关闭脱锚保护）
+    /// CHECK: 在 handler 中校验布局
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    /// 管理员或守护者
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        constraint = admin.key() == pool_state.authority
+            || (pool_state.guardian != Pubkey::default() && admin.key() == pool_state.guardian)
+            @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+}
+
+#[derive(Accounts)]
+pub struct CheckPeg<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        constraint = pool_state.price_feed != Pubkey::default() @ LpStakingError::InvalidPriceFeed,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    /// 价格账户
+    /// CHECK: 地址必须与池子配置一致，布局在 handler 中校验
+    #[account(address = pool_state.price_feed @ LpStakingError::InvalidPriceFeed)]
+    pub price_feed: UncheckedAccount<'info>,
+}
@@ -0,0 +1,147 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::System;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::ProtocolRewardsCollectedEvent;
+use crate::state::{PoolState, RewardConfig};
+use crate::utils::{reward_calculator, RewardVault};
+
+/// 设置协议抽成比例（仅管理员）
+///
+/// 新比例只作用于之后的排放，之前的区间按旧比例结算
+pub fn set_protocol_fee_bps_handler(
+    ctx: Context<SetProtocolFeeBps>,
+    protocol_fee_bps: u64,
+) -> Result<()> {
+    require!(
+        protocol_fee_bps <= BASIS_POINTS,
+        LpStakingError::InvalidProtocolFeeBps
+    );
+    
+    let reward_config = &mut ctx.accounts.reward_config;
+    reward_calculator::update_pool_reward(
+        &ctx.accounts.pool_state,
+        reward_config,
+        Clock::get()?.slot,
+    )?;
+    
+    let previous = reward_config.protocol_fee_bps;
+    reward_config.protocol_fee_bps = protocol_fee_bps;
+    
+    msg!("Protocol fee updated!");
+    msg!("Protocol fee bps: {} -> {}", previous, protocol_fee_bps);
+    
+    Ok(())
+}
+
+/// 提取协议奖励到国库（仅管理员）
+///
+/// 金库可用余额不足时只提取可用部分，剩余部分保留待下次提取
+pub fn collect_protocol_rewards_handler(ctx: Context<CollectProtocolRewards>) -> Result<()> {
+    let clock = Clock::get()?;
+    let treasury = ctx.accounts.treasury.to_account_info();
+    let accounts = &mut *ctx.accounts;
+    
+    // 1. 更新奖励池，计提最新的协议抽成
+    reward_calculator::update_pool_reward(
+        &accounts.pool_state,
+        &mut accounts.reward_config,
+        clock.slot,
+    )?;
+    require!(
+        accounts.reward_config.protocol_fees_accrued > 0,
+        LpStakingError::NoRewardToClaim
+    );
+    
+    // 2. 按金库可用余额确定提取数量
+    let reward_vault = RewardVault {
+        vault: &accounts.reward_vault,
+        pool_state: accounts.pool_state.key(),
+        bump: ctx.bumps.reward_vault,
+        system_program: accounts.system_program.as_ref(),
+    };
+    let amount = accounts.reward_config.protocol_fees_accrued.min(reward_vault.available()?);
+    require!(amount > 0, LpStakingError::InsufficientRewardVault);
+    
+    // 3. 从奖励金库转账到国库
+    reward_vault.transfer_to(&treasury, amount)?;
+    
+    // 4. 扣减已计提的协议奖励并记录支付
+    let reward_config = &mut accounts.reward_config;
+    reward_config.protocol_fees_accrued = reward_config.protocol_fees_accrued
+        .checked_sub(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    reward_config.record_payout(amount)?;
+    
+    emit!(ProtocolRewardsCollectedEvent {
+        treasury: treasury.key(),
+        amount,
+        remaining: reward_config.protocol_fees_accrued,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Protocol rewards collected!");
+    msg!("Treasury: {}", treasury.key());
+    msg!("Amount: {} lamports", amount);
+    msg!("Remaining accrued: {} lamports", reward_config.protocol_fees_accrued);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFeeBps<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolRewards<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+        constraint = pool_state.treasury != Pubkey::default() @ LpStakingError::InvalidTreasury,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 奖励金库（PDA，存放 SOL 奖励）
+    /// CHECK: 这是一个 PDA，用于存放奖励 SOL
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: AccountInfo<'info>,
+    
+    /// 国库地址
+    /// CHECK: 地址必须与池子记录的国库一致，仅接收 SOL
+    #[account(
+        mut,
+        address = pool_state.treasury @ LpStakingError::InvalidTreasury,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::instructions::deposit::calculate_lp_amount;
+use crate::instructions::withdraw::calculate_withdraw_amount;
+use crate::state::{EmissionType, PoolState, RewardConfig, UserPosition};
+use crate::utils::{reward_calculator, RewardCalculator};
+
+/// 排放参数快照（供客户端计算 APR）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EmissionQuote {
+    /// 排放类型
+    pub emission_type: EmissionType,
+    /// 当前每 slot 排放量（lamports）
+    pub reward_per_slot: u64,
+    /// 池子总质押量
+    pub total_staked: u64,
+    /// 池子总资产（wrappedUSDC）
+    pub total_deposited: u64,
+    /// LP Token 总供应量
+    pub total_lp_supply: u64,
+    /// 更新到当前 slot 后的累计每份奖励
+    pub acc_reward_per_share: u128,
+    /// 报价对应的 slot
+    pub slot: u64,
+}
+
+/// 池子累计统计数据（供看板与审计使用）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PoolStats {
+    /// 池子总资产（wrappedUSDC）
+    pub total_deposited: u64,
+    /// 池子总质押量
+    pub total_staked: u64,
+    /// 累计存入量（wrappedUSDC）
+    pub cumulative_deposited: u64,
+    /// 累计提取量（wrappedUSDC）
+    pub cumulative_withdrawn: u64,
+    /// 活跃仓位数量
+    pub active_positions: u64,
+    /// 质押量大于零的仓位数量
+    pub staker_count: u64,
+    /// 用户累计领取的奖励（lamports）
+    pub total_rewards_claimed: u64,
+    /// 最近一次用户操作的 slot
+    pub last_activity_slot: u64,
+}
+
+/// 查询用户当前可领取的奖励（只读，通过 return data 返回）
+///
+/// 在 RewardConfig 的副本上执行 update_pool_reward，不修改链上状态
+pub fn quote_pending_reward_handler(ctx: Context<QuotePendingReward>) -> Result<u64> {
+    let user_position = &ctx.accounts.user_position;
+    let mut reward_config = (*ctx.accounts.reward_config).clone();
+    
+    reward_calculator::update_pool_reward(
+        &ctx.accounts.pool_state,
+        &mut reward_config,
+        Clock::get()?.slot,
+    )?;
+    
+    let pending_from_staked = reward_calculator::calculate_pending_reward(
+        user_position.reward_weight()?,
+        reward_config.acc_reward_per_share,
+        user_position.reward_debt,
+    )?;
+    
+    let total_pending = user_position.pending_reward
+        .checked_add(pending_from_staked)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    Ok(total_pending)
+}
+
+/// 报价：存入 amount wrappedUSDC 可获得的 LP Token 数量
+pub fn quote_deposit_handler(ctx: Context<QuotePool>, amount: u64) -> Result<u64> {
+    let pool_state = &ctx.accounts.pool_state;
+    
+    calculate_lp_amount(
+        amount,
+        pool_state.total_deposited,
+        pool_state.total_lp_supply,
+    )
+}
+
+/// 报价：赎回 lp_amount LP Token 可获得的 wrappedUSDC 数量
+pub fn quote_withdraw_handler(ctx: Context<QuotePool>, lp_amount: u64) -> Result<u64> {
+    let pool_state = &ctx.accounts.pool_state;
+    
+    calculate_withdraw_amount(
+        lp_amount,
+        pool_state.total_deposited,
+        pool_state.total_lp_supply,
+    )
+}
+
+/// 查询当前排放速率与 APR 计算所需的池子数据
+pub fn quote_emission_handler(ctx: Context<QuoteEmission>) -> Result<EmissionQuote> {
+    let pool_state = &ctx.accounts.pool_state;
+    let mut reward_config = (*ctx.accounts.reward_config).clone();
+    let slot = Clock::get()?.slot;
+    
+    reward_calculator::update_pool_reward(pool_state, &mut reward_config, slot)?;
+    
+    let reward_per_slot = match reward_config.emission_type {
+        EmissionType::FixedRate => reward_config.emission_rate,
+        EmissionType::BlockBased => RewardCalculator::calculate_block_rate(
+            reward_config.initial_block_rate,
+            reward_config.decay_factor,
+            reward_config.blocks_per_period,
+            slot,
+        )?,
+    };
+    
+    Ok(EmissionQuote {
+        emission_type: reward_config.emission_type,
+        reward_per_slot,
+        total_staked: pool_state.total_staked,
+        total_deposited: pool_state.total_deposited,
+        total_lp_supply: pool_state.total_lp_supply,
+        acc_reward_per_share: reward_config.acc_reward_per_share,
+        slot,
+    })
+}
+
+/// 查询池子累计统计数据
+pub fn quote_pool_stats_handler(ctx: Context<QuotePool>) -> Result<PoolStats> {
+    let pool_state = &ctx.accounts.pool_state;
+    
+    Ok(PoolStats {
+        total_deposited: pool_state.total_deposited,
+        total_staked: pool_state.total_staked,
+        cumulative_deposited: pool_state.cumulative_deposited,
+        cumulative_withdrawn: pool_state.cumulative_withdrawn,
+        active_positions: pool_state.active_positions,
+        staker_count: pool_state.staker_count,
+        total_rewards_claimed: pool_state.total_rewards_claimed,
+        last_activity_slot: pool_state.last_activity_slot,
+    })
+}
+
+#[derive(Accounts)]
+pub struct QuotePendingReward<'info> {
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    #[account(
+        constraint = user_position.pool == pool_state.key() @ LpStakingError::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+#[derive(Accounts)]
+pub struct QuotePool<'info> {
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteEmission<'info> {
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+}
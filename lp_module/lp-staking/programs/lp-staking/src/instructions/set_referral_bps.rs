@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::state::{PoolState, RewardConfig};
+
+/// 设置推荐奖励比例（仅管理员）
+///
+/// 推荐奖励额外从奖励金库支付，不从被推荐人的奖励中扣除
+pub fn set_referral_bps_handler(ctx: Context<SetReferralBps>, referral_bps: u64) -> Result<()> {
+    require!(referral_bps <= BASIS_POINTS, LpStakingError::InvalidReferralBps);
+    
+    let reward_config = &mut ctx.accounts.reward_config;
+    let previous = reward_config.referral_bps;
+    reward_config.referral_bps = referral_bps;
+    
+    msg!("Referral share updated!");
+    msg!("Referral bps: {} -> {}", previous, referral_bps);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetReferralBps<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::state::PoolState;
+
+/// 设置国库地址（仅管理员）
+///
+/// 管理员从奖励金库提取的资金只能转入该地址
+pub fn set_treasury_handler(ctx: Context<SetTreasury>, treasury: Pubkey) -> Result<()> {
+    require!(treasury != Pubkey::default(), LpStakingError::InvalidTreasury);
+    
+    let pool_state = &mut ctx.accounts.pool_state;
+    let previous = pool_state.treasury;
+    pool_state.treasury = treasury;
+    
+    msg!("Treasury updated!");
+    msg!("Treasury: {} -> {}", previous, treasury);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+}
@@ -0,0 +1,215 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::{SnapshotTakenEvent, StakeProvenEvent};
+use crate::state::{PoolState, Snapshot, StakeHistory};
+
+/// 质押证明（prove_stake 的返回值）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct StakeProof {
+    /// 快照编号
+    pub snapshot_id: u64,
+    /// 快照 slot
+    pub slot: u64,
+    /// 用户钱包地址
+    pub owner: Pubkey,
+    /// 用户在快照 slot 结束时的质押量
+    pub staked_amount: u64,
+    /// 池子在快照时的总质押量
+    pub total_staked: u64,
+}
+
+/// 创建质押快照（仅管理员）
+///
+/// 快照记录编号、slot 与此时的池子总质押量；用户质押量由其 StakeHistory 检查点给出，
+/// 以快照 slot 结束时的状态为准
+pub fn take_snapshot_handler(ctx: Context<TakeSnapshot>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pool_state = &mut ctx.accounts.pool_state;
+    let snapshot = &mut ctx.accounts.snapshot;
+    
+    snapshot.version = Snapshot::VERSION;
+    snapshot.pool = pool_state.key();
+    snapshot.id = pool_state.snapshot_count;
+    snapshot.slot = clock.slot;
+    snapshot.timestamp = clock.unix_timestamp;
+    snapshot.bump = ctx.bumps.snapshot;
+    snapshot.total_staked = pool_state.total_staked;
+    
+    pool_state.snapshot_count = pool_state.snapshot_count
+        .checked_add(1)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    emit!(SnapshotTakenEvent {
+        snapshot_id: snapshot.id,
+        slot: snapshot.slot,
+        total_staked: pool_state.total_staked,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Snapshot taken!");
+    msg!("Snapshot id: {}", snapshot.id);
+    msg!("Slot: {}", snapshot.slot);
+    
+    Ok(())
+}
+
+/// 证明用户在快照时的质押量（只读，通过 return data 返回并发出事件）
+///
+/// v1 快照未记录总质押量，需要提供升级前的全局 StakeHistory
+pub fn prove_stake_handler(ctx: Context<ProveStake>, snapshot_id: u64) -> Result<StakeProof> {
+    let snapshot = &ctx.accounts.snapshot;
+    let user_history = &ctx.accounts.user_stake_history;
+    
+    let total_staked = if snapshot.version >= Snapshot::VERSION {
+        snapshot.total_staked
+    } else {
+        ctx.accounts
+            .global_stake_history
+            .as_ref()
+            .ok_or(LpStakingError::UnsupportedAccountVersion)?
+            .amount_at(snapshot.slot)
+    };
+    
+    let proof = StakeProof {
+        snapshot_id,
+        slot: snapshot.slot,
+        owner: user_history.owner,
+        staked_amount: user_history.amount_at(snapshot.slot),
+        total_staked,
+    };
+    
+    emit!(StakeProvenEvent {
+        snapshot_id,
+        slot: proof.slot,
+        owner: proof.owner,
+        staked_amount: proof.staked_amount,
+        total_staked: proof.total_staked,
+    });
+    
+    Ok(proof)
+}
+
+/// 查询池子在 slot 结束时的总质押量（只读）
+///
+/// 全局质押量历史按分页存储：slot 不早于本页第一个检查点（第 0 页除外），
+/// 且早于下一页（已创建时）的第一个检查点
+pub fn total_staked_at_handler(ctx: Context<QueryTotalStaked>, page: u32, slot: u64) -> Result<u64> {
+    let history = &ctx.accounts.stake_history;
+    if page > 0 {
+        require!(
+            history.first_slot().is_some_and(|first| first <= slot),
+            LpStakingError::StakeHistoryPageMismatch
+        );
+    }
+    
+    let next_info = &ctx.accounts.next_stake_history;
+    if !next_info.data_is_empty() {
+        let next = StakeHistory::try_deserialize(&mut &next_info.try_borrow_data()?[..])?;
+        require!(
+            next.first_slot().is_none_or(|first| slot < first),
+            LpStakingError::StakeHistoryPageMismatch
+        );
+    }
+    
+    Ok(history.amount_at(slot))
+}
+
+#[derive(Accounts)]
+pub struct TakeSnapshot<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = Snapshot::LEN,
+        seeds = [
+            SNAPSHOT_SEED,
+            pool_state.key().as_ref(),
+            &pool_state.snapshot_count.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(snapshot_id: u64)]
+pub struct ProveStake<'info> {
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [
+            SNAPSHOT_SEED,
+            pool_state.key().as_ref(),
+            &snapshot_id.to_le_bytes()
+        ],
+        bump = snapshot.bump,
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    #[account(
+        seeds = [
+            STAKE_HISTORY_SEED,
+            user_stake_history.owner.as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_stake_history.bump,
+    )]
+    pub user_stake_history: Account<'info, StakeHistory>,
+    
+    /// 未分页的旧全局质押量历史（已停止更新，仅证明 v1 快照时需要）
+    #[account(
+        seeds = [STAKE_HISTORY_SEED, pool_state.key().as_ref()],
+        bump = global_stake_history.bump,
+    )]
+    pub global_stake_history: Option<Account<'info, StakeHistory>>,
+}
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+pub struct QueryTotalStaked<'info> {
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    /// 包含查询 slot 的全局质押量历史分页
+    #[account(
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &page.to_le_bytes()
+        ],
+        bump = stake_history.bump,
+    )]
+    pub stake_history: Account<'info, StakeHistory>,
+    
+    /// 下一个分页
+    /// CHECK: 地址由种子校验；尚未创建时为空账户，在 handler 中处理
+    #[account(
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &page.saturating_add(1).to_le_bytes()
+        ],
+        bump,
+    )]
+    pub next_stake_history: UncheckedAccount<'info>,
+}
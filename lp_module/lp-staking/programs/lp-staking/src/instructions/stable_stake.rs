@@ -0,0 +1,357 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, System};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::{StableRewardClaimedEvent, StableStakeEvent};
+use crate::state::{PoolState, StableStakePosition, StableSwapState};
+use crate::utils::ensure_rent_exempt;
+
+/// 设置 StableSwap LP 质押奖励的排放速率（仅管理员）
+///
+/// 先按原速率结算到当前 slot，新速率不追溯之前的区间
+pub fn set_stable_reward_rate_handler(ctx: Context<SetStableRewardRate>, reward_rate: u64) -> Result<()> {
+    let stable_swap = &mut ctx.accounts.stable_swap;
+    stable_swap.upgrade();
+    stable_swap.update_reward(Clock::get()?.slot)?;
+    
+    let previous = stable_swap.reward_rate;
+    stable_swap.reward_rate = reward_rate;
+    
+    msg!("StableSwap reward rate updated!");
+    msg!("Reward rate: {} -> {} lamports/slot", previous, reward_rate);
+    
+    Ok(())
+}
+
+/// 向 StableSwap LP 质押奖励金库注资（任何人都可调用）
+///
+/// 排放量以 total_funded 为上限，与单币种池的奖励金库分开记账
+pub fn fund_stable_rewards_handler(ctx: Context<FundStableRewards>, amount: u64) -> Result<()> {
+    require!(amount > 0, LpStakingError::InvalidAmount);
+    
+    let stable_swap = &mut ctx.accounts.stable_swap;
+    stable_swap.upgrade();
+    
+    // 1. 先按原有资金结算排放，新资金不追溯之前的区间
+    stable_swap.update_reward(Clock::get()?.slot)?;
+    
+    // 2. 金库首次注资时由注资者补足免租最低余额，不计入注资
+    let funder = ctx.accounts.funder.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let reward_vault = ctx.accounts.stable_reward_vault.to_account_info();
+    ensure_rent_exempt(&reward_vault, &funder, &system_program, 0)?;
+    
+    // 3. 转入并记账
+    system_program::transfer(
+        CpiContext::new(
+            system_program,
+            system_program::Transfer {
+                from: funder,
+                to: reward_vault,
+            },
+        ),
+        amount,
+    )?;
+    stable_swap.total_funded = stable_swap.total_funded
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    msg!("StableSwap rewards funded!");
+    msg!("Amount: {} lamports", amount);
+    msg!("Total funded: {} lamports", stable_swap.total_funded);
+    
+    Ok(())
+}
+
+/// 质押 StableSwap LP
+///
+/// LP 转入由 StableSwap PDA 控制的托管金库，按质押数量分享 reward_rate 排放的奖励
+pub fn stable_stake_handler(ctx: Context<StableStake>, amount: u64) -> Result<()> {
+    require!(!ctx.accounts.pool_state.paused, LpStakingError::PoolPaused);
+    require!(amount > 0, LpStakingError::InvalidAmount);
+    
+    let accounts = &mut *ctx.accounts;
+    let stable_swap = &mut accounts.stable_swap;
+    stable_swap.upgrade();
+    stable_swap.update_reward(Clock::get()?.slot)?;
+    
+    // 1. 首次质押时初始化仓位
+    let position = &mut accounts.stable_stake_position;
+    if position.version == 0 {
+        position.version = StableStakePosition::VERSION;
+        position.owner = accounts.user.key();
+        position.stable_swap = stable_swap.key();
+        position.bump = ctx.bumps.stable_stake_position;
+    }
+    
+    // 2. 转入 LP 到托管金库
+    token::transfer(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.user_lp_account.to_account_info(),
+                to: accounts.stable_stake_vault.to_account_info(),
+                authority: accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    
+    // 3. 按原质押数量结算奖励后更新质押数量
+    let staked_amount = position.staked_amount
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    position.settle(stable_swap.acc_reward_per_share, staked_amount)?;
+    stable_swap.total_staked = stable_swap.total_staked
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    emit!(StableStakeEvent {
+        user: accounts.user.key(),
+        amount: amount as i64,
+        staked_amount,
+        total_staked: stable_swap.total_staked,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("StableSwap LP stake successful!");
+    msg!("Staked: {}", amount);
+    msg!("Total staked: {}", stable_swap.total_staked);
+    
+    Ok(())
+}
+
+/// 解除质押 StableSwap LP
+///
+/// 暂停期间仍可解除质押，已结算的奖励保留在仓位中等待领取
+pub fn stable_unstake_handler(ctx: Context<StableStake>, amount: u64) -> Result<()> {
+    require!(amount > 0, LpStakingError::InvalidAmount);
+    
+    let accounts = &mut *ctx.accounts;
+    let stable_swap = &mut accounts.stable_swap;
+    stable_swap.update_reward(Clock::get()?.slot)?;
+    
+    // 1. 结算奖励并扣减质押数量
+    let position = &mut accounts.stable_stake_position;
+    let staked_amount = position.staked_amount
+        .checked_sub(amount)
+        .ok_or(LpStakingError::InsufficientStaked)?;
+    position.settle(stable_swap.acc_reward_per_share, staked_amount)?;
+    stable_swap.total_staked = stable_swap.total_staked
+        .checked_sub(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    // 2. StableSwap PDA 签名从托管金库转出 LP
+    let pool_key = accounts.pool_state.key();
+    let seeds = &[STABLE_SWAP_SEED, pool_key.as_ref(), &[stable_swap.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.stable_stake_vault.to_account_info(),
+                to: accounts.user_lp_account.to_account_info(),
+                authority: stable_swap.to_account_info(),
+            },
+            &[&seeds[..]],
+        ),
+        amount,
+    )?;
+    
+    emit!(StableStakeEvent {
+        user: accounts.user.key(),
+        amount: -(amount as i64),
+        staked_amount,
+        total_staked: stable_swap.total_staked,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("StableSwap LP unstake successful!");
+    msg!("Unstaked: {}", amount);
+    msg!("Total staked: {}", stable_swap.total_staked);
+    
+    Ok(())
+}
+
+/// 领取 StableSwap LP 质押奖励（SOL）
+pub fn stable_claim_handler(ctx: Context<StableClaim>) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let stable_swap = &mut accounts.stable_swap;
+    stable_swap.update_reward(Clock::get()?.slot)?;
+    
+    // 1. 结算奖励
+    let position = &mut accounts.stable_stake_position;
+    let staked_amount = position.staked_amount;
+    position.settle(stable_swap.acc_reward_per_share, staked_amount)?;
+    let amount = position.pending_reward;
+    require!(amount > 0, LpStakingError::NoRewardToClaim);
+    position.pending_reward = 0;
+    
+    // 2. 奖励金库 PDA 签名转出（已排放奖励不超过注资，金库余额足够支付）
+    let stable_swap_key = stable_swap.key();
+    let seeds = &[
+        STABLE_REWARD_VAULT_SEED,
+        stable_swap_key.as_ref(),
+        &[ctx.bumps.stable_reward_vault],
+    ];
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: accounts.stable_reward_vault.to_account_info(),
+                to: accounts.user.to_account_info(),
+            },
+            &[&seeds[..]],
+        ),
+        amount,
+    )?;
+    
+    emit!(StableRewardClaimedEvent {
+        user: accounts.user.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("StableSwap reward claimed!");
+    msg!("Amount: {} lamports", amount);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetStableRewardRate<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+    
+    #[account(
+        mut,
+        seeds = [STABLE_SWAP_SEED, pool_state.key().as_ref()],
+        bump = stable_swap.bump,
+    )]
+    pub stable_swap: Box<Account<'info, StableSwapState>>,
+}
+
+#[derive(Accounts)]
+pub struct FundStableRewards<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+    
+    #[account(
+        mut,
+        seeds = [STABLE_SWAP_SEED, pool_state.key().as_ref()],
+        bump = stable_swap.bump,
+    )]
+    pub stable_swap: Box<Account<'info, StableSwapState>>,
+    
+    /// StableSwap LP 质押奖励金库（PDA，存放 SOL 奖励）
+    #[account(
+        mut,
+        seeds = [STABLE_REWARD_VAULT_SEED, stable_swap.key().as_ref()],
+        bump,
+    )]
+    pub stable_reward_vault: SystemAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StableStake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+    
+    #[account(
+        mut,
+        seeds = [STABLE_SWAP_SEED, pool_state.key().as_ref()],
+        bump = stable_swap.bump,
+        has_one = lp_mint,
+    )]
+    pub stable_swap: Box<Account<'info, StableSwapState>>,
+    
+    pub lp_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StableStakePosition::LEN,
+        seeds = [STABLE_STAKE_SEED, user.key().as_ref(), stable_swap.key().as_ref()],
+        bump,
+    )]
+    pub stable_stake_position: Box<Account<'info, StableStakePosition>>,
+    
+    /// 质押 LP 的托管金库（由 StableSwap PDA 控制）
+    #[account(
+        init_if_needed,
+        payer = user,
+        token::mint = lp_mint,
+        token::authority = stable_swap,
+        seeds = [STABLE_STAKE_VAULT_SEED, stable_swap.key().as_ref()],
+        bump,
+    )]
+    pub stable_stake_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = user,
+    )]
+    pub user_lp_account: Box<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StableClaim<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+    
+    #[account(
+        mut,
+        seeds = [STABLE_SWAP_SEED, pool_state.key().as_ref()],
+        bump = stable_swap.bump,
+    )]
+    pub stable_swap: Box<Account<'info, StableSwapState>>,
+    
+    #[account(
+        mut,
+        seeds = [STABLE_STAKE_SEED, user.key().as_ref(), stable_swap.key().as_ref()],
+        bump = stable_stake_position.bump,
+        constraint = stable_stake_position.owner == user.key() @ LpStakingError::Unauthorized,
+    )]
+    pub stable_stake_position: Box<Account<'info, StableStakePosition>>,
+    
+    /// StableSwap LP 质押奖励金库（PDA，存放 SOL 奖励）
+    #[account(
+        mut,
+        seeds = [STABLE_REWARD_VAULT_SEED, stable_swap.key().as_ref()],
+        bump,
+    )]
+    pub stable_reward_vault: SystemAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,580 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::{StableLiquidityEvent, StableSwapEvent};
+use crate::state::{PoolState, StableSwapState, StableToken};
+use crate::utils::{
+    calculate_add_liquidity, calculate_remove_liquidity, calculate_remove_liquidity_one, calculate_swap,
+};
+
+/// 创建 StableSwap 双稳定币池（仅管理员）
+///
+/// 币种 A 固定为池子的 wrappedUSDC，币种 B 由管理员指定（例如 wrappedUSDT）。
+/// LP Token 独立于单币种池的 LP Token，按不变量 D 铸造；
+/// 该 LP 通过 stable_stake 质押挖矿（见 instructions/stable_stake.rs）
+pub fn initialize_stable_swap_handler(
+    ctx: Context<InitializeStableSwap>,
+    amp: u64,
+    fee_bps: u64,
+) -> Result<()> {
+    require!(
+        amp > 0 && amp <= StableSwapState::MAX_AMP,
+        LpStakingError::InvalidStableSwapParams
+    );
+    require!(
+        fee_bps <= StableSwapState::MAX_FEE_BPS,
+        LpStakingError::InvalidStableSwapParams
+    );
+    
+    let stable_swap = &mut ctx.accounts.stable_swap;
+    stable_swap.version = StableSwapState::VERSION;
+    stable_swap.pool = ctx.accounts.pool_state.key();
+    stable_swap.token_a_mint = ctx.accounts.token_a_mint.key();
+    stable_swap.token_b_mint = ctx.accounts.token_b_mint.key();
+    stable_swap.vault_a = ctx.accounts.vault_a.key();
+    stable_swap.vault_b = ctx.accounts.vault_b.key();
+    stable_swap.lp_mint = ctx.accounts.lp_mint.key();
+    stable_swap.amp = amp;
+    stable_swap.fee_bps = fee_bps;
+    stable_swap.reserve_a = 0;
+    stable_swap.reserve_b = 0;
+    stable_swap.lp_supply = 0;
+    stable_swap.bump = ctx.bumps.stable_swap;
+    
+    msg!("StableSwap pool initialized!");
+    msg!("Token A: {}", stable_swap.token_a_mint);
+    msg!("Token B: {}", stable_swap.token_b_mint);
+    msg!("Amp: {}, fee: {} bps", amp, fee_bps);
+    
+    Ok(())
+}
+
+/// 添加流动性（支持平衡与非平衡存入）
+///
+/// 首次添加需同时存入两个币种；非平衡存入按偏离比例收取手续费
+pub fn stable_add_liquidity_handler(
+    ctx: Context<StableLiquidity>,
+    amount_a: u64,
+    amount_b: u64,
+    min_lp_out: u64,
+) -> Result<()> {
+    require!(!ctx.accounts.pool_state.paused, LpStakingError::PoolPaused);
+    require!(amount_a > 0 || amount_b > 0, LpStakingError::InvalidAmount);
+    
+    let accounts = &mut *ctx.accounts;
+    let stable_swap = &accounts.stable_swap;
+    
+    // 1. 按不变量计算铸造数量
+    let lp_amount = calculate_add_liquidity(
+        stable_swap.amp,
+        stable_swap.fee_bps,
+        stable_swap.reserves(),
+        [amount_a, amount_b],
+        stable_swap.lp_supply,
+    )?;
+    require!(lp_amount > 0, LpStakingError::InvalidAmount);
+    require!(lp_amount >= min_lp_out, LpStakingError::SlippageExceeded);
+    
+    // 2. 转入两个币种
+    let user = accounts.user.to_account_info();
+    transfer_in(&accounts.token_program, &accounts.user_token_a, &accounts.vault_a, &user, amount_a)?;
+    transfer_in(&accounts.token_program, &accounts.user_token_b, &accounts.vault_b, &user, amount_b)?;
+    
+    // 3. 铸造 LP Token
+    let pool_key = accounts.pool_state.key();
+    let seeds = &[STABLE_SWAP_SEED, pool_key.as_ref(), &[stable_swap.bump]];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            MintTo {
+                mint: accounts.lp_mint.to_account_info(),
+                to: accounts.user_lp_account.to_account_info(),
+                authority: accounts.stable_swap.to_account_info(),
+            },
+            &[&seeds[..]],
+        ),
+        lp_amount,
+    )?;
+    
+    // 4. 更新池子状态
+    let stable_swap = &mut accounts.stable_swap;
+    let reserves = stable_swap.reserves();
+    stable_swap.set_reserves([
+        reserves[0].checked_add(amount_a).ok_or(LpStakingError::MathOverflow)?,
+        reserves[1].checked_add(amount_b).ok_or(LpStakingError::MathOverflow)?,
+    ]);
+    stable_swap.lp_supply = stable_swap.lp_supply
+        .checked_add(lp_amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    emit!(StableLiquidityEvent {
+        user: accounts.user.key(),
+        amount_a: amount_a as i64,
+        amount_b: amount_b as i64,
+        lp_amount: lp_amount as i64,
+        reserve_a: stable_swap.reserve_a,
+        reserve_b: stable_swap.reserve_b,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("StableSwap add liquidity successful!");
+    msg!("Deposited: {} A, {} B", amount_a, amount_b);
+    msg!("Minted LP: {}", lp_amount);
+    
+    Ok(())
+}
+
+/// 平衡移除流动性：按余额比例取出两个币种
+///
+/// 销毁全部 LP 的最后退出只能走此路径，两个币种余额全部取出
+pub fn stable_remove_liquidity_handler(
+    ctx: Context<StableLiquidity>,
+    lp_amount: u64,
+    min_amount_a: u64,
+    min_amount_b: u64,
+) -> Result<()> {
+    require!(!ctx.accounts.pool_state.paused, LpStakingError::PoolPaused);
+    
+    let accounts = &mut *ctx.accounts;
+    let stable_swap = &accounts.stable_swap;
+    
+    // 1. 按余额比例计算取出数量
+    let [amount_a, amount_b] = calculate_remove_liquidity(
+        stable_swap.reserves(),
+        lp_amount,
+        stable_swap.lp_supply,
+    )?;
+    require!(amount_a > 0 || amount_b > 0, LpStakingError::InvalidAmount);
+    require!(
+        amount_a >= min_amount_a && amount_b >= min_amount_b,
+        LpStakingError::SlippageExceeded
+    );
+    
+    // 2. 销毁 LP Token
+    token::burn(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            Burn {
+                mint: accounts.lp_mint.to_account_info(),
+                from: accounts.user_lp_account.to_account_info(),
+                authority: accounts.user.to_account_info(),
+            },
+        ),
+        lp_amount,
+    )?;
+    
+    // 3. 转出两个币种
+    let pool_key = accounts.pool_state.key();
+    let seeds = &[STABLE_SWAP_SEED, pool_key.as_ref(), &[stable_swap.bump]];
+    let stable_swap_info = accounts.stable_swap.to_account_info();
+    for (vault, user_token, amount) in [
+        (&accounts.vault_a, &accounts.user_token_a, amount_a),
+        (&accounts.vault_b, &accounts.user_token_b, amount_b),
+    ] {
+        if amount > 0 {
+            transfer_out(
+                &accounts.token_program,
+                vault,
+                user_token,
+                &stable_swap_info,
+                &[&seeds[..]],
+                amount,
+            )?;
+        }
+    }
+    
+    // 4. 更新池子状态
+    let stable_swap = &mut accounts.stable_swap;
+    let reserves = stable_swap.reserves();
+    stable_swap.set_reserves([
+        reserves[0].checked_sub(amount_a).ok_or(LpStakingError::InsufficientLiquidity)?,
+        reserves[1].checked_sub(amount_b).ok_or(LpStakingError::InsufficientLiquidity)?,
+    ]);
+    stable_swap.lp_supply = stable_swap.lp_supply
+        .checked_sub(lp_amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    emit!(StableLiquidityEvent {
+        user: accounts.user.key(),
+        amount_a: -(amount_a as i64),
+        amount_b: -(amount_b as i64),
+        lp_amount: -(lp_amount as i64),
+        reserve_a: stable_swap.reserve_a,
+        reserve_b: stable_swap.reserve_b,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("StableSwap balanced remove liquidity successful!");
+    msg!("Burned LP: {}", lp_amount);
+    msg!("Withdrawn: {} A, {} B", amount_a, amount_b);
+    
+    Ok(())
+}
+
+/// 单币种移除流动性
+///
+/// 不能销毁全部 LP 供应量，最后的退出需使用平衡移除
+pub fn stable_remove_liquidity_one_handler(
+    ctx: Context<StableLiquidity>,
+    lp_amount: u64,
+    token_out: StableToken,
+    min_amount_out: u64,
+) -> Result<()> {
+    require!(!ctx.accounts.pool_state.paused, LpStakingError::PoolPaused);
+    
+    let accounts = &mut *ctx.accounts;
+    let stable_swap = &accounts.stable_swap;
+    let index = token_out.index();
+    
+    // 1. 按不变量计算取出数量
+    let (amount_out, fee) = calculate_remove_liquidity_one(
+        stable_swap.amp,
+        stable_swap.fee_bps,
+        stable_swap.reserves(),
+        lp_amount,
+        stable_swap.lp_supply,
+        index,
+    )?;
+    require!(amount_out > 0, LpStakingError::InvalidAmount);
+    require!(amount_out >= min_amount_out, LpStakingError::SlippageExceeded);
+    
+    // 2. 销毁 LP Token
+    token::burn(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            Burn {
+                mint: accounts.lp_mint.to_account_info(),
+                from: accounts.user_lp_account.to_account_info(),
+                authority: accounts.user.to_account_info(),
+            },
+        ),
+        lp_amount,
+    )?;
+    
+    // 3. 转出指定币种
+    let pool_key = accounts.pool_state.key();
+    let seeds = &[STABLE_SWAP_SEED, pool_key.as_ref(), &[stable_swap.bump]];
+    let (vault, user_token) = match token_out {
+        StableToken::A => (&accounts.vault_a, &accounts.user_token_a),
+        StableToken::B => (&accounts.vault_b, &accounts.user_token_b),
+    };
+    transfer_out(
+        &accounts.token_program,
+        vault,
+        user_token,
+        &accounts.stable_swap.to_account_info(),
+        &[&seeds[..]],
+        amount_out,
+    )?;
+    
+    // 4. 更新池子状态
+    let stable_swap = &mut accounts.stable_swap;
+    let mut reserves = stable_swap.reserves();
+    reserves[index] = reserves[index]
+        .checked_sub(amount_out)
+        .ok_or(LpStakingError::InsufficientLiquidity)?;
+    stable_swap.set_reserves(reserves);
+    stable_swap.lp_supply = stable_swap.lp_supply
+        .checked_sub(lp_amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    let (amount_a, amount_b) = match token_out {
+        StableToken::A => (-(amount_out as i64), 0),
+        StableToken::B => (0, -(amount_out as i64)),
+    };
+    emit!(StableLiquidityEvent {
+        user: accounts.user.key(),
+        amount_a,
+        amount_b,
+        lp_amount: -(lp_amount as i64),
+        reserve_a: stable_swap.reserve_a,
+        reserve_b: stable_swap.reserve_b,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("StableSwap remove liquidity successful!");
+    msg!("Burned LP: {}", lp_amount);
+    msg!("Withdrawn: {} of token {:?} (fee {})", amount_out, token_out, fee);
+    
+    Ok(())
+}
+
+/// 兑换：投入 token_in，获得另一币种
+pub fn stable_swap_handler(
+    ctx: Context<StableSwapTrade>,
+    token_in: StableToken,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    require!(!ctx.accounts.pool_state.paused, LpStakingError::PoolPaused);
+    
+    let accounts = &mut *ctx.accounts;
+    let stable_swap = &accounts.stable_swap;
+    let index_in = token_in.index();
+    let index_out = 1 - index_in;
+    let reserves = stable_swap.reserves();
+    
+    // 1. 计算输出数量
+    let (amount_out, fee) = calculate_swap(
+        stable_swap.amp,
+        stable_swap.fee_bps,
+        reserves[index_in],
+        reserves[index_out],
+        amount_in,
+    )?;
+    require!(amount_out > 0, LpStakingError::InvalidAmount);
+    require!(amount_out >= min_amount_out, LpStakingError::SlippageExceeded);
+    
+    // 2. 转入输入币种、转出输出币种
+    let user = accounts.user.to_account_info();
+    let pool_key = accounts.pool_state.key();
+    let seeds = &[STABLE_SWAP_SEED, pool_key.as_ref(), &[stable_swap.bump]];
+    let stable_swap_info = accounts.stable_swap.to_account_info();
+    let (vault_in, user_in, vault_out, user_out) = match token_in {
+        StableToken::A => (&accounts.vault_a, &accounts.user_token_a, &accounts.vault_b, &accounts.user_token_b),
+        StableToken::B => (&accounts.vault_b, &accounts.user_token_b, &accounts.vault_a, &accounts.user_token_a),
+    };
+    transfer_in(&accounts.token_program, user_in, vault_in, &user, amount_in)?;
+    transfer_out(
+        &accounts.token_program,
+        vault_out,
+        user_out,
+        &stable_swap_info,
+        &[&seeds[..]],
+        amount_out,
+    )?;
+    
+    // 3. 更新余额（手续费留在池中，归 LP 所有）
+    let mut reserves = reserves;
+    reserves[index_in] = reserves[index_in]
+        .checked_add(amount_in)
+        .ok_or(LpStakingError::MathOverflow)?;
+    reserves[index_out] = reserves[index_out]
+        .checked_sub(amount_out)
+        .ok_or(LpStakingError::InsufficientLiquidity)?;
+    accounts.stable_swap.set_reserves(reserves);
+    
+    emit!(StableSwapEvent {
+        user: accounts.user.key(),
+        token_in,
+        amount_in,
+        amount_out,
+        fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("StableSwap successful!");
+    msg!("In: {} of token {:?}", amount_in, token_in);
+    msg!("Out: {} (fee {})", amount_out, fee);
+    
+    Ok(())
+}
+
+/// 用户签名转入金库
+fn transfer_in<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: authority.clone(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// StableSwap PDA 签名从金库转出
+fn transfer_out<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+#[derive(Accounts)]
+pub struct InitializeStableSwap<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+    
+    #[account(address = pool_state.wrapped_usdc_mint)]
+    pub token_a_mint: Box<Account<'info, Mint>>,
+    
+    /// 币种 B（需与币种 A 精度一致）
+    #[account(
+        constraint = token_b_mint.key() != token_a_mint.key() @ LpStakingError::InvalidStableSwapParams,
+        constraint = token_b_mint.decimals == token_a_mint.decimals @ LpStakingError::InvalidStableSwapParams,
+    )]
+    pub token_b_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = StableSwapState::LEN,
+        seeds = [STABLE_SWAP_SEED, pool_state.key().as_ref()],
+        bump
+    )]
+    pub stable_swap: Box<Account<'info, StableSwapState>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = token_a_mint,
+        token::authority = stable_swap,
+        seeds = [STABLE_VAULT_SEED, stable_swap.key().as_ref(), token_a_mint.key().as_ref()],
+        bump
+    )]
+    pub vault_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = token_b_mint,
+        token::authority = stable_swap,
+        seeds = [STABLE_VAULT_SEED, stable_swap.key().as_ref(), token_b_mint.key().as_ref()],
+        bump
+    )]
+    pub vault_b: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = token_a_mint.decimals,
+        mint::authority = stable_swap,
+        seeds = [STABLE_LP_MINT_SEED, stable_swap.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StableLiquidity<'info> {
+    pub user: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+    
+    #[account(
+        mut,
+        seeds = [STABLE_SWAP_SEED, pool_state.key().as_ref()],
+        bump = stable_swap.bump,
+        has_one = vault_a,
+        has_one = vault_b,
+        has_one = lp_mint,
+    )]
+    pub stable_swap: Box<Account<'info, StableSwapState>>,
+    
+    #[account(mut)]
+    pub vault_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub vault_b: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub lp_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        mut,
+        token::mint = stable_swap.token_a_mint,
+        token::authority = user,
+    )]
+    pub user_token_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = stable_swap.token_b_mint,
+        token::authority = user,
+    )]
+    pub user_token_b: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = user,
+    )]
+    pub user_lp_account: Box<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct StableSwapTrade<'info> {
+    pub user: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+    
+    #[account(
+        mut,
+        seeds = [STABLE_SWAP_SEED, pool_state.key().as_ref()],
+        bump = stable_swap.bump,
+        has_one = vault_a,
+        has_one = vault_b,
+    )]
+    pub stable_swap: Box<Account<'info, StableSwapState>>,
+    
+    #[account(mut)]
+    pub vault_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub vault_b: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = stable_swap.token_a_mint,
+        token::authority = user,
+    )]
+    pub user_token_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = stable_swap.token_b_mint,
+        token::authority = user,
+    )]
+    pub user_token_b: Box<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+}
@@ -1,25 +1,61 @@
 use anchor_lang::prelude::*;
 use crate::constants::*;
 use crate::errors::LpStakingError;
-use crate::state::{PoolState, UserPosition, RewardConfig};
-use crate::utils::reward_calculator;
+use crate::state::{PoolState, UserPosition, RewardConfig, StakeHistory};
+use crate::utils::{reward_calculator, StakeHistoryRecorder};
 
 /// 质押 LP Token 到池子，开始赚取奖励
-pub fn stake_handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
+///
+/// * `referrer` - 可选推荐人，仅在仓位首次记录时生效
+pub fn stake_handler(ctx: Context<Stake>, amount: u64, referrer: Option<Pubkey>) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = &mut *ctx.accounts;
+    
+    process_stake(
+        &mut accounts.pool_state,
+        &mut accounts.user_position,
+        &mut accounts.reward_config,
+        amount,
+        referrer,
+        &clock,
+    )?;
+    
+    StakeHistoryRecorder {
+        payer: &accounts.user.to_account_info(),
+        system_program: accounts.system_program.as_ref(),
+        pool: accounts.pool_state.key(),
+        slot: clock.slot,
+    }
+    .record_stake_change(
+        &mut accounts.user_stake_history,
+        ctx.bumps.user_stake_history,
+        &mut accounts.global_stake_history,
+        ctx.bumps.global_stake_history,
+        &accounts.user_position,
+        &mut accounts.pool_state,
+    )
+}
+
+/// 质押核心逻辑（stake 与 deposit_and_stake 共用）
+pub fn process_stake(
+    pool_state: &mut PoolState,
+    user_position: &mut UserPosition,
+    reward_config: &mut RewardConfig,
+    amount: u64,
+    referrer: Option<Pubkey>,
+    clock: &Clock,
+) -> Result<()> {
     // 参数验证
     require!(amount >= MIN_STAKE_AMOUNT, LpStakingError::InvalidAmount);
     
-    let user_position = &mut ctx.accounts.user_position;
-    let pool_state = &mut ctx.accounts.pool_state;
-    let reward_config = &mut ctx.accounts.reward_config;
-    let clock = Clock::get()?;
-    
     // 检查用户 LP 余额是否充足
     require!(
         user_position.lp_balance >= amount,
         LpStakingError::InsufficientLpTokens
     );
     
+    user_position.record_referrer(referrer)?;
+    
     // 1. 更新奖励池状态（在状态改变前）
     reward_calculator::update_pool_reward(
         pool_state,
@@ -27,11 +63,12 @@ pub fn stake_handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
         clock.slot,
     )?;
     
-    // 2. 如果用户已有质押，先结算之前的奖励
-    if user_position.staked_amount > 0 {
-        let pending = reward_calculator::calculate_pending_reward(
-            user_position.staked_amount,
-            reward_config.acc_reward_per_share,
+    // 2. 如果用户已有质押，先按原权重结算之前的奖励
+    let weight_before = user_position.reward_weight()?;
+    if weight_before > 0 {
+        let pending = reward_calculator::settle_pending_reward(
+            reward_config,
+            weight_before,
             user_position.reward_debt,
         )?;
         
@@ -41,6 +78,7 @@ pub fn stake_handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
     }
     
     // 3. 更新用户质押数量
+    let staked_before = user_position.staked_amount;
     user_position.lp_balance = user_position.lp_balance
         .checked_sub(amount)
         .ok_or(LpStakingError::MathOverflow)?;
@@ -49,21 +87,34 @@ pub fn stake_handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
         .checked_add(amount)
         .ok_or(LpStakingError::MathOverflow)?;
     
-    // 4. 更新池子总质押量
+    // 4. 更新池子总质押量与统计数据
     pool_state.total_staked = pool_state.total_staked
         .checked_add(amount)
         .ok_or(LpStakingError::MathOverflow)?;
+    pool_state.update_staker_count(staked_before, user_position.staked_amount)?;
+    pool_state.record_activity(clock.slot);
+    
+    // 5. 按数量加权调整连续质押起始时间，并刷新忠诚度加成
+    user_position.last_stake_time = reward_calculator::adjust_tenure_start(
+        user_position.last_stake_time,
+        staked_before,
+        user_position.staked_amount,
+        clock.unix_timestamp,
+    );
+    reward_calculator::refresh_loyalty_bonus(
+        pool_state,
+        reward_config,
+        user_position,
+        clock.unix_timestamp,
+    )?;
     
-    // 5. 更新 reward_debt（基于新的质押量）
-    user_position.reward_debt = (user_position.staked_amount as u128)
+    // 6. 更新 reward_debt（基于新的奖励权重）
+    user_position.reward_debt = (user_position.reward_weight()? as u128)
         .checked_mul(reward_config.acc_reward_per_share)
         .ok_or(LpStakingError::MathOverflow)?;
     
-    // 6. 更新时间戳
-    user_position.last_stake_time = clock.unix_timestamp;
-    
     msg!("Stake successful!");
-    msg!("User: {}", ctx.accounts.user.key());
+    msg!("User: {}", user_position.owner);
     msg!("Staked LP amount: {}", amount);
     msg!("Total staked: {}", user_position.staked_amount);
     msg!("Pool total staked: {}", pool_state.total_staked);
@@ -101,4 +152,34 @@ pub struct Stake<'info> {
         bump = reward_config.bump,
     )]
     pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 用户质押量历史
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub user_stake_history: Account<'info, StakeHistory>,
+    
+    /// 全局质押量历史的当前分页
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &pool_state.stake_history_page.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub global_stake_history: Account<'info, StakeHistory>,
+    
+    pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,298 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::StakeForEvent;
+use crate::instructions::deposit::{process_deposit, DepositAccounts};
+use crate::instructions::stake::process_stake;
+use crate::state::{PoolState, UserPosition, RewardConfig, StakeHistory};
+use crate::utils::StakeHistoryRecorder;
+
+/// 代存：出资者存入 wrappedUSDC，LP Token 记入受益人的仓位
+///
+/// 受益人的仓位不存在时由出资者支付租金创建；代存不记录推荐人
+pub fn deposit_for_handler(
+    ctx: Context<DepositFor>,
+    amount: u64,
+    min_lp_out: u64,
+    expiry_ts: i64,
+) -> Result<()> {
+    let beneficiary_position_bump = ctx.bumps.beneficiary_position;
+    let accounts = ctx.accounts;
+    
+    let lp_amount = process_deposit(
+        DepositAccounts {
+            user: &accounts.payer,
+            owner: accounts.beneficiary.key(),
+            pool_state: &mut accounts.pool_state,
+            user_position: &mut accounts.beneficiary_position,
+            user_usdc_account: &accounts.payer_usdc_account,
+            pool_usdc_account: &accounts.pool_usdc_account,
+            lp_token_mint: &accounts.lp_token_mint,
+            user_lp_account: &accounts.beneficiary_lp_account,
+            token_program: &accounts.token_program,
+            price_feed: accounts.price_feed.as_ref().map(|feed| feed.as_ref()),
+        },
+        beneficiary_position_bump,
+        amount,
+        min_lp_out,
+        expiry_ts,
+        None,
+    )?;
+    
+    emit!(StakeForEvent {
+        payer: accounts.payer.key(),
+        beneficiary: accounts.beneficiary.key(),
+        usdc_amount: amount,
+        lp_amount,
+        staked: false,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// 代质押：出资者把自己仓位中未质押的 LP Token 转给受益人并直接质押
+///
+/// LP Token 从出资者的钱包转入受益人的钱包，与仓位记账保持一致；
+/// 之后解除质押与提取均由受益人自行操作
+pub fn stake_for_handler(ctx: Context<StakeFor>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = &mut *ctx.accounts;
+    
+    require!(amount >= MIN_STAKE_AMOUNT, LpStakingError::InvalidAmount);
+    require!(
+        accounts.payer_position.lp_balance >= amount,
+        LpStakingError::InsufficientLpTokens
+    );
+    
+    // 1. 转移 LP Token 到受益人钱包
+    let transfer_ctx = CpiContext::new(
+        accounts.token_program.to_account_info(),
+        Transfer {
+            from: accounts.payer_lp_account.to_account_info(),
+            to: accounts.beneficiary_lp_account.to_account_info(),
+            authority: accounts.payer.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, amount)?;
+    
+    // 2. 更新出资者仓位
+    accounts.payer_position.lp_balance = accounts.payer_position.lp_balance
+        .checked_sub(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    // 3. 初始化受益人仓位（如果是首次）并记入 LP 余额
+    let beneficiary_position = &mut accounts.beneficiary_position;
+    if beneficiary_position.owner == Pubkey::default() {
+        beneficiary_position.reset(
+            accounts.beneficiary.key(),
+            accounts.pool_state.key(),
+            ctx.bumps.beneficiary_position,
+        );
+        accounts.pool_state.open_position()?;
+    }
+    beneficiary_position.lp_balance = beneficiary_position.lp_balance
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    // 4. 质押到受益人仓位
+    process_stake(
+        &mut accounts.pool_state,
+        beneficiary_position,
+        &mut accounts.reward_config,
+        amount,
+        None,
+        &clock,
+    )?;
+    
+    StakeHistoryRecorder {
+        payer: &accounts.payer.to_account_info(),
+        system_program: accounts.system_program.as_ref(),
+        pool: accounts.pool_state.key(),
+        slot: clock.slot,
+    }
+    .record_stake_change(
+        &mut accounts.beneficiary_stake_history,
+        ctx.bumps.beneficiary_stake_history,
+        &mut accounts.global_stake_history,
+        ctx.bumps.global_stake_history,
+        &accounts.beneficiary_position,
+        &mut accounts.pool_state,
+    )?;
+    
+    emit!(StakeForEvent {
+        payer: accounts.payer.key(),
+        beneficiary: accounts.beneficiary.key(),
+        usdc_amount: 0,
+        lp_amount: amount,
+        staked: true,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositFor<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// 受益人（仓位所有者，可以是其他程序的 PDA）
+    /// CHECK: 仅用作仓位 PDA 种子与 LP 账户 authority 校验
+    pub beneficiary: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserPosition::LEN,
+        seeds = [
+            USER_POSITION_SEED,
+            beneficiary.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+        constraint = beneficiary_position.owner == Pubkey::default()
+            || beneficiary_position.owner == beneficiary.key() @ LpStakingError::Unauthorized,
+    )]
+    pub beneficiary_position: Box<Account<'info, UserPosition>>,
+    
+    #[account(
+        mut,
+        token::mint = pool_state.wrapped_usdc_mint,
+        token::authority = payer,
+    )]
+    pub payer_usdc_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        address = pool_state.pool_usdc_account,
+    )]
+    pub pool_usdc_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        address = pool_state.lp_token_mint,
+    )]
+    pub lp_token_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        mut,
+        token::mint = lp_token_mint,
+        token::authority = beneficiary,
+    )]
+    pub beneficiary_lp_account: Box<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    /// 价格账户（池子启用脱锚保护时必须传入）
+    /// CHECK: 地址必须与池子配置一致，布局在 handler 中校验
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StakeFor<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// 受益人（仓位所有者，可以是其他程序的 PDA）
+    /// CHECK: 仅用作仓位 PDA 种子与 LP 账户 authority 校验
+    #[account(constraint = beneficiary.key() != payer.key() @ LpStakingError::InvalidBeneficiary)]
+    pub beneficiary: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Box<Account<'info, RewardConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            payer.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = payer_position.bump,
+        constraint = payer_position.owner == payer.key() @ LpStakingError::Unauthorized,
+    )]
+    pub payer_position: Box<Account<'info, UserPosition>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserPosition::LEN,
+        seeds = [
+            USER_POSITION_SEED,
+            beneficiary.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+        constraint = beneficiary_position.owner == Pubkey::default()
+            || beneficiary_position.owner == beneficiary.key() @ LpStakingError::Unauthorized,
+    )]
+    pub beneficiary_position: Box<Account<'info, UserPosition>>,
+    
+    #[account(
+        mut,
+        token::mint = pool_state.lp_token_mint,
+        token::authority = payer,
+    )]
+    pub payer_lp_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        token::mint = pool_state.lp_token_mint,
+        token::authority = beneficiary,
+    )]
+    pub beneficiary_lp_account: Box<Account<'info, TokenAccount>>,
+    
+    /// 受益人质押量历史
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            beneficiary.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub beneficiary_stake_history: Box<Account<'info, StakeHistory>>,
+    
+    /// 全局质押量历史的当前分页
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &pool_state.stake_history_page.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub global_stake_history: Box<Account<'info, StakeHistory>>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    pub system_program: Program<'info, System>,
+}
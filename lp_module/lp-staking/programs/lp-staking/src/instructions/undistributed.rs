@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::System;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::UndistributedWithdrawnEvent;
+use crate::state::{PoolState, RewardConfig};
+use crate::utils::{reward_calculator, RewardVault};
+
+/// 设置未分配奖励的处理方式（仅管理员）
+///
+/// * true：无人质押期间的排放并入下一次分配
+/// * false：保留在未分配奖励中，由管理员提取到国库
+pub fn set_carry_over_undistributed_handler(
+    ctx: Context<SetCarryOverUndistributed>,
+    carry_over: bool,
+) -> Result<()> {
+    let reward_config = &mut ctx.accounts.reward_config;
+    
+    // 先按旧策略结算到当前 slot，避免新策略追溯生效
+    reward_calculator::update_pool_reward(
+        &ctx.accounts.pool_state,
+        reward_config,
+        Clock::get()?.slot,
+    )?;
+    reward_config.carry_over_undistributed = carry_over;
+    
+    msg!("Undistributed reward policy updated!");
+    msg!("Carry over: {}", carry_over);
+    msg!("Undistributed: {} lamports", reward_config.undistributed);
+    
+    Ok(())
+}
+
+/// 提取未分配奖励到国库（仅管理员）
+pub fn withdraw_undistributed_handler(
+    ctx: Context<WithdrawUndistributed>,
+    amount: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let treasury = ctx.accounts.treasury.to_account_info();
+    let accounts = &mut *ctx.accounts;
+    
+    // 1. 更新奖励池，将最新的无人质押排放计入未分配奖励
+    reward_calculator::update_pool_reward(
+        &accounts.pool_state,
+        &mut accounts.reward_config,
+        clock.slot,
+    )?;
+    
+    // 2. 校验提取数量
+    require!(amount > 0, LpStakingError::InvalidAmount);
+    require!(
+        amount <= accounts.reward_config.undistributed,
+        LpStakingError::InsufficientUndistributed
+    );
+    
+    // 3. 从奖励金库转账到国库
+    let reward_vault = RewardVault {
+        vault: &accounts.reward_vault,
+        pool_state: accounts.pool_state.key(),
+        bump: ctx.bumps.reward_vault,
+        system_program: accounts.system_program.as_ref(),
+    };
+    require!(
+        reward_vault.available()? >= amount,
+        LpStakingError::InsufficientRewardVault
+    );
+    reward_vault.transfer_to(&treasury, amount)?;
+    
+    // 4. 扣减未分配奖励并记录支付
+    let reward_config = &mut accounts.reward_config;
+    reward_config.undistributed = reward_config.undistributed
+        .checked_sub(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    reward_config.record_payout(amount)?;
+    
+    emit!(UndistributedWithdrawnEvent {
+        treasury: treasury.key(),
+        amount,
+        remaining: reward_config.undistributed,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Undistributed rewards withdrawn!");
+    msg!("Treasury: {}", treasury.key());
+    msg!("Amount: {} lamports", amount);
+    msg!("Remaining undistributed: {} lamports", reward_config.undistributed);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetCarryOverUndistributed<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUndistributed<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+        has_one = authority @ LpStakingError::Unauthorized,
+        constraint = pool_state.treasury != Pubkey::default() @ LpStakingError::InvalidTreasury,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 奖励金库（PDA，存放 SOL 奖励）
+    /// CHECK: 这是一个 PDA，用于存放奖励 SOL
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: AccountInfo<'info>,
+    
+    /// 国库地址
+    /// CHECK: 地址必须与池子记录的国库一致，仅接收 SOL
+    #[account(
+        mut,
+        address = pool_state.treasury @ LpStakingError::InvalidTreasury,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
@@ -1,25 +1,61 @@
 use anchor_lang::prelude::*;
 use crate::constants::*;
 use crate::errors::LpStakingError;
-use crate::state::{PoolState, UserPosition, RewardConfig};
-use crate::utils::reward_calculator;
+use crate::state::{PoolState, UserPosition, RewardConfig, StakeHistory};
+use crate::utils::{reward_calculator, StakeHistoryRecorder};
 
 /// 解除质押，取回 LP Token
 pub fn unstake_handler(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = &mut *ctx.accounts;
+    
+    process_unstake(
+        &mut accounts.pool_state,
+        &mut accounts.user_position,
+        &mut accounts.reward_config,
+        amount,
+        &clock,
+    )?;
+    
+    StakeHistoryRecorder {
+        payer: &accounts.user.to_account_info(),
+        system_program: accounts.system_program.as_ref(),
+        pool: accounts.pool_state.key(),
+        slot: clock.slot,
+    }
+    .record_stake_change(
+        &mut accounts.user_stake_history,
+        ctx.bumps.user_stake_history,
+        &mut accounts.global_stake_history,
+        ctx.bumps.global_stake_history,
+        &accounts.user_position,
+        &mut accounts.pool_state,
+    )
+}
+
+/// 解除质押核心逻辑（unstake 与 unstake_and_withdraw 共用）
+pub fn process_unstake(
+    pool_state: &mut PoolState,
+    user_position: &mut UserPosition,
+    reward_config: &mut RewardConfig,
+    amount: u64,
+    clock: &Clock,
+) -> Result<()> {
     // 参数验证
     require!(amount > 0, LpStakingError::InvalidAmount);
     
-    let user_position = &mut ctx.accounts.user_position;
-    let pool_state = &mut ctx.accounts.pool_state;
-    let reward_config = &mut ctx.accounts.reward_config;
-    let clock = Clock::get()?;
-    
     // 检查用户质押余额是否充足
     require!(
         user_position.staked_amount >= amount,
         LpStakingError::InsufficientStaked
     );
     
+    // 锁仓中的质押不可解除
+    require!(
+        user_position.staked_amount - amount >= user_position.active_locked_amount(clock.slot),
+        LpStakingError::StakeLocked
+    );
+    
     // 1. 更新奖励池状态
     reward_calculator::update_pool_reward(
         pool_state,
@@ -27,10 +63,10 @@ pub fn unstake_handler(ctx: Context<Unstake>, amount: u64) -> Result<()> {
         clock.slot,
     )?;
     
-    // 2. 结算待领取奖励
-    let pending = reward_calculator::calculate_pending_reward(
-        user_position.staked_amount,
-        reward_config.acc_reward_per_share,
+    // 2. 按原权重结算待领取奖励
+    let pending = reward_calculator::settle_pending_reward(
+        reward_config,
+        user_position.reward_weight()?,
         user_position.reward_debt,
     )?;
     
@@ -39,6 +75,7 @@ pub fn unstake_handler(ctx: Context<Unstake>, amount: u64) -> Result<()> {
         .ok_or(LpStakingError::MathOverflow)?;
     
     // 3. 更新用户质押数量
+    let staked_before = user_position.staked_amount;
     user_position.staked_amount = user_position.staked_amount
         .checked_sub(amount)
         .ok_or(LpStakingError::MathOverflow)?;
@@ -47,18 +84,34 @@ pub fn unstake_handler(ctx: Context<Unstake>, amount: u64) -> Result<()> {
         .checked_add(amount)
         .ok_or(LpStakingError::MathOverflow)?;
     
-    // 4. 更新池子总质押量
+    // 4. 更新池子总质押量与统计数据
     pool_state.total_staked = pool_state.total_staked
         .checked_sub(amount)
         .ok_or(LpStakingError::MathOverflow)?;
+    pool_state.update_staker_count(staked_before, user_position.staked_amount)?;
+    pool_state.record_activity(clock.slot);
+    
+    // 5. 按剩余比例缩减连续质押时长，并刷新忠诚度加成
+    user_position.last_stake_time = reward_calculator::adjust_tenure_start(
+        user_position.last_stake_time,
+        staked_before,
+        user_position.staked_amount,
+        clock.unix_timestamp,
+    );
+    reward_calculator::refresh_loyalty_bonus(
+        pool_state,
+        reward_config,
+        user_position,
+        clock.unix_timestamp,
+    )?;
     
-    // 5. 更新 reward_debt（基于新的质押量）
-    user_position.reward_debt = (user_position.staked_amount as u128)
+    // 6. 更新 reward_debt（基于新的奖励权重）
+    user_position.reward_debt = (user_position.reward_weight()? as u128)
         .checked_mul(reward_config.acc_reward_per_share)
         .ok_or(LpStakingError::MathOverflow)?;
     
     msg!("Unstake successful!");
-    msg!("User: {}", ctx.accounts.user.key());
+    msg!("User: {}", user_position.owner);
     msg!("Unstaked LP amount: {}", amount);
     msg!("Remaining staked: {}", user_position.staked_amount);
     msg!("Pending reward: {}", user_position.pending_reward);
@@ -96,4 +149,34 @@ pub struct Unstake<'info> {
         bump = reward_config.bump,
     )]
     pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 用户质押量历史
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub user_stake_history: Account<'info, StakeHistory>,
+    
+    /// 全局质押量历史的当前分页
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &pool_state.stake_history_page.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub global_stake_history: Account<'info, StakeHistory>,
+    
+    pub system_program: Program<'info, System>,
 }
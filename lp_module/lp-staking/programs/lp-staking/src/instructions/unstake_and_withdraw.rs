@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::UnstakeAndWithdrawEvent;
+use crate::instructions::unstake::process_unstake;
+use crate::instructions::withdraw::{process_withdraw, WithdrawAccounts};
+use crate::state::{PoolState, UserPosition, RewardConfig, StakeHistory};
+use crate::utils::StakeHistoryRecorder;
+
+/// 解除质押并赎回对应 LP Token 为 wrappedUSDC（原子操作）
+pub fn unstake_and_withdraw_handler(
+    ctx: Context<UnstakeAndWithdraw>,
+    lp_amount: u64,
+    min_usdc_out: u64,
+    expiry_ts: i64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    let clock = Clock::get()?;
+    
+    // 1. 解除质押（同时结算奖励到 pending_reward）
+    process_unstake(
+        &mut accounts.pool_state,
+        &mut accounts.user_position,
+        &mut accounts.reward_config,
+        lp_amount,
+        &clock,
+    )?;
+    
+    // 2. 赎回 LP Token
+    let usdc_amount = process_withdraw(
+        WithdrawAccounts {
+            user: &accounts.user,
+            pool_state: &mut accounts.pool_state,
+            user_position: &mut accounts.user_position,
+            user_usdc_account: &accounts.user_usdc_account,
+            pool_usdc_account: &accounts.pool_usdc_account,
+            lp_token_mint: &accounts.lp_token_mint,
+            user_lp_account: &accounts.user_lp_account,
+            token_program: &accounts.token_program,
+        },
+        lp_amount,
+        min_usdc_out,
+        expiry_ts,
+    )?;
+    
+    StakeHistoryRecorder {
+        payer: &accounts.user.to_account_info(),
+        system_program: accounts.system_program.as_ref(),
+        pool: accounts.pool_state.key(),
+        slot: clock.slot,
+    }
+    .record_stake_change(
+        &mut accounts.user_stake_history,
+        ctx.bumps.user_stake_history,
+        &mut accounts.global_stake_history,
+        ctx.bumps.global_stake_history,
+        &accounts.user_position,
+        &mut accounts.pool_state,
+    )?;
+    
+    emit!(UnstakeAndWithdrawEvent {
+        user: accounts.user.key(),
+        lp_amount,
+        usdc_amount,
+        staked_amount: accounts.user_position.staked_amount,
+        pending_reward: accounts.user_position.pending_reward,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UnstakeAndWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key() @ LpStakingError::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    
+    /// 用户质押量历史
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub user_stake_history: Account<'info, StakeHistory>,
+    
+    /// 全局质押量历史的当前分页
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeHistory::space(0),
+        seeds = [
+            STAKE_HISTORY_SEED,
+            pool_state.key().as_ref(),
+            &pool_state.stake_history_page.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub global_stake_history: Account<'info, StakeHistory>,
+    
+    #[account(
+        mut,
+        token::mint = pool_state.wrapped_usdc_mint,
+        token::authority = user,
+    )]
+    pub user_usdc_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool_state.pool_usdc_account,
+    )]
+    pub pool_usdc_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool_state.lp_token_mint,
+    )]
+    pub lp_token_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = lp_token_mint,
+        token::authority = user,
+    )]
+    pub user_lp_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::events::PoolUpdatedEvent;
+use crate::state::{PoolState, RewardConfig};
+use crate::utils::reward_calculator;
+
+/// 推进奖励累计值（无需权限，任何人都可调用）
+///
+/// 供 keeper 定期调用，使 RewardConfig 中的 acc_reward_per_share 保持最新，
+/// 并缩短 BlockBased 模式下跨周期计算的间隔
+pub fn update_pool_handler(ctx: Context<UpdatePool>) -> Result<()> {
+    let pool_state = &ctx.accounts.pool_state;
+    let reward_config = &mut ctx.accounts.reward_config;
+    let clock = Clock::get()?;
+    
+    let acc_reward_per_share_before = reward_config.acc_reward_per_share;
+    let last_update_slot_before = reward_config.last_update_slot;
+    
+    reward_calculator::update_pool_reward(
+        pool_state,
+        reward_config,
+        clock.slot,
+    )?;
+    
+    emit!(PoolUpdatedEvent {
+        caller: ctx.accounts.caller.key(),
+        acc_reward_per_share_before,
+        acc_reward_per_share_after: reward_config.acc_reward_per_share,
+        last_update_slot_before,
+        last_update_slot_after: reward_config.last_update_slot,
+        total_staked: pool_state.total_staked,
+    });
+    
+    msg!("Pool updated!");
+    msg!("acc_reward_per_share: {} -> {}", acc_reward_per_share_before, reward_config.acc_reward_per_share);
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdatePool<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [REWARD_CONFIG_SEED, pool_state.key().as_ref()],
+        bump = reward_config.bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+}
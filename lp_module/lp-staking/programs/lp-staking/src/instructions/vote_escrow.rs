@@ -0,0 +1,346 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::LpStakingError;
+use crate::events::StakeLockedEvent;
+use crate::state::{
+    PoolState, UserPosition, VeCheckpoint, VoteEscrow, VoteEscrowGlobal, VoteEscrowGlobalPage,
+};
+use crate::utils::{
+    add_slope_change, align_unlock_slot, checkpoint_global, create_pda_account,
+    find_vote_escrow_page_address, lock_point, push_checkpoint, remove_slope_change,
+    resize_account, take_archived_checkpoints, total_voting_power_at, upgrade_global,
+    user_voting_power_at, write_account,
+};
+
+/// 锁仓已质押的 LP Token，获得线性衰减的投票权
+///
+/// * `amount` - 追加锁仓的数量（可为 0，仅延长期限）
+/// * `unlock_slot` - 到期 slot，向下对齐到 VE_SLOT_GRANULARITY，不得早于当前到期 slot
+///
+/// 锁仓期内被锁定的质押不可解除；到期后自动释放，无需额外操作
+pub fn lock_stake_handler(ctx: Context<LockStake>, amount: u64, unlock_slot: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.slot;
+    let user_position = &mut ctx.accounts.user_position;
+    
+    // 1. 计算新的锁仓参数
+    let old_amount = user_position.active_locked_amount(now);
+    let old_end = user_position.lock_end_slot;
+    
+    let new_amount = old_amount
+        .checked_add(amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    let new_end = align_unlock_slot(unlock_slot);
+    
+    // 2. 参数验证
+    require!(new_amount > 0, LpStakingError::InvalidLock);
+    require!(
+        new_amount <= user_position.staked_amount,
+        LpStakingError::InsufficientStaked
+    );
+    require!(
+        new_end > now && new_end <= now.saturating_add(VE_MAX_LOCK_SLOTS),
+        LpStakingError::InvalidLock
+    );
+    if old_amount > 0 {
+        require!(new_end >= old_end, LpStakingError::InvalidLock);
+        require!(amount > 0 || new_end > old_end, LpStakingError::InvalidLock);
+    }
+    
+    // 3. 首次锁仓时初始化投票托管账户
+    let vote_escrow = &mut ctx.accounts.vote_escrow;
+    if vote_escrow.version == 0 {
+        vote_escrow.version = VoteEscrow::VERSION;
+        vote_escrow.owner = ctx.accounts.user.key();
+        vote_escrow.pool = ctx.accounts.pool_state.key();
+        vote_escrow.bump = ctx.bumps.vote_escrow;
+    }
+    
+    let global = &mut ctx.accounts.vote_escrow_global;
+    if global.version == 0 {
+        global.version = VoteEscrowGlobal::VERSION;
+        global.pool = ctx.accounts.pool_state.key();
+        global.bump = ctx.bumps.vote_escrow_global;
+    }
+    
+    // 4. 更新用户与全局投票权
+    apply_lock_change(
+        LockChangeAccounts {
+            vote_escrow,
+            vote_escrow_global: global,
+            vote_escrow_page: Some(&ctx.accounts.vote_escrow_page.to_account_info()),
+            payer: &ctx.accounts.user.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+        },
+        (old_amount, old_end),
+        (new_amount, new_end),
+        now,
+    )?;
+    
+    // 5. 更新锁仓状态
+    user_position.locked_amount = new_amount;
+    user_position.lock_end_slot = new_end;
+    
+    let voting_power = user_voting_power_at(&vote_escrow.checkpoints, now);
+    
+    emit!(StakeLockedEvent {
+        user: ctx.accounts.user.key(),
+        locked_amount: new_amount,
+        lock_end_slot: new_end,
+        voting_power,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    msg!("Lock successful!");
+    msg!("User: {}", ctx.accounts.user.key());
+    msg!("Locked amount: {}", new_amount);
+    msg!("Lock end slot: {}", new_end);
+    msg!("Voting power: {}", voting_power);
+    
+    Ok(())
+}
+
+/// 锁仓变化涉及的投票托管账户（lock_stake 与 tokenize_stake 共用）
+pub struct LockChangeAccounts<'a, 'info> {
+    pub vote_escrow: &'a mut Account<'info, VoteEscrow>,
+    pub vote_escrow_global: &'a mut Account<'info, VoteEscrowGlobal>,
+    /// 下一个归档分页（全局检查点需要归档时必须提供）
+    pub vote_escrow_page: Option<&'a AccountInfo<'info>>,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+/// 将锁仓从 (数量, 到期 slot) 的 old 调整为 new，同步更新用户检查点、全局检查点与斜率变化
+///
+/// 数量为 0 表示无锁仓；按最坏情况预留检查点空间
+pub fn apply_lock_change(
+    accounts: LockChangeAccounts,
+    old: (u64, u64),
+    new: (u64, u64),
+    now: u64,
+) -> Result<()> {
+    let old_point = lock_point(old.0, old.1, now);
+    let new_point = lock_point(new.0, new.1, now);
+    
+    // 1. 预留检查点空间（按最坏情况各追加一个条目）
+    let vote_escrow = accounts.vote_escrow;
+    let user_space = VoteEscrow::space(vote_escrow.checkpoints.len() + 1);
+    let user_info = vote_escrow.to_account_info();
+    if user_info.data_len() < user_space {
+        resize_account(&user_info, accounts.payer, accounts.system_program, user_space)?;
+    }
+    
+    // 旧版本账户原地升级；到期的斜率变化会写入检查点后移除
+    let global = accounts.vote_escrow_global;
+    if global.version < VoteEscrowGlobal::VERSION {
+        upgrade_global(global);
+    }
+    let applied = global.slope_changes.partition_point(|c| c.slot <= now);
+    let global_space = VoteEscrowGlobal::space(
+        global.checkpoints.len() + applied + 1,
+        global.slope_changes.len() - applied + 1,
+    );
+    let global_info = global.to_account_info();
+    if global_info.data_len() < global_space {
+        resize_account(&global_info, accounts.payer, accounts.system_program, global_space)?;
+    }
+    
+    // 2. 更新全局检查点与斜率变化
+    let mut point = checkpoint_global(global, now);
+    point.bias = point.bias.saturating_sub(old_point.bias).saturating_add(new_point.bias);
+    point.slope = point.slope.saturating_sub(old_point.slope).saturating_add(new_point.slope);
+    push_checkpoint(&mut global.checkpoints, point);
+    
+    if old_point.slope > 0 {
+        remove_slope_change(&mut global.slope_changes, old.1, old_point.slope);
+    }
+    if new_point.slope > 0 {
+        add_slope_change(&mut global.slope_changes, new.1, new_point.slope);
+    }
+    
+    // 3. 检查点超过上限时归档较早的检查点
+    if let Some(checkpoints) = take_archived_checkpoints(global) {
+        let page_info = accounts.vote_escrow_page.ok_or(LpStakingError::VoteEscrowRequired)?;
+        archive_checkpoints(
+            page_info,
+            global,
+            checkpoints,
+            accounts.payer,
+            accounts.system_program,
+        )?;
+    }
+    
+    // 4. 更新用户检查点
+    push_checkpoint(&mut vote_escrow.checkpoints, new_point);
+    
+    Ok(())
+}
+
+/// 将取出的检查点写入编号为 archived_pages 的归档分页
+///
+/// 分页创建时为最小大小，写入前用 resize_account 扩容到所需大小
+fn archive_checkpoints<'info>(
+    page_info: &AccountInfo<'info>,
+    global: &mut VoteEscrowGlobal,
+    checkpoints: Vec<VeCheckpoint>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let page_index = global.archived_pages;
+    let (address, bump) = find_vote_escrow_page_address(&global.pool, page_index);
+    require_keys_eq!(page_info.key(), address, LpStakingError::VoteEscrowPageMismatch);
+    
+    // 1. 创建分页并扩容
+    let index_bytes = page_index.to_le_bytes();
+    let signer_seeds: &[&[u8]] = &[VOTE_ESCROW_PAGE_SEED, global.pool.as_ref(), &index_bytes, &[bump]];
+    create_pda_account(
+        payer,
+        page_info,
+        system_program,
+        VoteEscrowGlobalPage::space(0),
+        &[signer_seeds],
+    )?;
+    resize_account(
+        page_info,
+        payer,
+        system_program,
+        VoteEscrowGlobalPage::space(checkpoints.len()),
+    )?;
+    
+    // 2. 写入检查点，本页覆盖到全局账户中保留的第一个检查点之前
+    let page = VoteEscrowGlobalPage {
+        version: VoteEscrowGlobalPage::VERSION,
+        pool: global.pool,
+        page: page_index,
+        bump,
+        end_slot: global.checkpoints.first().map(|c| c.slot).unwrap_or(u64::MAX),
+        reserved: [0; 32],
+        checkpoints,
+    };
+    write_account(page_info, &page)?;
+    
+    global.archived_pages = page_index
+        .checked_add(1)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    msg!("Archived vote escrow checkpoints to page {}", page_index);
+    Ok(())
+}
+
+/// 查询用户在 slot 时的投票权（只读，通过 return data 返回）
+pub fn voting_power_at_handler(ctx: Context<QueryVotingPower>, slot: u64) -> Result<u64> {
+    Ok(user_voting_power_at(&ctx.accounts.vote_escrow.checkpoints, slot))
+}
+
+/// 查询全局在 slot 时的总投票权（只读，通过 return data 返回）
+///
+/// 早于全局账户中第一个检查点的 slot 位于归档分页，需传入覆盖该 slot 的 vote_escrow_page
+pub fn total_voting_power_at_handler(ctx: Context<QueryTotalVotingPower>, slot: u64) -> Result<u64> {
+    let global = &ctx.accounts.vote_escrow_global;
+    let archived = global.version >= 3
+        && global.archived_pages > 0
+        && global.checkpoints.first().is_some_and(|c| slot < c.slot);
+    
+    if archived {
+        let page = ctx.accounts.vote_escrow_page
+            .as_ref()
+            .ok_or(LpStakingError::VoteEscrowPageMismatch)?;
+        require!(page.covers(slot), LpStakingError::VoteEscrowPageMismatch);
+        return Ok(total_voting_power_at(&page.checkpoints, &[], slot));
+    }
+    
+    Ok(total_voting_power_at(&global.checkpoints, &global.slope_changes, slot))
+}
+
+#[derive(Accounts)]
+pub struct LockStake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        seeds = [POOL_STATE_SEED],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [
+            USER_POSITION_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key() @ LpStakingError::Unauthorized,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = VoteEscrow::space(0),
+        seeds = [
+            VOTE_ESCROW_SEED,
+            user.key().as_ref(),
+            pool_state.key().as_ref()
+        ],
+        bump,
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = VoteEscrowGlobal::space(0, 0),
+        seeds = [VOTE_ESCROW_GLOBAL_SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub vote_escrow_global: Account<'info, VoteEscrowGlobal>,
+    
+    /// CHECK: 下一个归档分页，地址由种子约束，仅在全局检查点需要归档时创建
+    #[account(
+        mut,
+        seeds = [
+            VOTE_ESCROW_PAGE_SEED,
+            pool_state.key().as_ref(),
+            &vote_escrow_global.archived_pages.to_le_bytes()
+        ],
+        bump,
+    )]
+    pub vote_escrow_page: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct QueryVotingPower<'info> {
+    #[account(
+        seeds = [
+            VOTE_ESCROW_SEED,
+            vote_escrow.owner.as_ref(),
+            vote_escrow.pool.as_ref()
+        ],
+        bump = vote_escrow.bump,
+    )]
+    pub vote_escrow: Account<'info, VoteEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct QueryTotalVotingPower<'info> {
+    #[account(
+        seeds = [VOTE_ESCROW_GLOBAL_SEED, vote_escrow_global.pool.as_ref()],
+        bump = vote_escrow_global.bump,
+    )]
+    pub vote_escrow_global: Account<'info, VoteEscrowGlobal>,
+    
+    /// 覆盖查询 slot 的归档分页（查询早于全局账户检查点的 slot 时必须提供）
+    #[account(
+        seeds = [
+            VOTE_ESCROW_PAGE_SEED,
+            vote_escrow_global.pool.as_ref(),
+            &vote_escrow_page.page.to_le_bytes()
+        ],
+        bump = vote_escrow_page.bump,
+    )]
+    pub vote_escrow_page: Option<Account<'info, VoteEscrowGlobalPage>>,
+}
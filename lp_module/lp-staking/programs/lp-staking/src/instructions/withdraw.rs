@@ -5,12 +5,68 @@ use crate::errors::LpStakingError;
 use crate::state::{PoolState, UserPosition};
 
 /// 赎回 LP Token，提取 wrappedUSDC
-pub fn withdraw_handler(ctx: Context<Withdraw>, lp_amount: u64) -> Result<()> {
+///
+/// * `min_usdc_out` - 最少应获得的 wrappedUSDC 数量（滑点保护）
+/// * `expiry_ts` - 交易截止时间（Unix 时间戳），超时则拒绝
+pub fn withdraw_handler(
+    ctx: Context<Withdraw>,
+    lp_amount: u64,
+    min_usdc_out: u64,
+    expiry_ts: i64,
+) -> Result<()> {
+    let accounts = ctx.accounts;
+    
+    process_withdraw(
+        WithdrawAccounts {
+            user: &accounts.user,
+            pool_state: &mut accounts.pool_state,
+            user_position: &mut accounts.user_position,
+            user_usdc_account: &accounts.user_usdc_account,
+            pool_usdc_account: &accounts.pool_usdc_account,
+            lp_token_mint: &accounts.lp_token_mint,
+            user_lp_account: &accounts.user_lp_account,
+            token_program: &accounts.token_program,
+        },
+        lp_amount,
+        min_usdc_out,
+        expiry_ts,
+    )?;
+    
+    Ok(())
+}
+
+/// 提取流程涉及的账户（withdraw 与 unstake_and_withdraw 共用）
+pub struct WithdrawAccounts<'a, 'info> {
+    pub user: &'a Signer<'info>,
+    pub pool_state: &'a mut Account<'info, PoolState>,
+    pub user_position: &'a mut Account<'info, UserPosition>,
+    pub user_usdc_account: &'a Account<'info, TokenAccount>,
+    pub pool_usdc_account: &'a Account<'info, TokenAccount>,
+    pub lp_token_mint: &'a Account<'info, Mint>,
+    pub user_lp_account: &'a Account<'info, TokenAccount>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+/// 提取核心逻辑：销毁 LP Token、转出 USDC 并更新池子与仓位
+///
+/// 返回转出的 wrappedUSDC 数量
+pub fn process_withdraw(
+    accounts: WithdrawAccounts,
+    lp_amount: u64,
+    min_usdc_out: u64,
+    expiry_ts: i64,
+) -> Result<u64> {
     // 参数验证
     require!(lp_amount > 0, LpStakingError::InvalidAmount);
+    require!(
+        Clock::get()?.unix_timestamp <= expiry_ts,
+        LpStakingError::TransactionExpired
+    );
     
-    let pool_state = &mut ctx.accounts.pool_state;
-    let user_position = &mut ctx.accounts.user_position;
+    let pool_state = accounts.pool_state;
+    let user_position = accounts.user_position;
+    require!(!pool_state.paused, LpStakingError::PoolPaused);
+    require!(pool_state.flash_loan_amount == 0, LpStakingError::FlashLoanActive);
     
     // 检查用户 LP Token 余额
     require!(
@@ -30,6 +86,7 @@ pub fn withdraw_handler(ctx: Context<Withdraw>, lp_amount: u64) -> Result<()> {
         pool_state.total_deposited,
         pool_state.total_lp_supply,
     )?;
+    require!(usdc_amount >= min_usdc_out, LpStakingError::SlippageExceeded);
     
     // 检查池子 USDC 余额是否充足
     require!(
@@ -37,13 +94,22 @@ pub fn withdraw_handler(ctx: Context<Withdraw>, lp_amount: u64) -> Result<()> {
         LpStakingError::InsufficientBalance
     );
     
+    // 部分资金可能已分配给收益策略，需检查闲置余额
+    require!(
+        accounts.pool_usdc_account.amount >= usdc_amount,
+        LpStakingError::InsufficientLiquidity
+    );
+    
+    // 熔断：检查滚动窗口内的提取限额
+    pool_state.record_outflow(usdc_amount, Clock::get()?.unix_timestamp)?;
+    
     // 1. 销毁用户的 LP Token
     let burn_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
+        accounts.token_program.to_account_info(),
         Burn {
-            mint: ctx.accounts.lp_token_mint.to_account_info(),
-            from: ctx.accounts.user_lp_account.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
+            mint: accounts.lp_token_mint.to_account_info(),
+            from: accounts.user_lp_account.to_account_info(),
+            authority: accounts.user.to_account_info(),
         },
     );
     token::burn(burn_ctx, lp_amount)?;
@@ -56,10 +122,10 @@ pub fn withdraw_handler(ctx: Context<Withdraw>, lp_amount: u64) -> Result<()> {
     let signer = &[&seeds[..]];
     
     let transfer_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
+        accounts.token_program.to_account_info(),
         Transfer {
-            from: ctx.accounts.pool_usdc_account.to_account_info(),
-            to: ctx.accounts.user_usdc_account.to_account_info(),
+            from: accounts.pool_usdc_account.to_account_info(),
+            to: accounts.user_usdc_account.to_account_info(),
             authority: pool_state.to_account_info(),
         },
         signer,
@@ -74,21 +140,28 @@ pub fn withdraw_handler(ctx: Context<Withdraw>, lp_amount: u64) -> Result<()> {
         .checked_sub(lp_amount)
         .ok_or(LpStakingError::MathOverflow)?;
     
-    // 4. 更新用户仓位
+    // 4. 更新统计数据
+    pool_state.cumulative_withdrawn = pool_state.cumulative_withdrawn
+        .checked_add(usdc_amount)
+        .ok_or(LpStakingError::MathOverflow)?;
+    pool_state.record_activity(Clock::get()?.slot);
+    
+    // 5. 更新用户仓位
     user_position.lp_balance = user_position.lp_balance
         .checked_sub(lp_amount)
         .ok_or(LpStakingError::MathOverflow)?;
+    user_position.record_withdrawal(usdc_amount)?;
     
     msg!("Withdraw successful!");
-    msg!("User: {}", ctx.accounts.user.key());
+    msg!("User: {}", accounts.user.key());
     msg!("Burned LP Token: {}", lp_amount);
     msg!("Withdrawn USDC: {}", usdc_amount);
     
-    Ok(())
+    Ok(usdc_amount)
 }
 
 /// 计算应该返还的 USDC 数量
-fn calculate_withdraw_amount(
+pub fn calculate_withdraw_amount(
     lp_amount: u64,
     total_deposited: u64,
     total_lp_supply: u64,
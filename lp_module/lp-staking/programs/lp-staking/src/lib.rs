@@ -4,12 +4,17 @@ declare_id!("AoQuXAg7gK5KHkeuhbLpJ5AtnziNb5M9FqjLNUaVudTx");
 
 pub mod constants;
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod state;
 pub mod utils;
 
+/// 通过 CPI 集成时使用的 PDA 推导函数
+#[cfg(feature = "cpi")]
+pub use utils::pda;
+
 use instructions::*;
-use state::EmissionType;
+use state::{EmissionType, StableToken};
 
 #[program]
 pub mod lp_staking {
@@ -35,18 +40,29 @@ pub mod lp_staking {
     }
     
     /// 存入 wrappedUSDC，获得 LP Token
-    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        instructions::deposit::deposit_handler(ctx, amount)
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        amount: u64,
+        min_lp_out: u64,
+        expiry_ts: i64,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::deposit::deposit_handler(ctx, amount, min_lp_out, expiry_ts, referrer)
     }
 
     /// 赎回 LP Token，提取 wrappedUSDC
-    pub fn withdraw(ctx: Context<Withdraw>, lp_amount: u64) -> Result<()> {
-        instructions::withdraw::withdraw_handler(ctx, lp_amount)
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        lp_amount: u64,
+        min_usdc_out: u64,
+        expiry_ts: i64,
+    ) -> Result<()> {
+        instructions::withdraw::withdraw_handler(ctx, lp_amount, min_usdc_out, expiry_ts)
     }
     
     /// 质押 LP Token，开始赚取奖励
-    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
-        instructions::stake::stake_handler(ctx, amount)
+    pub fn stake(ctx: Context<Stake>, amount: u64, referrer: Option<Pubkey>) -> Result<()> {
+        instructions::stake::stake_handler(ctx, amount, referrer)
     }
     
     /// 解除质押，取回 LP Token
@@ -58,4 +74,414 @@ pub mod lp_staking {
     pub fn claim(ctx: Context<Claim>) -> Result<()> {
         instructions::claim::claim_handler(ctx)
     }
+    
+    /// 领取质押奖励到指定地址
+    pub fn claim_to(ctx: Context<ClaimTo>) -> Result<()> {
+        instructions::claim::claim_to_handler(ctx)
+    }
+    
+    /// 设置或清除收获代理
+    pub fn set_harvest_delegate(
+        ctx: Context<SetHarvestDelegate>,
+        delegate: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::harvest::set_harvest_delegate_handler(ctx, delegate)
+    }
+    
+    /// 收获代理批量领取多个仓位的奖励
+    pub fn harvest_many<'info>(
+        ctx: Context<'_, '_, 'info, 'info, HarvestMany<'info>>,
+    ) -> Result<()> {
+        instructions::harvest::harvest_many_handler(ctx)
+    }
+    
+    /// 质押 LP Token 并铸造可转让的仓位代币
+    pub fn stake_nft(ctx: Context<StakeNft>, amount: u64) -> Result<()> {
+        instructions::position_nft::stake_nft_handler(ctx, amount)
+    }
+    
+    /// 将已有质押（包括锁仓中的质押）转换为可转让的仓位代币
+    pub fn tokenize_stake(ctx: Context<TokenizeStake>, amount: u64) -> Result<()> {
+        instructions::position_nft::tokenize_stake_handler(ctx, amount)
+    }
+    
+    /// 仓位代币持有者领取奖励
+    pub fn claim_nft(ctx: Context<ClaimNft>) -> Result<()> {
+        instructions::position_nft::claim_nft_handler(ctx)
+    }
+    
+    /// 销毁仓位代币，取回托管的 LP Token
+    pub fn exit_nft(ctx: Context<ExitNft>) -> Result<()> {
+        instructions::position_nft::exit_nft_handler(ctx)
+    }
+    
+    /// 锁仓已质押的 LP Token，获得线性衰减的投票权
+    pub fn lock_stake(ctx: Context<LockStake>, amount: u64, unlock_slot: u64) -> Result<()> {
+        instructions::vote_escrow::lock_stake_handler(ctx, amount, unlock_slot)
+    }
+    
+    /// 查询用户在指定 slot 的投票权
+    pub fn voting_power_at(ctx: Context<QueryVotingPower>, slot: u64) -> Result<u64> {
+        instructions::vote_escrow::voting_power_at_handler(ctx, slot)
+    }
+    
+    /// 查询指定 slot 的全局总投票权
+    pub fn total_voting_power_at(ctx: Context<QueryTotalVotingPower>, slot: u64) -> Result<u64> {
+        instructions::vote_escrow::total_voting_power_at_handler(ctx, slot)
+    }
+    
+    /// 创建质押快照（仅管理员）
+    pub fn take_snapshot(ctx: Context<TakeSnapshot>) -> Result<()> {
+        instructions::snapshot::take_snapshot_handler(ctx)
+    }
+    
+    /// 证明用户在快照时的质押量
+    pub fn prove_stake(ctx: Context<ProveStake>, snapshot_id: u64) -> Result<StakeProof> {
+        instructions::snapshot::prove_stake_handler(ctx, snapshot_id)
+    }
+    
+    /// 查询池子在 slot 结束时的总质押量（只读，page 为包含该 slot 的全局历史分页）
+    pub fn total_staked_at(ctx: Context<QueryTotalStaked>, page: u32, slot: u64) -> Result<u64> {
+        instructions::snapshot::total_staked_at_handler(ctx, page, slot)
+    }
+    
+    /// 存入 wrappedUSDC 并质押获得的 LP Token（原子操作）
+    pub fn deposit_and_stake(
+        ctx: Context<DepositAndStake>,
+        amount: u64,
+        min_lp_out: u64,
+        expiry_ts: i64,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::deposit_and_stake::deposit_and_stake_handler(
+            ctx,
+            amount,
+            min_lp_out,
+            expiry_ts,
+            referrer,
+        )
+    }
+    
+    /// 解除质押并赎回 LP Token（原子操作）
+    pub fn unstake_and_withdraw(
+        ctx: Context<UnstakeAndWithdraw>,
+        lp_amount: u64,
+        min_usdc_out: u64,
+        expiry_ts: i64,
+    ) -> Result<()> {
+        instructions::unstake_and_withdraw::unstake_and_withdraw_handler(
+            ctx,
+            lp_amount,
+            min_usdc_out,
+            expiry_ts,
+        )
+    }
+    
+    /// 关闭空仓位，退还租金
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        instructions::close_position::close_position_handler(ctx)
+    }
+    
+    /// 升级池子账户布局，并补建缺失的 RewardConfig（仅管理员）
+    pub fn migrate_pool(
+        ctx: Context<MigratePool>,
+        emission_type: EmissionType,
+        emission_rate: u64,
+        initial_block_rate: u64,
+        decay_factor: u64,
+        blocks_per_period: u64,
+        legacy_stats: LegacyStats,
+    ) -> Result<()> {
+        instructions::migrate::migrate_pool_handler(
+            ctx,
+            emission_type,
+            emission_rate,
+            initial_block_rate,
+            decay_factor,
+            blocks_per_period,
+            legacy_stats,
+        )
+    }
+    
+    /// 升级用户仓位账户布局
+    pub fn migrate_position(ctx: Context<MigratePosition>) -> Result<()> {
+        instructions::migrate::migrate_position_handler(ctx)
+    }
+    
+    /// 查询用户待领取奖励（只读，可通过 simulateTransaction 调用）
+    pub fn quote_pending_reward(ctx: Context<QuotePendingReward>) -> Result<u64> {
+        instructions::quote::quote_pending_reward_handler(ctx)
+    }
+    
+    /// 报价：存入 USDC 可获得的 LP Token（只读）
+    pub fn quote_deposit(ctx: Context<QuotePool>, amount: u64) -> Result<u64> {
+        instructions::quote::quote_deposit_handler(ctx, amount)
+    }
+    
+    /// 报价：赎回 LP Token 可获得的 USDC（只读）
+    pub fn quote_withdraw(ctx: Context<QuotePool>, lp_amount: u64) -> Result<u64> {
+        instructions::quote::quote_withdraw_handler(ctx, lp_amount)
+    }
+    
+    /// 查询当前排放速率与 APR 计算参数（只读）
+    pub fn quote_emission(ctx: Context<QuoteEmission>) -> Result<EmissionQuote> {
+        instructions::quote::quote_emission_handler(ctx)
+    }
+    
+    /// 查询池子累计统计数据（只读）
+    pub fn quote_pool_stats(ctx: Context<QuotePool>) -> Result<PoolStats> {
+        instructions::quote::quote_pool_stats_handler(ctx)
+    }
+    
+    /// 推进奖励累计值（无需权限的 crank）
+    pub fn update_pool(ctx: Context<UpdatePool>) -> Result<()> {
+        instructions::update_pool::update_pool_handler(ctx)
+    }
+    
+    /// 向奖励金库注资（任何人都可调用）
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+        instructions::fund_rewards::fund_rewards_handler(ctx, amount)
+    }
+    
+    /// 设置推荐奖励比例（仅管理员）
+    pub fn set_referral_bps(ctx: Context<SetReferralBps>, referral_bps: u64) -> Result<()> {
+        instructions::set_referral_bps::set_referral_bps_handler(ctx, referral_bps)
+    }
+    
+    /// 设置国库地址（仅管理员）
+    pub fn set_treasury(ctx: Context<SetTreasury>, treasury: Pubkey) -> Result<()> {
+        instructions::set_treasury::set_treasury_handler(ctx, treasury)
+    }
+    
+    /// 设置无人质押期间排放的处理方式（仅管理员）
+    pub fn set_carry_over_undistributed(
+        ctx: Context<SetCarryOverUndistributed>,
+        carry_over: bool,
+    ) -> Result<()> {
+        instructions::undistributed::set_carry_over_undistributed_handler(ctx, carry_over)
+    }
+    
+    /// 提取未分配奖励到国库（仅管理员）
+    pub fn withdraw_undistributed(ctx: Context<WithdrawUndistributed>, amount: u64) -> Result<()> {
+        instructions::undistributed::withdraw_undistributed_handler(ctx, amount)
+    }
+    
+    /// 设置协议抽成比例（仅管理员）
+    pub fn set_protocol_fee_bps(ctx: Context<SetProtocolFeeBps>, protocol_fee_bps: u64) -> Result<()> {
+        instructions::protocol_fee::set_protocol_fee_bps_handler(ctx, protocol_fee_bps)
+    }
+    
+    /// 提取协议奖励到国库（仅管理员）
+    pub fn collect_protocol_rewards(ctx: Context<CollectProtocolRewards>) -> Result<()> {
+        instructions::protocol_fee::collect_protocol_rewards_handler(ctx)
+    }
+    
+    /// 设置守护者（仅管理员，传 None 清除）
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Option<Pubkey>) -> Result<()> {
+        instructions::outflow_limit::set_guardian_handler(ctx, guardian)
+    }
+    
+    /// 设置提取限额（管理员或守护者）
+    pub fn set_outflow_limit(
+        ctx: Context<ManageOutflowLimit>,
+        limit_bps: u64,
+        window_secs: i64,
+    ) -> Result<()> {
+        instructions::outflow_limit::set_outflow_limit_handler(ctx, limit_bps, window_secs)
+    }
+    
+    /// 重置提取限额窗口（管理员或守护者）
+    pub fn reset_outflow_window(ctx: Context<ManageOutflowLimit>) -> Result<()> {
+        instructions::outflow_limit::reset_outflow_window_handler(ctx)
+    }
+    
+    /// 配置脱锚保护（仅管理员）
+    pub fn configure_price_guard(
+        ctx: Context<ConfigurePriceGuard>,
+        peg_band_bps: u64,
+        max_price_age_secs: i64,
+    ) -> Result<()> {
+        instructions::price_guard::configure_price_guard_handler(ctx, peg_band_bps, max_price_age_secs)
+    }
+    
+    /// 暂停或恢复存取（管理员或守护者）
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        instructions::price_guard::set_paused_handler(ctx, paused)
+    }
+    
+    /// 检查锚定状态，脱锚时自动暂停（无需权限）
+    pub fn check_peg(ctx: Context<CheckPeg>) -> Result<()> {
+        instructions::price_guard::check_peg_handler(ctx)
+    }
+    
+    /// 创建 StableSwap 双稳定币池（仅管理员）
+    pub fn initialize_stable_swap(
+        ctx: Context<InitializeStableSwap>,
+        amp: u64,
+        fee_bps: u64,
+    ) -> Result<()> {
+        instructions::stable_swap::initialize_stable_swap_handler(ctx, amp, fee_bps)
+    }
+    
+    /// StableSwap 添加流动性（平衡或非平衡）
+    pub fn stable_add_liquidity(
+        ctx: Context<StableLiquidity>,
+        amount_a: u64,
+        amount_b: u64,
+        min_lp_out: u64,
+    ) -> Result<()> {
+        instructions::stable_swap::stable_add_liquidity_handler(ctx, amount_a, amount_b, min_lp_out)
+    }
+    
+    /// StableSwap 平衡移除流动性（按余额比例取出两个币种）
+    pub fn stable_remove_liquidity(
+        ctx: Context<StableLiquidity>,
+        lp_amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+    ) -> Result<()> {
+        instructions::stable_swap::stable_remove_liquidity_handler(
+            ctx,
+            lp_amount,
+            min_amount_a,
+            min_amount_b,
+        )
+    }
+    
+    /// StableSwap 单币种移除流动性
+    pub fn stable_remove_liquidity_one(
+        ctx: Context<StableLiquidity>,
+        lp_amount: u64,
+        token_out: StableToken,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::stable_swap::stable_remove_liquidity_one_handler(
+            ctx,
+            lp_amount,
+            token_out,
+            min_amount_out,
+        )
+    }
+    
+    /// StableSwap 兑换
+    pub fn stable_swap(
+        ctx: Context<StableSwapTrade>,
+        token_in: StableToken,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::stable_swap::stable_swap_handler(ctx, token_in, amount_in, min_amount_out)
+    }
+    
+    /// 设置 StableSwap LP 质押奖励排放速率（仅管理员）
+    pub fn set_stable_reward_rate(ctx: Context<SetStableRewardRate>, reward_rate: u64) -> Result<()> {
+        instructions::stable_stake::set_stable_reward_rate_handler(ctx, reward_rate)
+    }
+    
+    /// 向 StableSwap LP 质押奖励金库注资
+    pub fn fund_stable_rewards(ctx: Context<FundStableRewards>, amount: u64) -> Result<()> {
+        instructions::stable_stake::fund_stable_rewards_handler(ctx, amount)
+    }
+    
+    /// 质押 StableSwap LP
+    pub fn stable_stake(ctx: Context<StableStake>, amount: u64) -> Result<()> {
+        instructions::stable_stake::stable_stake_handler(ctx, amount)
+    }
+    
+    /// 解除质押 StableSwap LP
+    pub fn stable_unstake(ctx: Context<StableStake>, amount: u64) -> Result<()> {
+        instructions::stable_stake::stable_unstake_handler(ctx, amount)
+    }
+    
+    /// 领取 StableSwap LP 质押奖励
+    pub fn stable_claim(ctx: Context<StableClaim>) -> Result<()> {
+        instructions::stable_stake::stable_claim_handler(ctx)
+    }
+    
+    /// 设置闪电贷手续费（仅管理员）
+    pub fn set_flash_loan_fee(ctx: Context<SetFlashLoanFee>, fee_bps: u64) -> Result<()> {
+        instructions::flash_loan::set_flash_loan_fee_handler(ctx, fee_bps)
+    }
+    
+    /// 闪电贷借出（同一交易中须调用 flash_repay 归还）
+    pub fn flash_loan(ctx: Context<FlashLoan>, amount: u64) -> Result<()> {
+        instructions::flash_loan::flash_loan_handler(ctx, amount)
+    }
+    
+    /// 归还闪电贷本金与手续费
+    pub fn flash_repay(ctx: Context<FlashRepay>) -> Result<()> {
+        instructions::flash_loan::flash_repay_handler(ctx)
+    }
+    
+    /// 设置忠诚度加成曲线（仅管理员）
+    pub fn set_loyalty_curve(
+        ctx: Context<SetLoyaltyCurve>,
+        max_bonus_bps: u64,
+        ramp_secs: i64,
+    ) -> Result<()> {
+        instructions::loyalty::set_loyalty_curve_handler(ctx, max_bonus_bps, ramp_secs)
+    }
+    
+    /// 刷新仓位的忠诚度加成（无需权限）
+    pub fn refresh_loyalty(ctx: Context<RefreshLoyalty>) -> Result<()> {
+        instructions::loyalty::refresh_loyalty_handler(ctx)
+    }
+    
+    /// 代存：出资者存入 wrappedUSDC，LP Token 记入受益人仓位
+    pub fn deposit_for(
+        ctx: Context<DepositFor>,
+        amount: u64,
+        min_lp_out: u64,
+        expiry_ts: i64,
+    ) -> Result<()> {
+        instructions::stake_for::deposit_for_handler(ctx, amount, min_lp_out, expiry_ts)
+    }
+    
+    /// 代质押：把出资者未质押的 LP Token 转给受益人并直接质押
+    pub fn stake_for(ctx: Context<StakeFor>, amount: u64) -> Result<()> {
+        instructions::stake_for::stake_for_handler(ctx, amount)
+    }
+    
+    /// 授权操作员管理仓位（仅仓位所有者）
+    pub fn approve_operator(ctx: Context<ApproveOperator>, operator: Pubkey) -> Result<()> {
+        instructions::operator::approve_operator_handler(ctx, operator)
+    }
+    
+    /// 撤销操作员授权（仅仓位所有者）
+    pub fn revoke_operator(ctx: Context<RevokeOperator>) -> Result<()> {
+        instructions::operator::revoke_operator_handler(ctx)
+    }
+    
+    /// 操作员代为质押
+    pub fn operator_stake(ctx: Context<OperatorManage>, amount: u64) -> Result<()> {
+        instructions::operator::operator_stake_handler(ctx, amount)
+    }
+    
+    /// 操作员代为解除质押
+    pub fn operator_unstake(ctx: Context<OperatorManage>, amount: u64) -> Result<()> {
+        instructions::operator::operator_unstake_handler(ctx, amount)
+    }
+    
+    /// 配置收益策略（仅管理员）
+    pub fn initialize_strategy(
+        ctx: Context<InitializeStrategy>,
+        max_allocation_bps: u64,
+    ) -> Result<()> {
+        instructions::initialize_strategy::initialize_strategy_handler(ctx, max_allocation_bps)
+    }
+    
+    /// 将闲置资金分配给外部策略（仅管理员）
+    pub fn allocate_to_strategy(ctx: Context<ManageStrategy>, amount: u64) -> Result<()> {
+        instructions::manage_strategy::allocate_to_strategy_handler(ctx, amount)
+    }
+    
+    /// 收获策略收益（仅管理员）
+    pub fn harvest_strategy(ctx: Context<ManageStrategy>) -> Result<()> {
+        instructions::manage_strategy::harvest_strategy_handler(ctx)
+    }
+    
+    /// 从策略召回资金（仅管理员）
+    pub fn recall_from_strategy(ctx: Context<ManageStrategy>, amount: u64) -> Result<()> {
+        instructions::manage_strategy::recall_from_strategy_handler(ctx, amount)
+    }
 }
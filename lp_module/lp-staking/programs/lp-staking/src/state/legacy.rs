@@ -0,0 +1,348 @@
+//! 旧版账户布局（version 0：无版本字段、无预留空间）
+//!
+//! 仅用于 migrate 指令读取并升级已部署的账户，数据前 8 字节为与当前版本相同的 discriminator。
+
+use anchor_lang::prelude::*;
+use super::{EmissionType, PoolState, RewardConfig, UserPosition};
+
+/// PoolState v0 布局
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PoolStateV0 {
+    pub authority: Pubkey,
+    pub wrapped_usdc_mint: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub pool_usdc_account: Pubkey,
+    pub total_deposited: u64,
+    pub total_lp_supply: u64,
+    pub total_staked: u64,
+    pub reward_vault: Pubkey,
+    pub bump: u8,
+}
+
+impl PoolStateV0 {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 32 + 1;
+
+    /// 升级为当前布局
+    pub fn upgrade(self) -> PoolState {
+        PoolState {
+            version: PoolState::VERSION,
+            authority: self.authority,
+            wrapped_usdc_mint: self.wrapped_usdc_mint,
+            lp_token_mint: self.lp_token_mint,
+            pool_usdc_account: self.pool_usdc_account,
+            total_deposited: self.total_deposited,
+            total_lp_supply: self.total_lp_supply,
+            total_staked: self.total_staked,
+            reward_vault: self.reward_vault,
+            bump: self.bump,
+            snapshot_count: 0,
+            treasury: Pubkey::default(),
+            guardian: Pubkey::default(),
+            outflow_limit_bps: 0,
+            outflow_window_secs: 0,
+            outflow_window_start: 0,
+            outflow_window_tvl: 0,
+            outflow_current: 0,
+            outflow_previous: 0,
+            price_feed: Pubkey::default(),
+            peg_band_bps: 0,
+            max_price_age_secs: 0,
+            paused: false,
+            flash_loan_fee_bps: 0,
+            flash_loan_amount: 0,
+            flash_loan_fee: 0,
+            cumulative_deposited: 0,
+            cumulative_withdrawn: 0,
+            active_positions: 0,
+            staker_count: 0,
+            total_rewards_claimed: 0,
+            last_activity_slot: 0,
+            total_loyalty_bonus: 0,
+            stake_history_page: 0,
+            reserved: [0; 3],
+        }
+    }
+}
+
+/// UserPosition v0 布局
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct UserPositionV0 {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub lp_balance: u64,
+    pub staked_amount: u64,
+    pub reward_debt: u128,
+    pub pending_reward: u64,
+    pub last_stake_time: i64,
+    pub last_claim_time: i64,
+    pub bump: u8,
+}
+
+impl UserPositionV0 {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 16 + 8 + 8 + 8 + 1;
+
+    /// 升级为当前布局
+    pub fn upgrade(self) -> UserPosition {
+        UserPosition {
+            version: UserPosition::VERSION,
+            owner: self.owner,
+            pool: self.pool,
+            lp_balance: self.lp_balance,
+            staked_amount: self.staked_amount,
+            reward_debt: self.reward_debt,
+            pending_reward: self.pending_reward,
+            last_stake_time: self.last_stake_time,
+            last_claim_time: self.last_claim_time,
+            bump: self.bump,
+            harvest_delegate: Pubkey::default(),
+            referrer: Pubkey::default(),
+            locked_amount: 0,
+            lock_end_slot: 0,
+            cumulative_deposited: 0,
+            cumulative_withdrawn: 0,
+            total_rewards_claimed: 0,
+            first_deposit_time: 0,
+            claim_count: 0,
+            loyalty_bonus: 0,
+            reserved: [0; 4],
+        }
+    }
+}
+
+/// RewardConfig v0 布局
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RewardConfigV0 {
+    pub pool: Pubkey,
+    pub emission_type: EmissionType,
+    pub emission_rate: u64,
+    pub initial_block_rate: u64,
+    pub decay_factor: u64,
+    pub blocks_per_period: u64,
+    pub last_update_slot: u64,
+    pub acc_reward_per_share: u128,
+    pub bump: u8,
+}
+
+impl RewardConfigV0 {
+    pub const LEN: usize = 8 + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 16 + 1;
+
+    /// 升级为当前布局
+    pub fn upgrade(self) -> RewardConfig {
+        RewardConfig {
+            version: RewardConfig::VERSION,
+            pool: self.pool,
+            emission_type: self.emission_type,
+            emission_rate: self.emission_rate,
+            initial_block_rate: self.initial_block_rate,
+            decay_factor: self.decay_factor,
+            blocks_per_period: self.blocks_per_period,
+            last_update_slot: self.last_update_slot,
+            acc_reward_per_share: self.acc_reward_per_share,
+            bump: self.bump,
+            referral_bps: 0,
+            undistributed: 0,
+            carry_over_undistributed: false,
+            total_funded: 0,
+            total_emitted: 0,
+            total_claimed: 0,
+            reward_dust: 0,
+            protocol_fee_bps: 0,
+            protocol_fees_accrued: 0,
+            loyalty_max_bonus_bps: 0,
+            loyalty_ramp_secs: 0,
+            reserved: [0; 39],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator};
+
+    /// 模拟链上旧账户数据：discriminator + v0 borsh 数据
+    fn legacy_bytes<T: AnchorSerialize>(discriminator: &[u8], value: &T) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        value.serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_legacy_lengths_match_serialized_size() {
+        let pool = PoolStateV0 {
+            authority: Pubkey::new_unique(),
+            wrapped_usdc_mint: Pubkey::new_unique(),
+            lp_token_mint: Pubkey::new_unique(),
+            pool_usdc_account: Pubkey::new_unique(),
+            total_deposited: 1,
+            total_lp_supply: 2,
+            total_staked: 3,
+            reward_vault: Pubkey::new_unique(),
+            bump: 255,
+        };
+        assert_eq!(legacy_bytes(PoolState::DISCRIMINATOR, &pool).len(), PoolStateV0::LEN);
+
+        let position = UserPositionV0 {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            lp_balance: 1,
+            staked_amount: 2,
+            reward_debt: 3,
+            pending_reward: 4,
+            last_stake_time: 5,
+            last_claim_time: 6,
+            bump: 254,
+        };
+        assert_eq!(
+            legacy_bytes(UserPosition::DISCRIMINATOR, &position).len(),
+            UserPositionV0::LEN
+        );
+
+        let config = RewardConfigV0 {
+            pool: Pubkey::new_unique(),
+            emission_type: EmissionType::BlockBased,
+            emission_rate: 1,
+            initial_block_rate: 2,
+            decay_factor: 3,
+            blocks_per_period: 4,
+            last_update_slot: 5,
+            acc_reward_per_share: 6,
+            bump: 253,
+        };
+        assert_eq!(
+            legacy_bytes(RewardConfig::DISCRIMINATOR, &config).len(),
+            RewardConfigV0::LEN
+        );
+    }
+
+    #[test]
+    fn test_upgrade_user_position_preserves_fields() {
+        let legacy = UserPositionV0 {
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            lp_balance: 100,
+            staked_amount: 50,
+            reward_debt: 7_000_000_000_000,
+            pending_reward: 42,
+            last_stake_time: 1_700_000_000,
+            last_claim_time: 1_700_000_100,
+            bump: 251,
+        };
+
+        let upgraded = legacy.clone().upgrade();
+
+        // 升级后按当前布局序列化，应能被 Anchor 正常反序列化
+        let mut data = Vec::new();
+        upgraded.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), UserPosition::LEN);
+
+        let decoded = UserPosition::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.version, UserPosition::VERSION);
+        assert_eq!(decoded.owner, legacy.owner);
+        assert_eq!(decoded.lp_balance, 100);
+        assert_eq!(decoded.staked_amount, 50);
+        assert_eq!(decoded.reward_debt, 7_000_000_000_000);
+        assert_eq!(decoded.pending_reward, 42);
+        assert_eq!(decoded.bump, 251);
+    }
+
+    #[test]
+    fn test_upgraded_lengths_match_current_len() {
+        let mut data = Vec::new();
+        PoolStateV0 {
+            authority: Pubkey::default(),
+            wrapped_usdc_mint: Pubkey::default(),
+            lp_token_mint: Pubkey::default(),
+            pool_usdc_account: Pubkey::default(),
+            total_deposited: 0,
+            total_lp_supply: 0,
+            total_staked: 0,
+            reward_vault: Pubkey::default(),
+            bump: 0,
+        }
+        .upgrade()
+        .try_serialize(&mut data)
+        .unwrap();
+        assert_eq!(data.len(), PoolState::LEN);
+
+        let mut data = Vec::new();
+        RewardConfigV0 {
+            pool: Pubkey::default(),
+            emission_type: EmissionType::FixedRate,
+            emission_rate: 0,
+            initial_block_rate: 0,
+            decay_factor: 0,
+            blocks_per_period: 0,
+            last_update_slot: 0,
+            acc_reward_per_share: 0,
+            bump: 0,
+        }
+        .upgrade()
+        .try_serialize(&mut data)
+        .unwrap();
+        assert_eq!(data.len(), RewardConfig::LEN);
+    }
+
+    #[test]
+    fn test_v1_user_position_reads_with_zero_lifetime_stats() {
+        // v1 账户：版本号为 1，累计统计字段所在的预留空间为零
+        let position = UserPosition {
+            version: 1,
+            owner: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            lp_balance: 100,
+            staked_amount: 50,
+            bump: 250,
+            ..UserPosition::test_default()
+        };
+        let mut data = Vec::new();
+        position.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), UserPosition::LEN);
+
+        let mut decoded = UserPosition::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.cumulative_deposited, 0);
+        assert_eq!(decoded.first_deposit_time, 0);
+        assert_eq!(decoded.claim_count, 0);
+
+        decoded.record_deposit(1_000, 1_700_000_000).unwrap();
+        decoded.record_deposit(500, 1_700_000_100).unwrap();
+        decoded.record_claim(42).unwrap();
+        assert_eq!(decoded.cumulative_deposited, 1_500);
+        assert_eq!(decoded.first_deposit_time, 1_700_000_000);
+        assert_eq!(decoded.total_rewards_claimed, 42);
+        assert_eq!(decoded.claim_count, 1);
+    }
+
+    #[test]
+    fn test_v1_pool_and_config_read_with_zeroed_new_fields() {
+        // v1 账户：版本号为 1，v2 字段所在的预留空间为零
+        let pool = PoolState {
+            version: 1,
+            total_deposited: 1_000,
+            total_staked: 500,
+            ..PoolState::test_default()
+        };
+        let mut data = Vec::new();
+        pool.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), PoolState::LEN);
+        let decoded = PoolState::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.version, 1);
+        assert_eq!(decoded.total_staked, 500);
+        assert_eq!(decoded.outflow_limit_bps, 0);
+        assert_eq!(decoded.flash_loan_amount, 0);
+        assert!(!decoded.paused);
+
+        let config = RewardConfig {
+            version: 1,
+            acc_reward_per_share: 7,
+            ..RewardConfig::test_default()
+        };
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), RewardConfig::LEN);
+        let decoded = RewardConfig::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.version, 1);
+        assert_eq!(decoded.acc_reward_per_share, 7);
+        assert_eq!(decoded.protocol_fee_bps, 0);
+    }
+}
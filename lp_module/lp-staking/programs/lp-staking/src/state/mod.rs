@@ -1,7 +1,23 @@
 pub mod pool_state;
 pub mod user_position;
 pub mod reward_config;
+pub mod strategy_state;
+pub mod tokenized_position;
+pub mod vote_escrow;
+pub mod stake_history;
+pub mod snapshot;
+pub mod stable_swap;
+pub mod operator_approval;
+pub mod legacy;
 
 pub use pool_state::*;
 pub use user_position::*;
 pub use reward_config::*;
+pub use strategy_state::*;
+pub use tokenized_position::*;
+pub use vote_escrow::*;
+pub use stake_history::*;
+pub use snapshot::*;
+pub use stable_swap::*;
+pub use operator_approval::*;
+pub use legacy::*;
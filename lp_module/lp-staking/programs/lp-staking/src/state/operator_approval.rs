@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+/// 仓位操作员授权
+/// 仓位所有者授权的操作员（可以是其他程序的 PDA，通过 CPI 签名）
+/// 可以代为质押、解除质押，但无法提取 LP Token 或改变奖励接收地址
+#[account]
+pub struct OperatorApproval {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 仓位所有者
+    pub owner: Pubkey,
+    
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    
+    /// 被授权的操作员
+    pub operator: Pubkey,
+    
+    /// 授权时间（Unix 时间戳）
+    pub approved_at: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留空间（新增字段从这里划分，避免再次 realloc）
+    pub reserved: [u8; 32],
+}
+
+impl OperatorApproval {
+    /// 计算账户大小
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // version
+        32 + // owner
+        32 + // pool
+        32 + // operator
+        8 +  // approved_at
+        1 +  // bump
+        32;  // reserved
+    
+    /// 当前账户布局版本
+    pub const VERSION: u8 = 1;
+}
@@ -1,9 +1,14 @@
 use anchor_lang::prelude::*;
+use crate::constants::BASIS_POINTS;
+use crate::errors::LpStakingError;
 
 /// 流动性池状态账户
 /// 存储池子的核心信息和配置
 #[account]
 pub struct PoolState {
+    /// 账户布局版本
+    pub version: u8,
+    
     /// 管理员权限
     pub authority: Pubkey,
     
@@ -30,11 +35,87 @@ pub struct PoolState {
     
     /// PDA bump
     pub bump: u8,
+    
+    /// 已创建的质押快照数量（下一个快照编号）
+    pub snapshot_count: u64,
+    
+    /// 国库地址（接收管理员提取的资金，Pubkey::default() 表示未设置）
+    pub treasury: Pubkey,
+    
+    /// 守护者（可调整或重置提取限额，Pubkey::default() 表示未设置）
+    pub guardian: Pubkey,
+    
+    /// 每个窗口允许提取的 TVL 比例（基点，0 表示不限制）
+    pub outflow_limit_bps: u64,
+    
+    /// 提取限额窗口长度（秒）
+    pub outflow_window_secs: i64,
+    
+    /// 当前窗口开始时间（Unix 时间戳）
+    pub outflow_window_start: i64,
+    
+    /// 当前窗口开始时的 TVL（wrappedUSDC）
+    pub outflow_window_tvl: u64,
+    
+    /// 当前窗口内已提取的数量
+    pub outflow_current: u64,
+    
+    /// 上一个窗口内提取的数量（按剩余时间比例计入滚动窗口）
+    pub outflow_previous: u64,
+    
+    /// 价格账户（wrappedUSDC/USD，Pubkey::default() 表示不启用脱锚保护）
+    pub price_feed: Pubkey,
+    
+    /// 允许的锚定偏离（基点）
+    pub peg_band_bps: u64,
+    
+    /// 报价最大时效（秒）
+    pub max_price_age_secs: i64,
+    
+    /// 是否暂停存取
+    pub paused: bool,
+    
+    /// 闪电贷手续费（基点）
+    pub flash_loan_fee_bps: u64,
+    
+    /// 未归还的闪电贷本金（0 表示无未归还借款）
+    pub flash_loan_amount: u64,
+    
+    /// 未归还的闪电贷手续费
+    pub flash_loan_fee: u64,
+    
+    /// 累计存入量（wrappedUSDC，v2 之前创建的池子从升级时开始统计）
+    pub cumulative_deposited: u64,
+    
+    /// 累计提取量（wrappedUSDC，v2 之前创建的池子从升级时开始统计）
+    pub cumulative_withdrawn: u64,
+    
+    /// 活跃仓位数量（已创建且未关闭的 UserPosition，升级时由 migrate_pool 写入初始值）
+    pub active_positions: u64,
+    
+    /// 质押量大于零的仓位数量，包括代币化仓位（升级时由 migrate_pool 写入初始值）
+    pub staker_count: u64,
+    
+    /// 用户累计领取的奖励（lamports，含推荐奖励，v2 之前创建的池子从升级时开始统计）
+    pub total_rewards_claimed: u64,
+    
+    /// 最近一次用户操作的 slot
+    pub last_activity_slot: u64,
+    
+    /// 所有仓位的忠诚度加成之和（奖励权重 = total_staked + total_loyalty_bonus）
+    pub total_loyalty_bonus: u64,
+    
+    /// 全局质押量历史的当前分页编号（当前分页写满后指向下一页）
+    pub stake_history_page: u32,
+    
+    /// 预留空间（新增字段从这里划分，避免再次 realloc）
+    pub reserved: [u8; 3],
 }
 
 impl PoolState {
     /// 计算账户大小
     pub const LEN: usize = 8 + // discriminator
+        1 +  // version
         32 + // authority
         32 + // wrapped_usdc_mint
         32 + // lp_token_mint
@@ -43,5 +124,223 @@ impl PoolState {
         8 +  // total_lp_supply
         8 +  // total_staked
         32 + // reward_vault
-        1;   // bump
+        1 +  // bump
+        8 +  // snapshot_count
+        32 + // treasury
+        32 + // guardian
+        8 +  // outflow_limit_bps
+        8 +  // outflow_window_secs
+        8 +  // outflow_window_start
+        8 +  // outflow_window_tvl
+        8 +  // outflow_current
+        8 +  // outflow_previous
+        32 + // price_feed
+        8 +  // peg_band_bps
+        8 +  // max_price_age_secs
+        1 +  // paused
+        8 +  // flash_loan_fee_bps
+        8 +  // flash_loan_amount
+        8 +  // flash_loan_fee
+        8 +  // cumulative_deposited
+        8 +  // cumulative_withdrawn
+        8 +  // active_positions
+        8 +  // staker_count
+        8 +  // total_rewards_claimed
+        8 +  // last_activity_slot
+        8 +  // total_loyalty_bonus
+        4 +  // stake_history_page
+        3;   // reserved
+    
+    /// 当前账户布局版本
+    ///
+    /// 从预留空间划分新字段时提升版本号，并在 migrate_pool 中升级旧版本账户：
+    /// * v1：版本号与预留空间
+    /// * v2：快照、国库、守护者、提取限额、脱锚保护、闪电贷、统计与忠诚度字段
+    /// * v3：全局质押量历史分页编号
+    pub const VERSION: u8 = 3;
+    
+    /// 奖励分配的总权重（质押量加忠诚度加成）
+    pub fn total_reward_weight(&self) -> Result<u64> {
+        self.total_staked
+            .checked_add(self.total_loyalty_bonus)
+            .ok_or(LpStakingError::MathOverflow.into())
+    }
+    
+    /// 记录最近一次用户操作的 slot
+    pub fn record_activity(&mut self, slot: u64) {
+        self.last_activity_slot = slot;
+    }
+    
+    /// 仓位质押量变化后更新质押仓位数量
+    ///
+    /// 升级前已存在的仓位由 migrate_pool 写入初始计数，计数偏差会以 MathOverflow 暴露
+    pub fn update_staker_count(&mut self, staked_before: u64, staked_after: u64) -> Result<()> {
+        if staked_before == 0 && staked_after > 0 {
+            self.staker_count = self.staker_count
+                .checked_add(1)
+                .ok_or(LpStakingError::MathOverflow)?;
+        } else if staked_before > 0 && staked_after == 0 {
+            self.staker_count = self.staker_count
+                .checked_sub(1)
+                .ok_or(LpStakingError::MathOverflow)?;
+        }
+        Ok(())
+    }
+    
+    /// 新建仓位后增加活跃仓位数量
+    pub fn open_position(&mut self) -> Result<()> {
+        self.active_positions = self.active_positions
+            .checked_add(1)
+            .ok_or(LpStakingError::MathOverflow)?;
+        Ok(())
+    }
+    
+    /// 重置提取限额窗口，以当前 TVL 作为新窗口的基准
+    pub fn reset_outflow_window(&mut self, now: i64) {
+        self.outflow_window_start = now;
+        self.outflow_window_tvl = self.total_deposited;
+        self.outflow_current = 0;
+        self.outflow_previous = 0;
+    }
+    
+    /// 记录一笔提取并检查滚动窗口限额
+    ///
+    /// 滚动窗口内的提取量 = 上一窗口提取量 * 剩余比例 + 当前窗口提取量，
+    /// 不得超过窗口开始时 TVL 的 outflow_limit_bps。窗口内新存入的资金不提高限额，
+    /// 避免攻击者先存入再放大可提取量；需要提高限额时由 guardian 重置窗口
+    pub fn record_outflow(&mut self, amount: u64, now: i64) -> Result<()> {
+        if self.outflow_limit_bps == 0 {
+            return Ok(());
+        }
+        
+        // 1. 推进窗口
+        let window = self.outflow_window_secs;
+        let elapsed = now.saturating_sub(self.outflow_window_start);
+        if elapsed >= window.saturating_mul(2) {
+            self.reset_outflow_window(now);
+        } else if elapsed >= window {
+            self.outflow_window_start += window;
+            self.outflow_window_tvl = self.total_deposited;
+            self.outflow_previous = self.outflow_current;
+            self.outflow_current = 0;
+        }
+        
+        // 2. 计算滚动窗口内的提取量
+        let remaining = (window - (now - self.outflow_window_start)).max(0) as u128;
+        let carried = (self.outflow_previous as u128) * remaining / window as u128;
+        let current = self.outflow_current
+            .checked_add(amount)
+            .ok_or(LpStakingError::MathOverflow)?;
+        
+        // 3. 检查限额
+        let limit = (self.outflow_window_tvl as u128)
+            .checked_mul(self.outflow_limit_bps as u128)
+            .ok_or(LpStakingError::MathOverflow)?
+            / BASIS_POINTS as u128;
+        require!(
+            carried + current as u128 <= limit,
+            LpStakingError::OutflowLimitExceeded
+        );
+        
+        self.outflow_current = current;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl PoolState {
+    /// 测试用的当前版本账户，其余字段全部为零
+    ///
+    /// 按全零数据反序列化，新增字段时无需修改各测试模块的构造代码
+    pub fn test_default() -> Self {
+        let mut pool = Self::try_from_slice(&[0u8; Self::LEN - 8]).unwrap();
+        pool.version = Self::VERSION;
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn pool_with_limit(total_deposited: u64, limit_bps: u64, window_secs: i64) -> PoolState {
+        let mut pool = PoolState {
+            total_deposited,
+            total_lp_supply: total_deposited,
+            outflow_limit_bps: limit_bps,
+            outflow_window_secs: window_secs,
+            ..PoolState::test_default()
+        };
+        pool.reset_outflow_window(0);
+        pool
+    }
+    
+    /// 与 withdraw 相同：先检查限额，再扣减 TVL
+    fn withdraw(pool: &mut PoolState, amount: u64, now: i64) -> Result<()> {
+        pool.record_outflow(amount, now)?;
+        pool.total_deposited -= amount;
+        Ok(())
+    }
+    
+    #[test]
+    fn test_outflow_limit_within_window() {
+        // TVL 1000，每小时最多 10%
+        let mut pool = pool_with_limit(1_000, 1_000, 3_600);
+        assert!(withdraw(&mut pool, 60, 10).is_ok());
+        assert!(withdraw(&mut pool, 40, 20).is_ok());
+        assert!(withdraw(&mut pool, 1, 30).is_err());
+        assert_eq!(pool.outflow_current, 100);
+    }
+    
+    #[test]
+    fn test_outflow_limit_rolls_over() {
+        let mut pool = pool_with_limit(1_000, 1_000, 3_600);
+        withdraw(&mut pool, 100, 0).unwrap();
+        
+        // 下一个窗口过半：基准 TVL 为 900，限额 90，上一窗口的 100 仍计入 50
+        assert!(withdraw(&mut pool, 41, 5_400).is_err());
+        assert!(withdraw(&mut pool, 40, 5_400).is_ok());
+        
+        // 两个窗口之后完全重置：基准 TVL 为 860
+        assert!(withdraw(&mut pool, 86, 20_000).is_ok());
+    }
+    
+    #[test]
+    fn test_outflow_limit_disabled() {
+        let mut pool = pool_with_limit(1_000, 0, 0);
+        assert!(pool.record_outflow(1_000, 1).is_ok());
+    }
+    
+    #[test]
+    fn test_staker_count() {
+        let mut pool = pool_with_limit(1_000, 0, 0);
+        pool.update_staker_count(0, 100).unwrap();
+        pool.update_staker_count(100, 200).unwrap();
+        pool.update_staker_count(0, 50).unwrap();
+        assert_eq!(pool.staker_count, 2);
+        
+        pool.update_staker_count(200, 0).unwrap();
+        pool.update_staker_count(50, 0).unwrap();
+        assert_eq!(pool.staker_count, 0);
+        
+        // 计数偏差不再被饱和运算掩盖
+        assert!(pool.update_staker_count(10, 0).is_err());
+    }
+    
+    #[test]
+    fn test_outflow_limit_ignores_deposits_within_window() {
+        // 窗口基准 TVL 1000，限额 100
+        let mut pool = pool_with_limit(1_000, 1_000, 3_600);
+        
+        // 窗口内存入 9000 不提高限额
+        pool.total_deposited += 9_000;
+        assert!(withdraw(&mut pool, 101, 10).is_err());
+        assert!(withdraw(&mut pool, 100, 10).is_ok());
+        assert!(withdraw(&mut pool, 1, 20).is_err());
+        assert_eq!(pool.outflow_window_tvl, 1_000);
+        
+        // guardian 重置窗口后按当前 TVL 计算限额
+        pool.reset_outflow_window(30);
+        assert!(withdraw(&mut pool, 990, 40).is_ok());
+    }
 }
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::errors::LpStakingError;
 
 /// 奖励排放类型
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
@@ -13,6 +14,9 @@ pub enum EmissionType {
 /// 管理奖励分配策略和参数
 #[account]
 pub struct RewardConfig {
+    /// 账户布局版本
+    pub version: u8,
+    
     /// 关联的池子地址
     pub pool: Pubkey,
     
@@ -40,16 +44,53 @@ pub struct RewardConfig {
     pub last_update_slot: u64,
     
     /// 累计每份奖励（精度放大 1e12）
-    /// acc_reward_per_share += (reward * 1e12) / total_staked
+    /// acc_reward_per_share += (reward * 1e12) / (total_staked + total_loyalty_bonus)
     pub acc_reward_per_share: u128,
     
     /// PDA bump
     pub bump: u8,
+    
+    /// 推荐奖励比例（基点，相对于被推荐人领取的奖励，额外从排放中支付）
+    pub referral_bps: u64,
+    
+    /// 无人质押期间产生、尚未分配的奖励（lamports）
+    pub undistributed: u64,
+    
+    /// 是否将未分配奖励并入下一次分配（否则留待管理员提取到国库）
+    pub carry_over_undistributed: bool,
+    
+    /// 累计注入奖励金库的资金（lamports，通过 fund_rewards 记账）
+    pub total_funded: u64,
+    
+    /// 累计排放的奖励（lamports，含未分配奖励与推荐奖励，不超过 total_funded）
+    pub total_emitted: u64,
+    
+    /// 累计从奖励金库支付的奖励（lamports，含推荐奖励与国库提取）
+    pub total_claimed: u64,
+    
+    /// 取整产生的尘埃（精度放大 1e12），满 1 lamport 后转入未分配奖励
+    pub reward_dust: u128,
+    
+    /// 协议抽成比例（基点，从每次排放中扣除）
+    pub protocol_fee_bps: u64,
+    
+    /// 已计提、尚未提取到国库的协议奖励（lamports）
+    pub protocol_fees_accrued: u64,
+    
+    /// 忠诚度加成上限（基点，相对于质押量；0 表示未开启）
+    pub loyalty_max_bonus_bps: u64,
+    
+    /// 忠诚度加成达到上限所需的连续质押时长（秒）
+    pub loyalty_ramp_secs: i64,
+    
+    /// 预留空间（新增字段从这里划分，避免再次 realloc）
+    pub reserved: [u8; 39],
 }
 
 impl RewardConfig {
     /// 计算账户大小
     pub const LEN: usize = 8 + // discriminator
+        1 +  // version
         32 + // pool
         1 +  // emission_type
         8 +  // emission_rate
@@ -58,8 +99,158 @@ impl RewardConfig {
         8 +  // blocks_per_period
         8 +  // last_update_slot
         16 + // acc_reward_per_share (u128)
-        1;   // bump
+        1 +  // bump
+        8 +  // referral_bps
+        8 +  // undistributed
+        1 +  // carry_over_undistributed
+        8 +  // total_funded
+        8 +  // total_emitted
+        8 +  // total_claimed
+        16 + // reward_dust (u128)
+        8 +  // protocol_fee_bps
+        8 +  // protocol_fees_accrued
+        8 +  // loyalty_max_bonus_bps
+        8 +  // loyalty_ramp_secs
+        39;  // reserved
+    
+    /// 当前账户布局版本
+    ///
+    /// 从预留空间划分新字段时提升版本号，并在 migrate_pool 中升级旧版本账户：
+    /// * v1：版本号与预留空间
+    /// * v2：推荐比例、未分配奖励、奖励记账、协议抽成与忠诚度曲线字段
+    pub const VERSION: u8 = 2;
     
     /// 精度因子（1e12）
     pub const PRECISION: u128 = 1_000_000_000_000;
+    
+    /// 已注入但尚未排放的资金
+    pub fn unemitted(&self) -> u64 {
+        self.total_funded.saturating_sub(self.total_emitted)
+    }
+    
+    /// 记录排放（调用方需保证不超过 unemitted）
+    pub fn record_emission(&mut self, amount: u64) -> Result<()> {
+        self.total_emitted = self.total_emitted
+            .checked_add(amount)
+            .ok_or(LpStakingError::MathOverflow)?;
+        self.check_invariant()
+    }
+    
+    /// 记录从奖励金库支付的奖励
+    pub fn record_payout(&mut self, amount: u64) -> Result<()> {
+        self.total_claimed = self.total_claimed
+            .checked_add(amount)
+            .ok_or(LpStakingError::MathOverflow)?;
+        self.check_invariant()
+    }
+    
+    /// 记录取整尘埃（精度放大 1e12），满 1 lamport 的部分转入未分配奖励
+    ///
+    /// 尘埃已计入 total_emitted 但无人可领取，转入未分配奖励后可结转或提取到国库
+    pub fn record_dust(&mut self, dust: u128) -> Result<()> {
+        let dust = self.reward_dust
+            .checked_add(dust)
+            .ok_or(LpStakingError::MathOverflow)?;
+        let whole = u64::try_from(dust / Self::PRECISION)
+            .map_err(|_| LpStakingError::MathOverflow)?;
+        
+        self.undistributed = self.undistributed
+            .checked_add(whole)
+            .ok_or(LpStakingError::MathOverflow)?;
+        self.reward_dust = dust % Self::PRECISION;
+        Ok(())
+    }
+    
+    /// 为 v2 之前的配置写入奖励记账初始值（migrate_pool 调用）
+    ///
+    /// 升级前已累计但尚未领取的奖励（rewards_owed，链下按仓位统计）视为已排放，
+    /// 金库可用余额视为已注入；余额不足以覆盖 rewards_owed 时按 rewards_owed 记账，
+    /// 保证升级前的奖励可以领取且不变量成立，差额由金库不足时的欠付流程处理
+    pub fn seed_accounting(&mut self, vault_available: u64, rewards_owed: u64) {
+        self.total_emitted = rewards_owed;
+        self.total_funded = vault_available.max(rewards_owed);
+        self.total_claimed = 0;
+    }
+    
+    /// 校验奖励不变量：total_claimed <= total_emitted <= total_funded
+    pub fn check_invariant(&self) -> Result<()> {
+        require!(
+            self.total_claimed <= self.total_emitted && self.total_emitted <= self.total_funded,
+            LpStakingError::RewardInvariantViolated
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl RewardConfig {
+    /// 测试用的当前版本账户，其余字段全部为零
+    ///
+    /// 按全零数据反序列化，新增字段时无需修改各测试模块的构造代码
+    pub fn test_default() -> Self {
+        let mut config = Self::try_from_slice(&[0u8; Self::LEN - 8]).unwrap();
+        config.version = Self::VERSION;
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn config(total_funded: u64, total_emitted: u64, total_claimed: u64) -> RewardConfig {
+        RewardConfig {
+            total_funded,
+            total_emitted,
+            total_claimed,
+            ..RewardConfig::test_default()
+        }
+    }
+    
+    #[test]
+    fn test_invariant_rejects_overpayment() {
+        let mut cfg = config(100, 60, 50);
+        assert!(cfg.record_payout(10).is_ok());
+        assert!(cfg.record_payout(1).is_err());
+        
+        let mut cfg = config(100, 60, 0);
+        assert_eq!(cfg.unemitted(), 40);
+        assert!(cfg.record_emission(40).is_ok());
+        assert!(cfg.record_emission(1).is_err());
+    }
+    
+    #[test]
+    fn test_dust_flushed_to_undistributed() {
+        let mut cfg = config(0, 0, 0);
+        cfg.record_dust(RewardConfig::PRECISION / 2).unwrap();
+        assert_eq!(cfg.undistributed, 0);
+        
+        cfg.record_dust(RewardConfig::PRECISION / 2 + 7).unwrap();
+        assert_eq!(cfg.undistributed, 1);
+        assert_eq!(cfg.reward_dust, 7);
+    }
+    
+    #[test]
+    fn test_seed_accounting_for_migrated_config() {
+        // v1 配置：已有累计奖励，记账字段为零
+        let mut cfg = RewardConfig {
+            version: 1,
+            acc_reward_per_share: 5 * RewardConfig::PRECISION,
+            ..RewardConfig::test_default()
+        };
+        cfg.seed_accounting(1_000, 300);
+        
+        // 升级前欠付的 300 可以领取，剩余 700 继续排放
+        assert_eq!(cfg.unemitted(), 700);
+        assert!(cfg.record_payout(300).is_ok());
+        assert!(cfg.record_payout(1).is_err());
+        assert!(cfg.record_emission(700).is_ok());
+        
+        // 金库余额不足以覆盖欠付：不再排放新奖励，欠付仍可记账领取
+        let mut cfg = RewardConfig::test_default();
+        cfg.seed_accounting(100, 300);
+        assert_eq!(cfg.unemitted(), 0);
+        assert!(cfg.check_invariant().is_ok());
+        assert!(cfg.record_payout(300).is_ok());
+    }
 }
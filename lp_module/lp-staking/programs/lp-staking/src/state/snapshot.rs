@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+/// 质押快照
+/// 记录快照编号对应的 slot 与池子总质押量，用户质押量通过其 StakeHistory 在该 slot 的检查点证明
+#[account]
+pub struct Snapshot {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    
+    /// 快照编号（从 0 递增）
+    pub id: u64,
+    
+    /// 快照 slot
+    pub slot: u64,
+    
+    /// 快照时间（Unix 时间戳）
+    pub timestamp: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 快照时的池子总质押量
+    pub total_staked: u64,
+    
+    /// 预留空间（新增字段从这里划分，避免再次 realloc）
+    pub reserved: [u8; 24],
+}
+
+impl Snapshot {
+    /// 计算账户大小
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // version
+        32 + // pool
+        8 +  // id
+        8 +  // slot
+        8 +  // timestamp
+        1 +  // bump
+        8 +  // total_staked
+        24;  // reserved
+    
+    /// 当前账户布局版本
+    ///
+    /// 从预留空间划分新字段时提升版本号：
+    /// * v1：总质押量由全局 StakeHistory 在快照 slot 的检查点给出
+    /// * v2：快照账户直接记录总质押量
+    pub const VERSION: u8 = 2;
+}
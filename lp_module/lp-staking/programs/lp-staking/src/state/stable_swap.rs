@@ -0,0 +1,346 @@
+use anchor_lang::prelude::*;
+use crate::errors::LpStakingError;
+use crate::state::RewardConfig;
+
+/// StableSwap 池中的币种
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StableToken {
+    /// 币种 A（池子的 wrappedUSDC）
+    A,
+    /// 币种 B（例如 wrappedUSDT）
+    B,
+}
+
+impl StableToken {
+    /// 在余额数组中的下标
+    pub fn index(self) -> usize {
+        match self {
+            StableToken::A => 0,
+            StableToken::B => 1,
+        }
+    }
+}
+
+/// StableSwap 双稳定币池
+/// 与单币种池共用管理员，拥有独立的 LP Token 与代币金库，按 Curve StableSwap 不变量定价。
+/// StableSwap LP 可质押到本池的质押子池，按 reward_rate 独立排放 SOL 奖励，
+/// 奖励记账与单币种池的 total_staked / RewardConfig 分开，两种 LP 不按同一权重混合
+#[account]
+pub struct StableSwapState {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    
+    /// 币种 A mint（池子的 wrappedUSDC）
+    pub token_a_mint: Pubkey,
+    
+    /// 币种 B mint（例如 wrappedUSDT）
+    pub token_b_mint: Pubkey,
+    
+    /// 币种 A 金库
+    pub vault_a: Pubkey,
+    
+    /// 币种 B 金库
+    pub vault_b: Pubkey,
+    
+    /// StableSwap LP Token mint
+    pub lp_mint: Pubkey,
+    
+    /// 放大系数 A
+    pub amp: u64,
+    
+    /// 兑换手续费（基点），留在池中归 LP 所有
+    pub fee_bps: u64,
+    
+    /// 币种 A 余额
+    pub reserve_a: u64,
+    
+    /// 币种 B 余额
+    pub reserve_b: u64,
+    
+    /// LP Token 供应量
+    pub lp_supply: u64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 质押奖励排放速率（lamports / slot）
+    pub reward_rate: u64,
+    
+    /// 已质押的 StableSwap LP 数量
+    pub total_staked: u64,
+    
+    /// 累计每单位质押 LP 的奖励（乘以 RewardConfig::PRECISION）
+    pub acc_reward_per_share: u128,
+    
+    /// 上次更新奖励的 slot
+    pub last_reward_slot: u64,
+    
+    /// 累计注入质押奖励金库的资金（排放上限）
+    pub total_funded: u64,
+    
+    /// 累计排放的质押奖励
+    pub total_emitted: u64,
+    
+    /// 预留空间（新增字段从这里划分，避免再次 realloc）
+    pub reserved: [u8; 8],
+}
+
+impl StableSwapState {
+    /// 计算账户大小
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // version
+        32 + // pool
+        32 + // token_a_mint
+        32 + // token_b_mint
+        32 + // vault_a
+        32 + // vault_b
+        32 + // lp_mint
+        8 +  // amp
+        8 +  // fee_bps
+        8 +  // reserve_a
+        8 +  // reserve_b
+        8 +  // lp_supply
+        1 +  // bump
+        8 +  // reward_rate
+        8 +  // total_staked
+        16 + // acc_reward_per_share
+        8 +  // last_reward_slot
+        8 +  // total_funded
+        8 +  // total_emitted
+        8;   // reserved
+    
+    /// 当前账户布局版本
+    ///
+    /// 从预留空间划分新字段时提升版本号：
+    /// * v1：兑换与流动性
+    /// * v2：LP 质押与奖励字段（由 v1 的全零预留空间划分，v1 账户在首次质押相关操作时原地升级）
+    pub const VERSION: u8 = 2;
+    
+    /// 最大放大系数
+    pub const MAX_AMP: u64 = 1_000_000;
+    
+    /// 最大手续费（1%）
+    pub const MAX_FEE_BPS: u64 = 100;
+    
+    /// 两币种余额
+    pub fn reserves(&self) -> [u64; 2] {
+        [self.reserve_a, self.reserve_b]
+    }
+    
+    /// 写回两币种余额
+    pub fn set_reserves(&mut self, reserves: [u64; 2]) {
+        self.reserve_a = reserves[0];
+        self.reserve_b = reserves[1];
+    }
+    
+    /// v1 账户原地升级（新增字段位于全零的预留空间，无需迁移数据）
+    pub fn upgrade(&mut self) {
+        if self.version < Self::VERSION {
+            self.version = Self::VERSION;
+        }
+    }
+    
+    /// 已注资但尚未排放的质押奖励
+    pub fn unemitted(&self) -> u64 {
+        self.total_funded.saturating_sub(self.total_emitted)
+    }
+    
+    /// 将质押奖励推进到 slot
+    ///
+    /// 按 reward_rate 排放，以已注资未排放的资金为上限；没有质押时本段不排放
+    pub fn update_reward(&mut self, slot: u64) -> Result<()> {
+        if slot <= self.last_reward_slot {
+            return Ok(());
+        }
+        
+        if self.total_staked > 0 && self.reward_rate > 0 {
+            let elapsed = slot - self.last_reward_slot;
+            let emitted = (elapsed as u128)
+                .checked_mul(self.reward_rate as u128)
+                .ok_or(LpStakingError::MathOverflow)?
+                .min(self.unemitted() as u128);
+            
+            let reward_per_share = emitted
+                .checked_mul(RewardConfig::PRECISION)
+                .ok_or(LpStakingError::MathOverflow)?
+                / self.total_staked as u128;
+            self.acc_reward_per_share = self.acc_reward_per_share
+                .checked_add(reward_per_share)
+                .ok_or(LpStakingError::MathOverflow)?;
+            self.total_emitted = self.total_emitted
+                .checked_add(emitted as u64)
+                .ok_or(LpStakingError::MathOverflow)?;
+        }
+        
+        self.last_reward_slot = slot;
+        Ok(())
+    }
+}
+
+/// StableSwap LP 质押仓位
+/// 种子为 [STABLE_STAKE_SEED, owner, stable_swap]，质押的 LP 托管在 stable_stake_vault
+#[account]
+pub struct StableStakePosition {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 用户钱包地址
+    pub owner: Pubkey,
+    
+    /// 关联的 StableSwap 池地址
+    pub stable_swap: Pubkey,
+    
+    /// 已质押的 StableSwap LP 数量
+    pub staked_amount: u64,
+    
+    /// 奖励债务（staked_amount * acc_reward_per_share）
+    pub reward_debt: u128,
+    
+    /// 已结算未领取的奖励
+    pub pending_reward: u64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留空间
+    pub reserved: [u8; 32],
+}
+
+impl StableStakePosition {
+    /// 计算账户大小
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // version
+        32 + // owner
+        32 + // stable_swap
+        8 +  // staked_amount
+        16 + // reward_debt
+        8 +  // pending_reward
+        1 +  // bump
+        32;  // reserved
+    
+    /// 当前账户布局版本
+    pub const VERSION: u8 = 1;
+    
+    /// 按当前累计奖励结算待领取奖励，并将质押数量调整为 staked_amount
+    pub fn settle(&mut self, acc_reward_per_share: u128, staked_amount: u64) -> Result<()> {
+        let accumulated = (self.staked_amount as u128)
+            .checked_mul(acc_reward_per_share)
+            .ok_or(LpStakingError::MathOverflow)?
+            .checked_sub(self.reward_debt)
+            .ok_or(LpStakingError::MathOverflow)?;
+        let pending = u64::try_from(accumulated / RewardConfig::PRECISION)
+            .map_err(|_| LpStakingError::MathOverflow)?;
+        self.pending_reward = self.pending_reward
+            .checked_add(pending)
+            .ok_or(LpStakingError::MathOverflow)?;
+        
+        self.staked_amount = staked_amount;
+        self.reward_debt = (staked_amount as u128)
+            .checked_mul(acc_reward_per_share)
+            .ok_or(LpStakingError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn stable_swap() -> StableSwapState {
+        StableSwapState {
+            version: StableSwapState::VERSION,
+            pool: Pubkey::default(),
+            token_a_mint: Pubkey::default(),
+            token_b_mint: Pubkey::default(),
+            vault_a: Pubkey::default(),
+            vault_b: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            amp: 100,
+            fee_bps: 4,
+            reserve_a: 0,
+            reserve_b: 0,
+            lp_supply: 0,
+            bump: 0,
+            reward_rate: 0,
+            total_staked: 0,
+            acc_reward_per_share: 0,
+            last_reward_slot: 0,
+            total_funded: 0,
+            total_emitted: 0,
+            reserved: [0; 8],
+        }
+    }
+    
+    fn position(staked_amount: u64, acc_reward_per_share: u128) -> StableStakePosition {
+        StableStakePosition {
+            version: StableStakePosition::VERSION,
+            owner: Pubkey::default(),
+            stable_swap: Pubkey::default(),
+            staked_amount,
+            reward_debt: staked_amount as u128 * acc_reward_per_share,
+            pending_reward: 0,
+            bump: 0,
+            reserved: [0; 32],
+        }
+    }
+    
+    #[test]
+    fn test_space_matches_serialized_size() {
+        let mut data = Vec::new();
+        stable_swap().try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), StableSwapState::LEN);
+        
+        let mut data = Vec::new();
+        position(0, 0).try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), StableStakePosition::LEN);
+    }
+    
+    #[test]
+    fn test_emission_capped_by_funding_and_skipped_without_stake() {
+        let mut s = stable_swap();
+        s.reward_rate = 100;
+        s.total_funded = 1_500;
+        
+        // 没有质押时不排放
+        s.update_reward(10).unwrap();
+        assert_eq!(s.total_emitted, 0);
+        assert_eq!(s.last_reward_slot, 10);
+        
+        s.total_staked = 1_000;
+        s.update_reward(20).unwrap();
+        assert_eq!(s.total_emitted, 1_000);
+        assert_eq!(s.acc_reward_per_share, RewardConfig::PRECISION);
+        
+        // 剩余注资只够 500
+        s.update_reward(30).unwrap();
+        assert_eq!(s.total_emitted, 1_500);
+        assert_eq!(s.unemitted(), 0);
+    }
+    
+    #[test]
+    fn test_stakers_share_emission_by_stake() {
+        let mut s = stable_swap();
+        s.reward_rate = 300;
+        s.total_funded = u64::MAX;
+        
+        let mut alice = position(0, 0);
+        alice.settle(s.acc_reward_per_share, 1_000).unwrap();
+        s.total_staked = 1_000;
+        
+        s.update_reward(10).unwrap();
+        let mut bob = position(0, 0);
+        bob.settle(s.acc_reward_per_share, 2_000).unwrap();
+        s.total_staked = 3_000;
+        
+        s.update_reward(20).unwrap();
+        alice.settle(s.acc_reward_per_share, 0).unwrap();
+        bob.settle(s.acc_reward_per_share, 0).unwrap();
+        assert_eq!(alice.pending_reward, 3_000 + 1_000);
+        assert_eq!(bob.pending_reward, 2_000);
+        assert_eq!(alice.reward_debt, 0);
+        assert!(alice.pending_reward + bob.pending_reward <= s.total_emitted);
+    }
+}
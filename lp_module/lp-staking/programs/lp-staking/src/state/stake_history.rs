@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+
+/// 质押量检查点
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct StakeCheckpoint {
+    /// 检查点所在 slot
+    pub slot: u64,
+    /// 该 slot 结束时的质押量
+    pub amount: u64,
+}
+
+impl StakeCheckpoint {
+    pub const LEN: usize = 8 + 8;
+}
+
+/// 质押量历史账户
+/// 用户账户记录 UserPosition.staked_amount，代币化仓位账户（owner 为 TokenizedPosition 地址）
+/// 记录 TokenizedPosition.staked_amount。全局账户（owner 为 Pubkey::default()）记录
+/// PoolState.total_staked，按分页存储，每页最多 STAKE_HISTORY_PAGE_SIZE 个检查点；
+/// 未分页的旧全局账户已停止更新，仅用于证明 v1 快照。
+/// 检查点只追加，同一 slot 内多次变化只保留最终值
+#[account]
+pub struct StakeHistory {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    
+    /// 用户钱包或代币化仓位地址（全局历史为 Pubkey::default()）
+    pub owner: Pubkey,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留空间（新增定长字段从这里划分；检查点数组位于末尾，随追加 realloc 增长）
+    pub reserved: [u8; 32],
+    
+    /// 检查点（按 slot 升序）
+    pub checkpoints: Vec<StakeCheckpoint>,
+}
+
+impl StakeHistory {
+    /// 计算包含 n 个检查点时的账户大小
+    pub const fn space(checkpoints: usize) -> usize {
+        8 +  // discriminator
+        1 +  // version
+        32 + // pool
+        32 + // owner
+        1 +  // bump
+        32 + // reserved
+        4 + checkpoints * StakeCheckpoint::LEN
+    }
+    
+    /// 当前账户布局版本
+    pub const VERSION: u8 = 1;
+    
+    /// 记录 slot 结束时的质押量：与最后一个检查点同 slot 时覆盖，否则追加
+    ///
+    /// 返回是否追加了新条目
+    pub fn record(&mut self, slot: u64, amount: u64) -> bool {
+        match self.checkpoints.last_mut() {
+            Some(last) if last.slot == slot => {
+                last.amount = amount;
+                false
+            },
+            _ => {
+                self.checkpoints.push(StakeCheckpoint { slot, amount });
+                true
+            },
+        }
+    }
+    
+    /// 第一个检查点的 slot（没有检查点时为 None）
+    pub fn first_slot(&self) -> Option<u64> {
+        self.checkpoints.first().map(|c| c.slot)
+    }
+    
+    /// 查询 slot 结束时的质押量（早于第一个检查点时为 0）
+    pub fn amount_at(&self, slot: u64) -> u64 {
+        let index = self.checkpoints.partition_point(|c| c.slot <= slot);
+        index
+            .checked_sub(1)
+            .map(|i| self.checkpoints[i].amount)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn history() -> StakeHistory {
+        StakeHistory {
+            version: StakeHistory::VERSION,
+            pool: Pubkey::default(),
+            owner: Pubkey::default(),
+            bump: 0,
+            reserved: [0; 32],
+            checkpoints: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn test_amount_at_uses_last_checkpoint_at_or_before_slot() {
+        let mut h = history();
+        h.record(10, 100);
+        h.record(20, 300);
+        h.record(30, 0);
+        
+        assert_eq!(h.amount_at(9), 0);
+        assert_eq!(h.amount_at(10), 100);
+        assert_eq!(h.amount_at(19), 100);
+        assert_eq!(h.amount_at(25), 300);
+        assert_eq!(h.amount_at(30), 0);
+        assert_eq!(h.amount_at(u64::MAX), 0);
+    }
+    
+    #[test]
+    fn test_same_slot_keeps_final_value() {
+        let mut h = history();
+        assert_eq!(h.first_slot(), None);
+        assert!(h.record(5, 1));
+        assert!(!h.record(5, 2));
+        assert_eq!(h.checkpoints.len(), 1);
+        assert_eq!(h.amount_at(5), 2);
+        assert_eq!(h.first_slot(), Some(5));
+    }
+    
+    #[test]
+    fn test_space_matches_serialized_size() {
+        let mut h = history();
+        h.record(1, 1);
+        h.record(2, 2);
+        
+        let mut data = Vec::new();
+        h.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), StakeHistory::space(2));
+    }
+}
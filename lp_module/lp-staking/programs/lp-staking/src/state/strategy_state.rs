@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use crate::constants::BASIS_POINTS;
+
+/// 收益策略状态账户
+/// 记录池子分配给外部借贷程序的资金头寸
+#[account]
+pub struct StrategyState {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    
+    /// 外部策略程序（需实现策略接口）
+    pub strategy_program: Pubkey,
+    
+    /// 外部程序中的市场账户
+    pub market: Pubkey,
+    
+    /// 外部程序中的资金金库
+    pub market_vault: Pubkey,
+    
+    /// 池子在外部程序中的仓位账户
+    pub position: Pubkey,
+    
+    /// 最大分配比例（基点，相对于池子总资产）
+    pub max_allocation_bps: u64,
+    
+    /// 当前已分配的本金（wrappedUSDC）
+    pub allocated: u64,
+    
+    /// 累计收获的收益
+    pub total_harvested: u64,
+    
+    /// 累计确认的损失
+    pub total_loss: u64,
+    
+    /// 上次收获的 slot
+    pub last_harvest_slot: u64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留空间（新增字段从这里划分，避免再次 realloc）
+    pub reserved: [u8; 64],
+}
+
+impl StrategyState {
+    /// 计算账户大小
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // version
+        32 + // pool
+        32 + // strategy_program
+        32 + // market
+        32 + // market_vault
+        32 + // position
+        8 +  // max_allocation_bps
+        8 +  // allocated
+        8 +  // total_harvested
+        8 +  // total_loss
+        8 +  // last_harvest_slot
+        1 +  // bump
+        64;  // reserved
+    
+    /// 当前账户布局版本
+    pub const VERSION: u8 = 1;
+    
+    /// 按池子总资产计算允许分配的上限
+    pub fn max_allocation(&self, total_assets: u64) -> u64 {
+        ((total_assets as u128) * (self.max_allocation_bps as u128) / (BASIS_POINTS as u128)) as u64
+    }
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+/// 代币化质押仓位
+/// 质押权益由持有对应 1-of-1 仓位代币（position_mint）的钱包控制，
+/// 质押的 LP Token 托管在池子的 LP 金库中
+#[account]
+pub struct TokenizedPosition {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    
+    /// 仓位代币 mint（供应量固定为 1）
+    pub mint: Pubkey,
+    
+    /// 质押的 LP Token 数量
+    pub staked_amount: u64,
+    
+    /// 奖励债务（用于 Masterchef 算法）
+    pub reward_debt: u128,
+    
+    /// 待领取奖励
+    pub pending_reward: u64,
+    
+    /// 质押时间（Unix 时间戳）
+    pub last_stake_time: i64,
+    
+    /// 上次领取奖励时间（Unix 时间戳）
+    pub last_claim_time: i64,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 锁仓到期 slot（由锁仓中的质押转换而来时，到期前不可退出；0 表示无锁仓）
+    pub lock_end_slot: u64,
+    
+    /// 预留空间（新增字段从这里划分，避免再次 realloc）
+    pub reserved: [u8; 56],
+}
+
+impl TokenizedPosition {
+    /// 计算账户大小
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // version
+        32 + // pool
+        32 + // mint
+        8 +  // staked_amount
+        16 + // reward_debt (u128)
+        8 +  // pending_reward
+        8 +  // last_stake_time
+        8 +  // last_claim_time
+        1 +  // bump
+        8 +  // lock_end_slot
+        56;  // reserved
+    
+    /// 当前账户布局版本
+    ///
+    /// 从预留空间划分新字段时提升版本号：
+    /// * v1：版本号与预留空间
+    /// * v2：锁仓到期 slot（v1 账户的预留空间为零，读取为无锁仓，无需迁移）
+    pub const VERSION: u8 = 2;
+}
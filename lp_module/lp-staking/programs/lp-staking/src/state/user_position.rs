@@ -1,9 +1,13 @@
 use anchor_lang::prelude::*;
+use crate::errors::LpStakingError;
 
 /// 用户仓位账户
 /// 记录用户的 LP Token 持仓和质押信息
 #[account]
 pub struct UserPosition {
+    /// 账户布局版本
+    pub version: u8,
+    
     /// 用户钱包地址
     pub owner: Pubkey,
     
@@ -23,7 +27,8 @@ pub struct UserPosition {
     /// 待领取奖励
     pub pending_reward: u64,
     
-    /// 上次质押时间（Unix 时间戳）
+    /// 连续质押起始时间（Unix 时间戳）
+    /// 追加或部分解除质押时按数量比例调整，用于计算忠诚度加成
     pub last_stake_time: i64,
     
     /// 上次领取奖励时间（Unix 时间戳）
@@ -31,11 +36,45 @@ pub struct UserPosition {
     
     /// PDA bump
     pub bump: u8,
+    
+    /// 收获代理（可代为批量领取奖励到 owner，默认 Pubkey::default() 表示未设置）
+    pub harvest_delegate: Pubkey,
+    
+    /// 推荐人（首次记录后永久不变，默认 Pubkey::default() 表示无推荐人）
+    pub referrer: Pubkey,
+    
+    /// 锁仓的质押数量（锁仓期内不可解除质押）
+    pub locked_amount: u64,
+    
+    /// 锁仓到期 slot
+    pub lock_end_slot: u64,
+    
+    /// 累计存入量（wrappedUSDC）
+    pub cumulative_deposited: u64,
+    
+    /// 累计提取量（wrappedUSDC）
+    pub cumulative_withdrawn: u64,
+    
+    /// 累计领取的奖励（lamports，不含支付给推荐人的部分）
+    pub total_rewards_claimed: u64,
+    
+    /// 首次存入时间（Unix 时间戳，0 表示尚未存入）
+    pub first_deposit_time: i64,
+    
+    /// 领取奖励次数
+    pub claim_count: u32,
+    
+    /// 当前计入的忠诚度加成（奖励权重 = staked_amount + loyalty_bonus）
+    pub loyalty_bonus: u64,
+    
+    /// 预留空间（新增字段从这里划分，避免再次 realloc）
+    pub reserved: [u8; 4],
 }
 
 impl UserPosition {
     /// 计算账户大小
     pub const LEN: usize = 8 + // discriminator
+        1 +  // version
         32 + // owner
         32 + // pool
         8 +  // lp_balance
@@ -44,5 +83,178 @@ impl UserPosition {
         8 +  // pending_reward
         8 +  // last_stake_time
         8 +  // last_claim_time
-        1;   // bump
+        1 +  // bump
+        32 + // harvest_delegate
+        32 + // referrer
+        8 +  // locked_amount
+        8 +  // lock_end_slot
+        8 +  // cumulative_deposited
+        8 +  // cumulative_withdrawn
+        8 +  // total_rewards_claimed
+        8 +  // first_deposit_time
+        4 +  // claim_count
+        8 +  // loyalty_bonus
+        4;   // reserved
+    
+    /// 当前账户布局版本
+    ///
+    /// 从预留空间划分新字段时提升版本号，并在 migrate_position 中升级旧版本账户：
+    /// * v1：版本号与预留空间
+    /// * v2：收获代理、推荐人、锁仓、累计统计与忠诚度加成字段
+    pub const VERSION: u8 = 2;
+    
+    /// 初始化新建的仓位（包括 close_position 之后重新创建的仓位）
+    /// 显式重置所有字段，不信任任何残留状态
+    pub fn reset(&mut self, owner: Pubkey, pool: Pubkey, bump: u8) {
+        self.version = Self::VERSION;
+        self.owner = owner;
+        self.pool = pool;
+        self.lp_balance = 0;
+        self.staked_amount = 0;
+        self.reward_debt = 0;
+        self.pending_reward = 0;
+        self.last_stake_time = 0;
+        self.last_claim_time = 0;
+        self.bump = bump;
+        self.harvest_delegate = Pubkey::default();
+        self.referrer = Pubkey::default();
+        self.locked_amount = 0;
+        self.lock_end_slot = 0;
+        self.cumulative_deposited = 0;
+        self.cumulative_withdrawn = 0;
+        self.total_rewards_claimed = 0;
+        self.first_deposit_time = 0;
+        self.claim_count = 0;
+        self.loyalty_bonus = 0;
+        self.reserved = [0; 4];
+    }
+    
+    /// 奖励分配权重（质押量加忠诚度加成）
+    pub fn reward_weight(&self) -> Result<u64> {
+        self.staked_amount
+            .checked_add(self.loyalty_bonus)
+            .ok_or(LpStakingError::MathOverflow.into())
+    }
+    
+    /// 记录一笔存入
+    pub fn record_deposit(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.cumulative_deposited = self.cumulative_deposited
+            .checked_add(amount)
+            .ok_or(LpStakingError::MathOverflow)?;
+        if self.first_deposit_time == 0 {
+            self.first_deposit_time = now;
+        }
+        Ok(())
+    }
+    
+    /// 记录一笔提取
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.cumulative_withdrawn = self.cumulative_withdrawn
+            .checked_add(amount)
+            .ok_or(LpStakingError::MathOverflow)?;
+        Ok(())
+    }
+    
+    /// 记录一次奖励领取
+    pub fn record_claim(&mut self, amount: u64) -> Result<()> {
+        self.total_rewards_claimed = self.total_rewards_claimed
+            .checked_add(amount)
+            .ok_or(LpStakingError::MathOverflow)?;
+        self.claim_count = self.claim_count.saturating_add(1);
+        Ok(())
+    }
+    
+    /// 当前仍在锁仓期内的数量（已到期返回 0）
+    pub fn active_locked_amount(&self, slot: u64) -> u64 {
+        if slot < self.lock_end_slot {
+            self.locked_amount
+        } else {
+            0
+        }
+    }
+    
+    /// 转出 amount 质押时其中属于锁仓的数量（优先转出未锁仓的部分）
+    pub fn locked_portion(&self, amount: u64, slot: u64) -> u64 {
+        let locked = self.active_locked_amount(slot);
+        let free = self.staked_amount.saturating_sub(locked);
+        amount.saturating_sub(free).min(locked)
+    }
+    
+    /// 记录推荐人（仅在仓位首次创建、尚无任何存入时生效，之后的推荐人参数会被忽略）
+    ///
+    /// 推荐人一经记录即永久有效：记录了推荐人的仓位不能通过 close_position 关闭
+    pub fn record_referrer(&mut self, referrer: Option<Pubkey>) -> Result<()> {
+        if let Some(referrer) = referrer {
+            let fresh = self.cumulative_deposited == 0 && self.first_deposit_time == 0;
+            if fresh && self.referrer == Pubkey::default() {
+                require!(
+                    referrer != self.owner && referrer != Pubkey::default(),
+                    LpStakingError::InvalidReferrer
+                );
+                self.referrer = referrer;
+                msg!("Referrer recorded: {}", referrer);
+            } else {
+                msg!("Referrer ignored: position already exists");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl UserPosition {
+    /// 测试用的当前版本账户，其余字段全部为零
+    ///
+    /// 按全零数据反序列化，新增字段时无需修改各测试模块的构造代码
+    pub fn test_default() -> Self {
+        let mut position = Self::try_from_slice(&[0u8; Self::LEN - 8]).unwrap();
+        position.version = Self::VERSION;
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_locked_portion_takes_free_stake_first() {
+        let position = UserPosition {
+            staked_amount: 100,
+            locked_amount: 60,
+            lock_end_slot: 1_000,
+            ..UserPosition::test_default()
+        };
+        
+        // 未锁仓的 40 优先转出
+        assert_eq!(position.locked_portion(40, 500), 0);
+        assert_eq!(position.locked_portion(70, 500), 30);
+        assert_eq!(position.locked_portion(100, 500), 60);
+        
+        // 锁仓到期后全部视为未锁仓
+        assert_eq!(position.locked_portion(100, 1_000), 0);
+    }
+    
+    #[test]
+    fn test_referrer_only_recorded_on_new_position() {
+        let owner = Pubkey::new_unique();
+        let referrer = Pubkey::new_unique();
+        let mut position = UserPosition {
+            owner,
+            ..UserPosition::test_default()
+        };
+        
+        // 已有存入的仓位不能补记推荐人
+        position.record_deposit(100, 1).unwrap();
+        position.record_referrer(Some(referrer)).unwrap();
+        assert_eq!(position.referrer, Pubkey::default());
+        
+        // 新仓位首次记录后不能更换
+        position.reset(owner, Pubkey::new_unique(), 255);
+        assert!(position.record_referrer(Some(owner)).is_err());
+        position.record_referrer(Some(referrer)).unwrap();
+        position.record_deposit(100, 1).unwrap();
+        position.record_referrer(Some(Pubkey::new_unique())).unwrap();
+        assert_eq!(position.referrer, referrer);
+    }
 }
@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+
+/// 投票权检查点
+/// 从 slot 开始，投票权 = bias - slope * (当前 slot - slot) / PRECISION，最低为 0
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct VeCheckpoint {
+    /// 检查点所在 slot
+    pub slot: u64,
+    /// 该 slot 的投票权（精度放大 1e12）
+    pub bias: u128,
+    /// 每 slot 衰减量（精度放大 1e12）
+    pub slope: u128,
+}
+
+impl VeCheckpoint {
+    pub const LEN: usize = 8 + 16 + 16;
+}
+
+/// 计划中的斜率变化（锁仓到期时全局斜率减少）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct SlopeChange {
+    /// 生效 slot（锁仓到期 slot）
+    pub slot: u64,
+    /// 该 slot 起全局斜率减少的数量（精度放大 1e12）
+    pub slope: u128,
+}
+
+impl SlopeChange {
+    pub const LEN: usize = 8 + 16;
+}
+
+/// 用户投票托管账户
+/// 按时间顺序追加投票权检查点，可查询任意历史 slot 的投票权
+#[account]
+pub struct VoteEscrow {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 用户钱包地址
+    pub owner: Pubkey,
+    
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 预留空间（新增定长字段从这里划分；检查点数组位于末尾，随追加 realloc 增长）
+    pub reserved: [u8; 32],
+    
+    /// 检查点（按 slot 升序）
+    pub checkpoints: Vec<VeCheckpoint>,
+}
+
+impl VoteEscrow {
+    /// 计算包含 n 个检查点时的账户大小
+    pub const fn space(checkpoints: usize) -> usize {
+        8 +  // discriminator
+        1 +  // version
+        32 + // owner
+        32 + // pool
+        1 +  // bump
+        32 + // reserved
+        4 + checkpoints * VeCheckpoint::LEN
+    }
+    
+    /// 当前账户布局版本
+    pub const VERSION: u8 = 1;
+}
+
+/// 全局投票托管账户
+/// 记录最近的全局投票权检查点及各到期 slot 的斜率变化。
+/// 斜率变化生效时写入检查点后移除，只保留未到期的条目；
+/// 检查点超过 VE_GLOBAL_PAGE_SIZE 个时，除最新检查点外全部归档到 VoteEscrowGlobalPage，
+/// 两个数组的大小都有上限，完整历史由归档分页与本账户共同给出
+#[account]
+pub struct VoteEscrowGlobal {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 已归档的检查点分页数量（下一个分页的编号）
+    pub archived_pages: u32,
+    
+    /// 预留空间（新增定长字段从这里划分；数组位于末尾，随追加 realloc 增长）
+    pub reserved: [u8; 28],
+    
+    /// 最近的全局检查点（按 slot 升序，更早的检查点位于归档分页）
+    pub checkpoints: Vec<VeCheckpoint>,
+    
+    /// 未生效的斜率变化（按 slot 升序，到期 slot 按 VE_SLOT_GRANULARITY 对齐）
+    pub slope_changes: Vec<SlopeChange>,
+}
+
+impl VoteEscrowGlobal {
+    /// 计算包含 n 个检查点、m 个斜率变化时的账户大小
+    pub const fn space(checkpoints: usize, slope_changes: usize) -> usize {
+        8 +  // discriminator
+        1 +  // version
+        32 + // pool
+        1 +  // bump
+        4 +  // archived_pages
+        28 + // reserved
+        4 + checkpoints * VeCheckpoint::LEN +
+        4 + slope_changes * SlopeChange::LEN
+    }
+    
+    /// 当前账户布局版本
+    ///
+    /// 从预留空间划分新字段时提升版本号：
+    /// * v1：检查点与斜率变化只追加
+    /// * v2：已生效的斜率变化写入检查点后移除，检查点数量有上限
+    /// * v3：较早的检查点归档到分页账户，保留完整历史
+    ///
+    /// 旧版本账户布局兼容，下次锁仓时原地升级（v2 已丢弃的检查点无法恢复）
+    pub const VERSION: u8 = 3;
+}
+
+/// 全局投票权检查点归档分页
+/// 种子为 [VOTE_ESCROW_PAGE_SEED, pool, page]，覆盖 [第一个检查点 slot, end_slot) 的历史；
+/// 期间生效的斜率变化均已写入检查点，查询时无需斜率变化
+#[account]
+pub struct VoteEscrowGlobalPage {
+    /// 账户布局版本
+    pub version: u8,
+    
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    
+    /// 分页编号
+    pub page: u32,
+    
+    /// PDA bump
+    pub bump: u8,
+    
+    /// 本页覆盖的结束 slot（不含），即之后第一个检查点的 slot
+    pub end_slot: u64,
+    
+    /// 预留空间
+    pub reserved: [u8; 32],
+    
+    /// 归档的检查点（按 slot 升序）
+    pub checkpoints: Vec<VeCheckpoint>,
+}
+
+impl VoteEscrowGlobalPage {
+    /// 计算包含 n 个检查点时的账户大小
+    pub const fn space(checkpoints: usize) -> usize {
+        8 +  // discriminator
+        1 +  // version
+        32 + // pool
+        4 +  // page
+        1 +  // bump
+        8 +  // end_slot
+        32 + // reserved
+        4 + checkpoints * VeCheckpoint::LEN
+    }
+    
+    /// 当前账户布局版本
+    pub const VERSION: u8 = 1;
+    
+    /// 本页是否覆盖 slot（第 0 页覆盖第一个检查点之前的所有 slot）
+    pub fn covers(&self, slot: u64) -> bool {
+        slot < self.end_slot
+            && (self.page == 0 || self.checkpoints.first().is_some_and(|c| c.slot <= slot))
+    }
+}
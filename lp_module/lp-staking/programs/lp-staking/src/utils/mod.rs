@@ -1,3 +1,19 @@
 pub mod reward_calculator;
+pub mod strategy_adapter;
+pub mod account_utils;
+pub mod reward_vault;
+pub mod vote_escrow;
+pub mod stake_checkpoint;
+pub mod price_oracle;
+pub mod stable_swap;
+pub mod pda;
 
 pub use reward_calculator::*;
+pub use strategy_adapter::*;
+pub use account_utils::*;
+pub use reward_vault::*;
+pub use vote_escrow::*;
+pub use stake_checkpoint::*;
+pub use price_oracle::*;
+pub use stable_swap::*;
+pub use pda::*;
@@ -0,0 +1,87 @@
+//! PDA 地址推导（供链下客户端与通过 CPI 集成的程序组装账户）
+
+use anchor_lang::prelude::*;
+use crate::constants::*;
+
+/// 池子状态地址
+pub fn find_pool_state_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_STATE_SEED], &crate::ID)
+}
+
+/// 奖励配置地址
+pub fn find_reward_config_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_CONFIG_SEED, pool.as_ref()], &crate::ID)
+}
+
+/// 奖励金库地址
+pub fn find_reward_vault_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_VAULT_SEED, pool.as_ref()], &crate::ID)
+}
+
+/// 用户仓位地址
+pub fn find_user_position_address(owner: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[USER_POSITION_SEED, owner.as_ref(), pool.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// 用户质押量历史地址
+pub fn find_user_stake_history_address(owner: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[STAKE_HISTORY_SEED, owner.as_ref(), pool.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// 未分页的旧全局质押量历史地址（已停止更新，仅用于证明 v1 快照）
+pub fn find_global_stake_history_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_HISTORY_SEED, pool.as_ref()], &crate::ID)
+}
+
+/// 全局质押量历史分页地址
+pub fn find_stake_history_page_address(pool: &Pubkey, page: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[STAKE_HISTORY_SEED, pool.as_ref(), &page.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+/// 全局投票权检查点归档分页地址
+pub fn find_vote_escrow_page_address(pool: &Pubkey, page: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VOTE_ESCROW_PAGE_SEED, pool.as_ref(), &page.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+/// 操作员授权地址
+pub fn find_operator_approval_address(
+    owner: &Pubkey,
+    pool: &Pubkey,
+    operator: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[OPERATOR_APPROVAL_SEED, owner.as_ref(), pool.as_ref(), operator.as_ref()],
+        &crate::ID,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_operator_approval_address_is_per_operator() {
+        let owner = Pubkey::new_unique();
+        let (pool, _) = find_pool_state_address();
+        let (a, _) = find_operator_approval_address(&owner, &pool, &Pubkey::new_unique());
+        let (b, _) = find_operator_approval_address(&owner, &pool, &Pubkey::new_unique());
+        assert_ne!(a, b);
+        
+        // 用户仓位与质押历史使用相同的 owner/pool 种子，但前缀不同
+        let (position, _) = find_user_position_address(&owner, &pool);
+        let (history, _) = find_user_stake_history_address(&owner, &pool);
+        assert_ne!(position, history);
+    }
+}
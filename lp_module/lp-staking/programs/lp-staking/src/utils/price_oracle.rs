@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use crate::constants::BASIS_POINTS;
+use crate::errors::LpStakingError;
+use crate::state::PoolState;
+
+/// 价格账户的 discriminator（Anchor: sha256("account:PriceFeed") 前 8 字节）
+pub const PRICE_FEED_DISCRIMINATOR: [u8; 8] = [189, 103, 252, 23, 152, 35, 243, 156];
+
+/// 价格账户布局：`[discriminator(8)][authority(32)][price(i64)][expo(i32)][publish_time(i64)]`
+const PRICE_OFFSET: usize = 8 + 32;
+const PRICE_FEED_LEN: usize = PRICE_OFFSET + 8 + 4 + 8;
+
+/// 支持的最小指数（价格精度最多 18 位小数）
+const MIN_EXPO: i32 = -18;
+
+/// 预言机报价，实际价格 = price * 10^expo
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceData {
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+/// 锚定检查结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PegStatus {
+    /// 价格在锚定区间内且未过期
+    Healthy,
+    /// 价格偏离锚定超过允许区间（偏离基点）
+    Depegged(u64),
+    /// 价格过期（距发布时间的秒数）
+    Stale(i64),
+}
+
+/// 读取价格账户
+pub fn read_price_feed(info: &AccountInfo) -> Result<PriceData> {
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() >= PRICE_FEED_LEN && data[..8] == PRICE_FEED_DISCRIMINATOR,
+        LpStakingError::InvalidPriceFeed
+    );
+    
+    let field = |offset: usize, len: usize| &data[PRICE_OFFSET + offset..PRICE_OFFSET + offset + len];
+    Ok(PriceData {
+        price: i64::from_le_bytes(field(0, 8).try_into().unwrap()),
+        expo: i32::from_le_bytes(field(8, 4).try_into().unwrap()),
+        publish_time: i64::from_le_bytes(field(12, 8).try_into().unwrap()),
+    })
+}
+
+/// 计算价格相对 $1 的偏离（基点）
+pub fn peg_deviation_bps(price: i64, expo: i32) -> Result<u64> {
+    require!((MIN_EXPO..=0).contains(&expo), LpStakingError::InvalidPriceFeed);
+    require!(price > 0, LpStakingError::InvalidPriceFeed);
+    
+    let one = 10u128.pow(expo.unsigned_abs());
+    let deviation = (price as u128).abs_diff(one)
+        .checked_mul(BASIS_POINTS as u128)
+        .ok_or(LpStakingError::MathOverflow)?
+        / one;
+    
+    Ok(u64::try_from(deviation).unwrap_or(u64::MAX))
+}
+
+/// 按池子配置的锚定区间与最大价格时效检查报价
+pub fn peg_status(pool_state: &PoolState, price: &PriceData, now: i64) -> Result<PegStatus> {
+    let age = now.saturating_sub(price.publish_time);
+    if age > pool_state.max_price_age_secs {
+        return Ok(PegStatus::Stale(age));
+    }
+    
+    let deviation = peg_deviation_bps(price.price, price.expo)?;
+    if deviation > pool_state.peg_band_bps {
+        return Ok(PegStatus::Depegged(deviation));
+    }
+    
+    Ok(PegStatus::Healthy)
+}
+
+/// 存入前的脱锚检查：池子未配置价格账户时跳过，否则要求传入该账户且报价健康
+pub fn require_pegged(
+    pool_state: &PoolState,
+    price_feed: Option<&AccountInfo>,
+    now: i64,
+) -> Result<()> {
+    if pool_state.price_feed == Pubkey::default() {
+        return Ok(());
+    }
+    
+    let price_feed = price_feed.ok_or(LpStakingError::InvalidPriceFeed)?;
+    require_keys_eq!(price_feed.key(), pool_state.price_feed, LpStakingError::InvalidPriceFeed);
+    
+    match peg_status(pool_state, &read_price_feed(price_feed)?, now)? {
+        PegStatus::Healthy => Ok(()),
+        PegStatus::Depegged(_) => err!(LpStakingError::PriceOutOfPeg),
+        PegStatus::Stale(_) => err!(LpStakingError::StalePrice),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+    
+    #[test]
+    fn test_discriminator_matches_mock_oracle() {
+        assert_eq!(PRICE_FEED_DISCRIMINATOR, mock_oracle::PriceFeed::DISCRIMINATOR);
+    }
+    
+    #[test]
+    fn test_read_price_feed_layout() {
+        let feed = mock_oracle::PriceFeed {
+            authority: Pubkey::new_unique(),
+            price: 99_950_000,
+            expo: -8,
+            publish_time: 1_700_000_000,
+        };
+        let mut data = Vec::new();
+        feed.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), PRICE_FEED_LEN);
+        
+        let key = Pubkey::new_unique();
+        let owner = mock_oracle::ID;
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert_eq!(
+            read_price_feed(&info).unwrap(),
+            PriceData { price: 99_950_000, expo: -8, publish_time: 1_700_000_000 }
+        );
+    }
+    
+    #[test]
+    fn test_peg_deviation_bps() {
+        assert_eq!(peg_deviation_bps(100_000_000, -8).unwrap(), 0);
+        assert_eq!(peg_deviation_bps(99_500_000, -8).unwrap(), 50);
+        assert_eq!(peg_deviation_bps(1_020_000, -6).unwrap(), 200);
+        assert!(peg_deviation_bps(0, -8).is_err());
+        assert!(peg_deviation_bps(1, 2).is_err());
+    }
+}
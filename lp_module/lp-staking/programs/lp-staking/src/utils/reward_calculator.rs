@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
+use crate::constants::BASIS_POINTS;
 use crate::errors::LpStakingError;
-use crate::state::{RewardConfig, PoolState, EmissionType};
+use crate::state::{RewardConfig, PoolState, EmissionType, UserPosition};
 
 /// 计算用户待领取的奖励（公共函数）
 pub fn calculate_pending_reward(
@@ -25,55 +26,253 @@ pub fn calculate_pending_reward(
     Ok(pending as u64)
 }
 
+/// 结算待领取奖励并记录取整尘埃（公共函数）
+///
+/// 与 calculate_pending_reward 相同向下取整，被舍去的部分记入 reward_config 的尘埃。
+/// 调用方随后必须将 reward_debt 重置为 staked * acc_reward_per_share
+pub fn settle_pending_reward(
+    reward_config: &mut RewardConfig,
+    user_staked: u64,
+    reward_debt: u128,
+) -> Result<u64> {
+    if user_staked == 0 {
+        return Ok(0);
+    }
+    
+    let accumulated = (user_staked as u128)
+        .checked_mul(reward_config.acc_reward_per_share)
+        .ok_or(LpStakingError::MathOverflow)?
+        .checked_sub(reward_debt)
+        .ok_or(LpStakingError::MathOverflow)?;
+    
+    reward_config.record_dust(accumulated % RewardConfig::PRECISION)?;
+    u64::try_from(accumulated / RewardConfig::PRECISION)
+        .map_err(|_| LpStakingError::MathOverflow.into())
+}
+
+/// 计算协议抽成（公共函数）
+///
+/// 协议抽成 = 排放量 * protocol_fee_bps / 10000，向下取整
+pub fn calculate_protocol_fee(emitted: u64, protocol_fee_bps: u64) -> Result<u64> {
+    let fee = (emitted as u128)
+        .checked_mul(protocol_fee_bps as u128)
+        .ok_or(LpStakingError::MathOverflow)?
+        / BASIS_POINTS as u128;
+    
+    u64::try_from(fee).map_err(|_| LpStakingError::MathOverflow.into())
+}
+
+/// 计算推荐奖励（公共函数）
+///
+/// 推荐奖励 = 被推荐人领取的奖励 * referral_bps / 10000，向下取整
+pub fn calculate_referral_reward(claimed: u64, referral_bps: u64) -> Result<u64> {
+    let reward = (claimed as u128)
+        .checked_mul(referral_bps as u128)
+        .ok_or(LpStakingError::MathOverflow)?
+        / BASIS_POINTS as u128;
+    
+    u64::try_from(reward).map_err(|_| LpStakingError::MathOverflow.into())
+}
+
+/// 计算金库可用余额下本次可支付的奖励（公共函数）
+///
+/// 为推荐奖励预留比例：amount * (1 + referral_bps / 10000) <= available，向下取整
+pub fn calculate_claimable(total_pending: u64, available: u64, referral_bps: u64) -> Result<u64> {
+    let cap = (available as u128)
+        .checked_mul(BASIS_POINTS as u128)
+        .ok_or(LpStakingError::MathOverflow)?
+        / (BASIS_POINTS as u128 + referral_bps as u128);
+    
+    Ok(total_pending.min(cap as u64))
+}
+
+/// 领取支付计划
+#[derive(Debug, PartialEq, Eq)]
+pub struct ClaimPlan {
+    /// 支付给接收方的奖励
+    pub amount: u64,
+    /// 额外支付给推荐人的奖励
+    pub referral_reward: u64,
+    /// 金库不足而保留为待领取的奖励
+    pub shortfall: u64,
+}
+
+/// 按金库可用余额拆分本次领取（公共函数）
+///
+/// 推荐奖励不超过尚未排放的资金；金库为空时支付数量为 0，全部保留为欠付
+pub fn plan_claim(
+    total_pending: u64,
+    available: u64,
+    referral_bps: u64,
+    unemitted: u64,
+) -> Result<ClaimPlan> {
+    let amount = calculate_claimable(total_pending, available, referral_bps)?;
+    let referral_reward = calculate_referral_reward(amount, referral_bps)?.min(unemitted);
+    
+    Ok(ClaimPlan {
+        amount,
+        referral_reward,
+        shortfall: total_pending - amount,
+    })
+}
+
+/// 计算忠诚度加成（公共函数）
+///
+/// 加成 = 质押量 * max_bonus_bps * min(连续质押时长, ramp_secs) / ramp_secs / 10000，
+/// 向下取整；max_bonus_bps 为 0 表示未开启
+pub fn calculate_loyalty_bonus(
+    staked: u64,
+    tenure_secs: i64,
+    max_bonus_bps: u64,
+    ramp_secs: i64,
+) -> Result<u64> {
+    if max_bonus_bps == 0 || ramp_secs <= 0 || tenure_secs <= 0 {
+        return Ok(0);
+    }
+    
+    let tenure = tenure_secs.min(ramp_secs) as u128;
+    let bonus = (staked as u128)
+        .checked_mul(max_bonus_bps as u128)
+        .and_then(|v| v.checked_mul(tenure))
+        .ok_or(LpStakingError::MathOverflow)?
+        / (ramp_secs as u128 * BASIS_POINTS as u128);
+    
+    u64::try_from(bonus).map_err(|_| LpStakingError::MathOverflow.into())
+}
+
+/// 质押量变化后调整连续质押起始时间（公共函数）
+///
+/// 连续质押时长按 min(变化前, 变化后) / max(变化前, 变化后) 缩放：
+/// 追加质押相当于按数量加权平均起始时间，部分解除质押按剩余比例缩减时长
+pub fn adjust_tenure_start(start: i64, staked_before: u64, staked_after: u64, now: i64) -> i64 {
+    if staked_before == 0 || staked_after == 0 {
+        return now;
+    }
+    
+    let tenure = now.saturating_sub(start).max(0) as u128;
+    let (small, large) = if staked_before < staked_after {
+        (staked_before, staked_after)
+    } else {
+        (staked_after, staked_before)
+    };
+    let scaled = tenure * small as u128 / large as u128;
+    
+    now - scaled as i64
+}
+
+/// 刷新仓位的忠诚度加成，并同步池子的总加成（公共函数）
+///
+/// 调用前必须已按旧权重结算奖励，调用后必须将 reward_debt 重置为新权重 * acc_reward_per_share
+pub fn refresh_loyalty_bonus(
+    pool_state: &mut PoolState,
+    reward_config: &RewardConfig,
+    user_position: &mut UserPosition,
+    now: i64,
+) -> Result<()> {
+    let bonus = calculate_loyalty_bonus(
+        user_position.staked_amount,
+        now.saturating_sub(user_position.last_stake_time),
+        reward_config.loyalty_max_bonus_bps,
+        reward_config.loyalty_ramp_secs,
+    )?;
+    
+    pool_state.total_loyalty_bonus = pool_state.total_loyalty_bonus
+        .checked_sub(user_position.loyalty_bonus)
+        .and_then(|v| v.checked_add(bonus))
+        .ok_or(LpStakingError::MathOverflow)?;
+    user_position.loyalty_bonus = bonus;
+    
+    Ok(())
+}
+
 /// 更新奖励池状态（公共函数）
+///
+/// 排放量不超过已注入但尚未排放的资金（unemitted），先按 protocol_fee_bps 计提协议奖励，
+/// 剩余部分按奖励权重（质押量加忠诚度加成）分配给质押者。每份奖励向下取整，取整尘埃记入 reward_config，保证累计支付不超过累计排放
 pub fn update_pool_reward(
     pool_state: &PoolState,
     reward_config: &mut RewardConfig,
     current_slot: u64,
 ) -> Result<()> {
-    // 如果没有质押，不需要更新
-    if pool_state.total_staked == 0 {
-        reward_config.last_update_slot = current_slot;
-        return Ok(());
-    }
+    // 旧版本配置缺少奖励记账字段，必须先通过 migrate_pool 升级，避免排放被错误截断
+    require!(
+        reward_config.version == RewardConfig::VERSION,
+        LpStakingError::UnsupportedAccountVersion
+    );
     
     // 如果区块高度没有变化，不需要更新
     if current_slot <= reward_config.last_update_slot {
         return Ok(());
     }
     
-    match reward_config.emission_type {
-        EmissionType::FixedRate => {
-            // 固定速率模式：按 slot 估算时间
-            // Solana 平均 0.4秒/slot，但为了简化使用 slot 差值
-            let slot_diff = current_slot - reward_config.last_update_slot;
-            // 每 slot 约 0.4秒，为简化计算，将 emission_rate 理解为"每slot排放量"
-            // 或者：time_elapsed ≈ slot_diff * 0.4，但这会引入浮点数
-            // 更好的方案：直接使用 slot_diff 作为时间单位
-            let time_elapsed = slot_diff as i64;
-            
-            reward_config.acc_reward_per_share = RewardCalculator::update_fixed_rate_reward(
-                reward_config.acc_reward_per_share,
-                pool_state.total_staked,
-                reward_config.emission_rate,
-                time_elapsed,
-            )?;
-        },
-        EmissionType::BlockBased => {
-            // 按块动态模式
-            reward_config.acc_reward_per_share = RewardCalculator::update_block_based_reward(
-                reward_config.acc_reward_per_share,
-                pool_state.total_staked,
-                reward_config,
-                current_slot,
-            )?;
-        },
+    let emitted = calculate_emission(reward_config, current_slot)?
+        .min(reward_config.unemitted());
+    reward_config.record_emission(emitted)?;
+    
+    // 计提协议抽成
+    let protocol_fee = calculate_protocol_fee(emitted, reward_config.protocol_fee_bps)?;
+    reward_config.protocol_fees_accrued = reward_config.protocol_fees_accrued
+        .checked_add(protocol_fee)
+        .ok_or(LpStakingError::MathOverflow)?;
+    let emitted = emitted - protocol_fee;
+    
+    // 如果没有质押，本段排放计入未分配奖励
+    let total_weight = pool_state.total_reward_weight()?;
+    if total_weight == 0 {
+        reward_config.undistributed = reward_config.undistributed
+            .checked_add(emitted)
+            .ok_or(LpStakingError::MathOverflow)?;
+        reward_config.last_update_slot = current_slot;
+        return Ok(());
+    }
+    
+    // 开启结转时，未分配奖励并入本次分配
+    let mut reward = emitted;
+    if reward_config.carry_over_undistributed && reward_config.undistributed > 0 {
+        reward = reward
+            .checked_add(reward_config.undistributed)
+            .ok_or(LpStakingError::MathOverflow)?;
+        reward_config.undistributed = 0;
     }
     
+    let scaled_reward = (reward as u128)
+        .checked_mul(RewardConfig::PRECISION)
+        .ok_or(LpStakingError::MathOverflow)?;
+    let total_weight = total_weight as u128;
+    let reward_per_share = scaled_reward / total_weight;
+    reward_config.record_dust(scaled_reward % total_weight)?;
+    
+    reward_config.acc_reward_per_share = reward_config.acc_reward_per_share
+        .checked_add(reward_per_share)
+        .ok_or(LpStakingError::MathOverflow)?;
     reward_config.last_update_slot = current_slot;
     Ok(())
 }
 
+/// 计算从 last_update_slot 到 current_slot 的排放总量（公共函数）
+///
+/// * FixedRate：emission_rate 按"每 slot 排放量"计算
+/// * BlockBased：使用当前周期的速率（简化计算，未考虑跨周期）
+pub fn calculate_emission(reward_config: &RewardConfig, current_slot: u64) -> Result<u64> {
+    if current_slot <= reward_config.last_update_slot {
+        return Ok(0);
+    }
+    
+    let slots_elapsed = current_slot - reward_config.last_update_slot;
+    let rate = match reward_config.emission_type {
+        EmissionType::FixedRate => reward_config.emission_rate,
+        EmissionType::BlockBased => RewardCalculator::calculate_block_rate(
+            reward_config.initial_block_rate,
+            reward_config.decay_factor,
+            reward_config.blocks_per_period,
+            current_slot,
+        )?,
+    };
+    
+    rate.checked_mul(slots_elapsed)
+        .ok_or(LpStakingError::MathOverflow.into())
+}
+
 /// 奖励计算器
 /// 实现固定速率和按块动态两种奖励分配机制
 pub struct RewardCalculator;
@@ -120,6 +319,9 @@ impl RewardCalculator {
     /// 
     /// # 返回
     /// 更新后的累计每份奖励
+    ///
+    /// 不受已注入资金（unemitted）约束，也不计提协议抽成；链上结算请使用 update_pool_reward
+    #[deprecated(note = "use update_pool_reward, which caps emission by funded rewards")]
     pub fn update_fixed_rate_reward(
         acc_reward_per_share: u128,
         total_staked: u64,
@@ -189,6 +391,9 @@ impl RewardCalculator {
     /// 
     /// # 返回
     /// 更新后的累计每份奖励
+    ///
+    /// 不受已注入资金（unemitted）约束，也不计提协议抽成；链上结算请使用 update_pool_reward
+    #[deprecated(note = "use update_pool_reward, which caps emission by funded rewards")]
     pub fn update_block_based_reward(
         acc_reward_per_share: u128,
         total_staked: u64,
@@ -269,4 +474,174 @@ mod tests {
         
         assert_eq!(rate, 90); // 100 * 0.9 = 90
     }
+    
+    #[test]
+    fn test_calculate_referral_reward() {
+        // 5% 推荐比例
+        assert_eq!(calculate_referral_reward(1_000_000, 500).unwrap(), 50_000);
+        // 向下取整
+        assert_eq!(calculate_referral_reward(19, 500).unwrap(), 0);
+        // 未配置推荐比例
+        assert_eq!(calculate_referral_reward(1_000_000, 0).unwrap(), 0);
+    }
+    
+    fn fixed_rate_config(emission_rate: u64, last_update_slot: u64) -> RewardConfig {
+        RewardConfig {
+            emission_rate,
+            last_update_slot,
+            total_funded: u64::MAX,
+            ..RewardConfig::test_default()
+        }
+    }
+    
+    fn pool_with_staked(total_staked: u64) -> PoolState {
+        PoolState {
+            total_staked,
+            ..PoolState::test_default()
+        }
+    }
+    
+    #[test]
+    fn test_emission_without_stakers_is_undistributed() {
+        let mut config = fixed_rate_config(10, 100);
+        update_pool_reward(&pool_with_staked(0), &mut config, 150).unwrap();
+        
+        assert_eq!(config.undistributed, 500);
+        assert_eq!(config.acc_reward_per_share, 0);
+        assert_eq!(config.last_update_slot, 150);
+        
+        // 未开启结转：恢复质押后未分配奖励保持不变
+        update_pool_reward(&pool_with_staked(1000), &mut config, 160).unwrap();
+        assert_eq!(config.undistributed, 500);
+        assert_eq!(config.acc_reward_per_share, 100 * RewardConfig::PRECISION / 1000);
+    }
+    
+    #[test]
+    fn test_undistributed_carried_over_to_next_accrual() {
+        let mut config = fixed_rate_config(10, 100);
+        config.carry_over_undistributed = true;
+        update_pool_reward(&pool_with_staked(0), &mut config, 150).unwrap();
+        update_pool_reward(&pool_with_staked(1000), &mut config, 160).unwrap();
+        
+        // 500 结转 + 100 本段排放，全部分配给质押者
+        assert_eq!(config.undistributed, 0);
+        assert_eq!(config.acc_reward_per_share, 600 * RewardConfig::PRECISION / 1000);
+    }
+    
+    #[test]
+    fn test_emission_capped_by_funding() {
+        let mut config = fixed_rate_config(10, 100);
+        config.total_funded = 250;
+        update_pool_reward(&pool_with_staked(1000), &mut config, 150).unwrap();
+        
+        // 500 的排放被限制为已注入的 250
+        assert_eq!(config.total_emitted, 250);
+        assert_eq!(config.acc_reward_per_share, 250 * RewardConfig::PRECISION / 1000);
+        
+        update_pool_reward(&pool_with_staked(1000), &mut config, 200).unwrap();
+        assert_eq!(config.total_emitted, 250);
+        assert_eq!(config.last_update_slot, 200);
+    }
+    
+    #[test]
+    fn test_rounding_favours_pool() {
+        // 3 个质押者各 1，排放 10 无法整除
+        let mut config = fixed_rate_config(10, 0);
+        update_pool_reward(&pool_with_staked(3), &mut config, 1).unwrap();
+        
+        let mut paid = 0;
+        for _ in 0..3 {
+            paid += settle_pending_reward(&mut config, 1, 0).unwrap();
+        }
+        
+        // 每人 3，剩余 1 lamport 以尘埃形式回到未分配奖励
+        assert_eq!(paid, 9);
+        assert_eq!(config.undistributed, 1);
+        assert!(config.reward_dust < RewardConfig::PRECISION);
+        assert!(paid + config.undistributed <= config.total_emitted);
+    }
+    
+    #[test]
+    fn test_calculate_claimable() {
+        // 金库充足：全额支付
+        assert_eq!(calculate_claimable(1_000, 5_000, 1_000).unwrap(), 1_000);
+        // 金库不足：支付可用部分
+        assert_eq!(calculate_claimable(1_000, 600, 0).unwrap(), 600);
+        // 为 10% 推荐奖励预留：600 / 1.1 = 545
+        let amount = calculate_claimable(1_000, 600, 1_000).unwrap();
+        assert_eq!(amount, 545);
+        assert!(amount + calculate_referral_reward(amount, 1_000).unwrap() <= 600);
+    }
+    
+    #[test]
+    fn test_protocol_fee_taken_from_emission() {
+        let mut config = fixed_rate_config(10, 100);
+        config.protocol_fee_bps = 1_000;
+        update_pool_reward(&pool_with_staked(1000), &mut config, 150).unwrap();
+        
+        // 500 的排放中 10% 计提给协议，450 分配给质押者
+        assert_eq!(config.protocol_fees_accrued, 50);
+        assert_eq!(config.total_emitted, 500);
+        assert_eq!(config.acc_reward_per_share, 450 * RewardConfig::PRECISION / 1000);
+        
+        // 无人质押时同样计提
+        update_pool_reward(&pool_with_staked(0), &mut config, 160).unwrap();
+        assert_eq!(config.protocol_fees_accrued, 60);
+        assert_eq!(config.undistributed, 90);
+    }
+    
+    #[test]
+    fn test_calculate_loyalty_bonus() {
+        // 最多 +50%，30 天达到上限
+        let ramp = 30 * 86_400;
+        assert_eq!(calculate_loyalty_bonus(1_000, 0, 5_000, ramp).unwrap(), 0);
+        assert_eq!(calculate_loyalty_bonus(1_000, ramp / 2, 5_000, ramp).unwrap(), 250);
+        assert_eq!(calculate_loyalty_bonus(1_000, ramp, 5_000, ramp).unwrap(), 500);
+        // 超过 ramp 后不再增长
+        assert_eq!(calculate_loyalty_bonus(1_000, ramp * 10, 5_000, ramp).unwrap(), 500);
+        // 未开启
+        assert_eq!(calculate_loyalty_bonus(1_000, ramp, 0, ramp).unwrap(), 0);
+    }
+    
+    #[test]
+    fn test_adjust_tenure_start() {
+        let now = 10_000;
+        // 首次质押从当前时间开始
+        assert_eq!(adjust_tenure_start(0, 0, 100, now), now);
+        // 追加同等数量：时长减半（按数量加权平均起始时间）
+        assert_eq!(adjust_tenure_start(8_000, 100, 200, now), 9_000);
+        // 解除一半：时长减半
+        assert_eq!(adjust_tenure_start(8_000, 200, 100, now), 9_000);
+        // 全部解除：重置
+        assert_eq!(adjust_tenure_start(8_000, 200, 0, now), now);
+    }
+    
+    #[test]
+    fn test_loyalty_bonus_counts_in_reward_weight() {
+        // 质押 1000，加成 500：奖励按 1500 的总权重分配
+        let mut pool = pool_with_staked(1000);
+        pool.total_loyalty_bonus = 500;
+        let mut config = fixed_rate_config(15, 0);
+        update_pool_reward(&pool, &mut config, 100).unwrap();
+        assert_eq!(config.acc_reward_per_share, 1_500 * RewardConfig::PRECISION / 1_500);
+    }
+    
+    #[test]
+    fn test_plan_claim_shortfall() {
+        // 金库充足：全额支付并附带 10% 推荐奖励
+        let plan = plan_claim(1_000, 5_000, 1_000, u64::MAX).unwrap();
+        assert_eq!(plan, ClaimPlan { amount: 1_000, referral_reward: 100, shortfall: 0 });
+        
+        // 金库不足：部分支付，剩余部分保留
+        let plan = plan_claim(1_000, 600, 0, u64::MAX).unwrap();
+        assert_eq!(plan, ClaimPlan { amount: 600, referral_reward: 0, shortfall: 400 });
+        
+        // 推荐奖励不超过尚未排放的资金
+        let plan = plan_claim(1_000, 5_000, 1_000, 30).unwrap();
+        assert_eq!(plan.referral_reward, 30);
+        
+        // 金库为空：不支付，全部保留为欠付
+        let plan = plan_claim(1_000, 0, 1_000, u64::MAX).unwrap();
+        assert_eq!(plan, ClaimPlan { amount: 0, referral_reward: 0, shortfall: 1_000 });
+    }
 }
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::constants::REWARD_VAULT_SEED;
+
+/// 奖励金库（存放 SOL 奖励的系统账户 PDA）
+pub struct RewardVault<'a, 'info> {
+    /// 金库账户
+    pub vault: &'a AccountInfo<'info>,
+    /// 关联的池子地址（PDA seed）
+    pub pool_state: Pubkey,
+    /// 金库 PDA bump
+    pub bump: u8,
+    /// 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> RewardVault<'a, 'info> {
+    /// 金库当前余额
+    pub fn balance(&self) -> u64 {
+        self.vault.lamports()
+    }
+    
+    /// 可用于支付的余额（超出免租最低余额的部分）
+    pub fn available(&self) -> Result<u64> {
+        let minimum = Rent::get()?.minimum_balance(0);
+        Ok(self.balance().saturating_sub(minimum))
+    }
+    
+    /// 使用 PDA 签名从金库转出 SOL
+    pub fn transfer_to(&self, recipient: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let seeds = &[
+            REWARD_VAULT_SEED,
+            self.pool_state.as_ref(),
+            &[self.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                self.system_program.clone(),
+                Transfer {
+                    from: self.vault.clone(),
+                    to: recipient.clone(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
@@ -0,0 +1,308 @@
+use anchor_lang::prelude::*;
+use crate::constants::BASIS_POINTS;
+use crate::errors::LpStakingError;
+
+/// 币种数量
+const N_COINS: u128 = 2;
+
+/// 牛顿迭代最大次数
+const MAX_ITERATIONS: usize = 255;
+
+/// 计算 StableSwap 不变量 D（两币种）
+///
+/// A·n^n·Σx + D = A·D·n^n + D^(n+1) / (n^n·Πx)
+pub fn compute_d(amp: u64, x: u64, y: u64) -> Result<u128> {
+    let (x, y) = (x as u128, y as u128);
+    let sum = x + y;
+    if sum == 0 {
+        return Ok(0);
+    }
+    require!(x > 0 && y > 0, LpStakingError::InvalidStableSwapParams);
+
+    let ann = (amp as u128) * N_COINS * N_COINS;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // d_p = D^3 / (4xy)
+        let d_p = mul_div(d, d, x * N_COINS)?;
+        let d_p = mul_div(d_p, d, y * N_COINS)?;
+
+        let d_prev = d;
+        let numerator = ann
+            .checked_mul(sum)
+            .and_then(|v| v.checked_add(d_p * N_COINS))
+            .ok_or(LpStakingError::MathOverflow)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)
+            .and_then(|v| v.checked_add((N_COINS + 1) * d_p))
+            .ok_or(LpStakingError::MathOverflow)?;
+        d = mul_div(numerator, d, denominator)?;
+
+        if d.abs_diff(d_prev) <= 1 {
+            return Ok(d);
+        }
+    }
+
+    err!(LpStakingError::StableSwapNotConverged)
+}
+
+/// 已知另一币种余额 x 与不变量 D，求本币种余额 y
+pub fn compute_y(amp: u64, x: u64, d: u128) -> Result<u128> {
+    require!(x > 0 && d > 0, LpStakingError::InvalidStableSwapParams);
+    let x = x as u128;
+    let ann = (amp as u128) * N_COINS * N_COINS;
+
+    // c = D^3 / (4·x·Ann)，b = x + D / Ann
+    let c = mul_div(d, d, x * N_COINS)?;
+    let c = mul_div(c, d, ann * N_COINS)?;
+    let b = x + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let numerator = y
+            .checked_mul(y)
+            .and_then(|v| v.checked_add(c))
+            .ok_or(LpStakingError::MathOverflow)?;
+        let denominator = (2 * y + b)
+            .checked_sub(d)
+            .ok_or(LpStakingError::StableSwapNotConverged)?;
+        y = numerator / denominator;
+
+        if y.abs_diff(y_prev) <= 1 {
+            return Ok(y);
+        }
+    }
+
+    err!(LpStakingError::StableSwapNotConverged)
+}
+
+/// 兑换报价：投入 amount_in 的输入币种，返回 (输出数量, 手续费)
+///
+/// 手续费从输出中扣除并留在池中，归 LP 所有；结果额外减 1 以保证取整有利于池子
+pub fn calculate_swap(
+    amp: u64,
+    fee_bps: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+) -> Result<(u64, u64)> {
+    require!(amount_in > 0, LpStakingError::InvalidAmount);
+
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_in = reserve_in
+        .checked_add(amount_in)
+        .ok_or(LpStakingError::MathOverflow)?;
+    let new_reserve_out = compute_y(amp, new_reserve_in, d)?;
+
+    let dy = (reserve_out as u128)
+        .saturating_sub(new_reserve_out)
+        .saturating_sub(1);
+    let fee = dy * fee_bps as u128 / BASIS_POINTS as u128;
+
+    Ok(((dy - fee) as u64, fee as u64))
+}
+
+/// 添加流动性：返回应铸造的 LP 数量
+///
+/// 首次添加按 D 铸造；之后按 D 的增量比例铸造，非平衡存入按偏离理想比例的部分
+/// 收取 fee_bps / 2 的手续费（与 Curve 两币种池一致），手续费留在池中归 LP 所有
+pub fn calculate_add_liquidity(
+    amp: u64,
+    fee_bps: u64,
+    reserves: [u64; 2],
+    amounts: [u64; 2],
+    lp_supply: u64,
+) -> Result<u64> {
+    let new_reserves = [
+        reserves[0].checked_add(amounts[0]).ok_or(LpStakingError::MathOverflow)?,
+        reserves[1].checked_add(amounts[1]).ok_or(LpStakingError::MathOverflow)?,
+    ];
+
+    let d0 = compute_d(amp, reserves[0], reserves[1])?;
+    let d1 = compute_d(amp, new_reserves[0], new_reserves[1])?;
+    require!(d1 > d0, LpStakingError::InvalidAmount);
+
+    if lp_supply == 0 {
+        return u64::try_from(d1).map_err(|_| LpStakingError::MathOverflow.into());
+    }
+
+    // 非平衡手续费
+    let imbalance_fee_bps = fee_bps as u128 / 2;
+    let mut balances_after_fee = [0u64; 2];
+    for i in 0..2 {
+        let ideal = mul_div(d1, reserves[i] as u128, d0)?;
+        let difference = ideal.abs_diff(new_reserves[i] as u128);
+        let fee = difference * imbalance_fee_bps / BASIS_POINTS as u128;
+        balances_after_fee[i] = (new_reserves[i] as u128 - fee) as u64;
+    }
+    let d2 = compute_d(amp, balances_after_fee[0], balances_after_fee[1])?;
+
+    let minted = mul_div(lp_supply as u128, d2.saturating_sub(d0), d0)?;
+    u64::try_from(minted).map_err(|_| LpStakingError::MathOverflow.into())
+}
+
+/// 平衡移除流动性：销毁 lp_amount，按余额比例取出两个币种，不收取手续费
+///
+/// 销毁全部 LP 时取出全部余额，池子回到空池状态
+pub fn calculate_remove_liquidity(
+    reserves: [u64; 2],
+    lp_amount: u64,
+    lp_supply: u64,
+) -> Result<[u64; 2]> {
+    require!(
+        lp_amount > 0 && lp_amount <= lp_supply,
+        LpStakingError::InvalidAmount
+    );
+
+    let mut amounts = [0u64; 2];
+    for i in 0..2 {
+        let amount = mul_div(reserves[i] as u128, lp_amount as u128, lp_supply as u128)?;
+        amounts[i] = amount as u64;
+    }
+    Ok(amounts)
+}
+
+/// 单币种移除流动性：销毁 lp_amount，全部以 index 币种取出，返回 (输出数量, 手续费)
+///
+/// 不允许单币种销毁全部 LP：另一币种余额会滞留在无人持有的池中，
+/// 且单边为 0 的余额使不变量无法计算，全部退出需使用平衡移除
+pub fn calculate_remove_liquidity_one(
+    amp: u64,
+    fee_bps: u64,
+    reserves: [u64; 2],
+    lp_amount: u64,
+    lp_supply: u64,
+    index: usize,
+) -> Result<(u64, u64)> {
+    require!(
+        lp_amount > 0 && lp_amount <= lp_supply && index < 2,
+        LpStakingError::InvalidAmount
+    );
+    require!(lp_amount < lp_supply, LpStakingError::StableSwapFullExitOneSided);
+    let other = 1 - index;
+
+    let d0 = compute_d(amp, reserves[0], reserves[1])?;
+    let d1 = d0 - mul_div(lp_amount as u128, d0, lp_supply as u128)?;
+
+    // 不收费时的输出
+    let new_y = compute_y(amp, reserves[other], d1)?;
+    let dy_without_fee = (reserves[index] as u128).saturating_sub(new_y);
+
+    // 按偏离理想比例的部分扣除手续费后重新计算
+    let imbalance_fee_bps = fee_bps as u128 / 2;
+    let mut reduced = [0u128; 2];
+    for j in 0..2 {
+        let reserve = reserves[j] as u128;
+        let expected_dx = if j == index {
+            mul_div(reserve, d1, d0)?.saturating_sub(new_y)
+        } else {
+            reserve - mul_div(reserve, d1, d0)?
+        };
+        reduced[j] = reserve - expected_dx * imbalance_fee_bps / BASIS_POINTS as u128;
+    }
+    let reduced_other = u64::try_from(reduced[other]).map_err(|_| LpStakingError::MathOverflow)?;
+    let new_y_reduced = compute_y(amp, reduced_other, d1)?;
+    let dy = reduced[index]
+        .saturating_sub(new_y_reduced)
+        .saturating_sub(1);
+
+    let fee = dy_without_fee.saturating_sub(dy);
+    Ok((dy as u64, fee as u64))
+}
+
+/// a * b / c（u128，检查溢出）
+fn mul_div(a: u128, b: u128, c: u128) -> Result<u128> {
+    require!(c > 0, LpStakingError::MathOverflow);
+    a.checked_mul(b)
+        .map(|v| v / c)
+        .ok_or(LpStakingError::MathOverflow.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMP: u64 = 100;
+
+    #[test]
+    fn test_compute_d_balanced() {
+        // 平衡池的 D 等于余额之和
+        let d = compute_d(AMP, 1_000_000_000, 1_000_000_000).unwrap();
+        assert!(d.abs_diff(2_000_000_000) <= 1);
+        assert_eq!(compute_d(AMP, 0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_compute_y_round_trip() {
+        let d = compute_d(AMP, 1_000_000_000, 3_000_000_000).unwrap();
+        let y = compute_y(AMP, 1_000_000_000, d).unwrap();
+        assert!(y.abs_diff(3_000_000_000) <= 1);
+    }
+
+    #[test]
+    fn test_swap_near_one_to_one_with_fee() {
+        let reserve = 1_000_000_000_000;
+        let (out, fee) = calculate_swap(AMP, 4, reserve, reserve, 1_000_000).unwrap();
+        // 平衡池小额兑换接近 1:1，扣除 0.04% 手续费
+        assert!(out > 999_000 && out < 1_000_000);
+        assert!(fee > 0);
+
+        // 兑换后不变量不减少（手续费留在池中）
+        let d_before = compute_d(AMP, reserve, reserve).unwrap();
+        let d_after = compute_d(AMP, reserve + 1_000_000, reserve - out).unwrap();
+        assert!(d_after >= d_before);
+    }
+
+    #[test]
+    fn test_add_liquidity_balanced_and_imbalanced() {
+        let first = calculate_add_liquidity(AMP, 4, [0, 0], [1_000_000, 1_000_000], 0).unwrap();
+        assert!(first.abs_diff(2_000_000) <= 1);
+
+        let reserves = [1_000_000, 1_000_000];
+        let balanced = calculate_add_liquidity(AMP, 4, reserves, [500_000, 500_000], first).unwrap();
+        let imbalanced = calculate_add_liquidity(AMP, 4, reserves, [1_000_000, 0], first).unwrap();
+
+        // 平衡存入按比例铸造；非平衡存入收取手续费，铸造更少
+        assert!(balanced.abs_diff(first / 2) <= 1);
+        assert!(imbalanced < balanced * 2);
+        assert!(imbalanced > 0);
+    }
+
+    #[test]
+    fn test_remove_liquidity_one() {
+        let reserves = [1_000_000_000, 1_000_000_000];
+        let supply = compute_d(AMP, reserves[0], reserves[1]).unwrap() as u64;
+
+        let (out, fee) = calculate_remove_liquidity_one(AMP, 4, reserves, supply / 100, supply, 0).unwrap();
+        // 取出约 1% 的 D，全部为币种 A
+        assert!(out < 20_000_000 && out > 19_900_000);
+        assert!(fee > 0);
+
+        // 取回不超过存入：添加后立即单币种移除不应获利
+        let minted = calculate_add_liquidity(AMP, 4, reserves, [10_000_000, 0], supply).unwrap();
+        let new_reserves = [reserves[0] + 10_000_000, reserves[1]];
+        let (back, _) = calculate_remove_liquidity_one(AMP, 4, new_reserves, minted, supply + minted, 0).unwrap();
+        assert!(back <= 10_000_000);
+    }
+
+    #[test]
+    fn test_full_exit() {
+        let reserves = [1_000_000_000, 3_000_000_000];
+        let supply = compute_d(AMP, reserves[0], reserves[1]).unwrap() as u64;
+
+        // 单币种销毁全部 LP 被拒绝，避免另一币种余额滞留
+        assert!(calculate_remove_liquidity_one(AMP, 4, reserves, supply, supply, 0).is_err());
+
+        // 平衡移除全部 LP 取出全部余额，池子可重新初始化
+        let amounts = calculate_remove_liquidity(reserves, supply, supply).unwrap();
+        assert_eq!(amounts, reserves);
+        assert_eq!(compute_d(AMP, 0, 0).unwrap(), 0);
+        let minted = calculate_add_liquidity(AMP, 4, [0, 0], [1_000_000, 1_000_000], 0).unwrap();
+        assert!(minted.abs_diff(2_000_000) <= 1);
+
+        // 部分平衡移除按比例取出，两个币种都留有余额
+        let amounts = calculate_remove_liquidity(reserves, supply - 1, supply).unwrap();
+        assert!(amounts[0] < reserves[0] && amounts[1] < reserves[1]);
+        assert!(compute_d(AMP, reserves[0] - amounts[0], reserves[1] - amounts[1]).is_ok());
+    }
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use crate::constants::STAKE_HISTORY_PAGE_SIZE;
+use crate::errors::LpStakingError;
+use crate::state::{PoolState, StakeHistory, UserPosition};
+use crate::utils::resize_account;
+
+/// 质押量历史记录器
+///
+/// 质押量发生变化的指令在处理完成后调用 `record`，按需扩容 StakeHistory 并追加检查点
+pub struct StakeHistoryRecorder<'a, 'info> {
+    /// 扩容租金的支付者
+    pub payer: &'a AccountInfo<'info>,
+    /// 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// 关联的池子地址
+    pub pool: Pubkey,
+    /// 当前 slot
+    pub slot: u64,
+}
+
+impl<'a, 'info> StakeHistoryRecorder<'a, 'info> {
+    /// 记录质押量检查点（owner 为 Pubkey::default() 表示全局历史）
+    pub fn record(
+        &self,
+        history: &mut Account<'info, StakeHistory>,
+        owner: Pubkey,
+        bump: u8,
+        amount: u64,
+    ) -> Result<()> {
+        // 首次使用时初始化（init_if_needed 创建的账户数据全为零）
+        if history.version == 0 {
+            history.version = StakeHistory::VERSION;
+            history.pool = self.pool;
+            history.owner = owner;
+            history.bump = bump;
+        }
+        
+        let required = StakeHistory::space(history.checkpoints.len() + 1);
+        let info = history.to_account_info();
+        if info.data_len() < required {
+            resize_account(&info, self.payer, self.system_program, required)?;
+        }
+        
+        history.record(self.slot, amount);
+        Ok(())
+    }
+    
+    /// 记录池子总质押量到全局历史的当前分页
+    ///
+    /// 分页写满后 pool_state.stake_history_page 指向下一页，由下一次质押变化创建，
+    /// 每个分页的大小有上限，质押指令不会反序列化无限增长的账户
+    pub fn record_total_staked(
+        &self,
+        global_history: &mut Account<'info, StakeHistory>,
+        global_history_bump: u8,
+        pool_state: &mut PoolState,
+    ) -> Result<()> {
+        self.record(
+            global_history,
+            Pubkey::default(),
+            global_history_bump,
+            pool_state.total_staked,
+        )?;
+        
+        if global_history.checkpoints.len() >= STAKE_HISTORY_PAGE_SIZE {
+            pool_state.stake_history_page = pool_state.stake_history_page
+                .checked_add(1)
+                .ok_or(LpStakingError::MathOverflow)?;
+        }
+        Ok(())
+    }
+    
+    /// 记录用户质押量与池子总质押量（stake / unstake 类指令共用）
+    pub fn record_stake_change(
+        &self,
+        user_history: &mut Account<'info, StakeHistory>,
+        user_history_bump: u8,
+        global_history: &mut Account<'info, StakeHistory>,
+        global_history_bump: u8,
+        user_position: &UserPosition,
+        pool_state: &mut PoolState,
+    ) -> Result<()> {
+        self.record(
+            user_history,
+            user_position.owner,
+            user_history_bump,
+            user_position.staked_amount,
+        )?;
+        self.record_total_staked(global_history, global_history_bump, pool_state)
+    }
+}
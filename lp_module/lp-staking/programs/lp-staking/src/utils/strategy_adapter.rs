@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{get_return_data, invoke, invoke_signed};
+use crate::errors::LpStakingError;
+
+/// 策略接口指令的 discriminator（Anchor: sha256("global:<name>") 前 8 字节）
+pub const STRATEGY_DEPOSIT_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+pub const STRATEGY_WITHDRAW_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+pub const STRATEGY_POSITION_VALUE_DISCRIMINATOR: [u8; 8] = [148, 126, 42, 231, 97, 24, 44, 18];
+
+/// 策略适配器
+///
+/// 通过 CPI 调用外部借贷程序。任何实现了以下 Anchor 指令的程序都可以作为策略接入：
+///
+/// * `deposit(amount: u64)` / `withdraw(amount: u64)`，账户顺序：
+///   `[market(w), market_vault(w), position(w), depositor(signer), token_account(w), token_program]`
+/// * `position_value() -> u64`，账户顺序：`[market, position]`，通过 return data 返回仓位价值
+///
+/// 池子 PDA 作为 depositor 签名，资金往返于 `pool_usdc_account`。
+pub struct StrategyAdapter<'a, 'info> {
+    pub strategy_program: &'a AccountInfo<'info>,
+    pub market: &'a AccountInfo<'info>,
+    pub market_vault: &'a AccountInfo<'info>,
+    pub position: &'a AccountInfo<'info>,
+    pub pool_state: &'a AccountInfo<'info>,
+    pub pool_usdc_account: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> StrategyAdapter<'a, 'info> {
+    /// 将池子资金存入外部策略
+    pub fn deposit(&self, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        self.invoke_transfer(STRATEGY_DEPOSIT_DISCRIMINATOR, amount, signer_seeds)
+    }
+
+    /// 从外部策略取回资金到池子
+    pub fn withdraw(&self, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        self.invoke_transfer(STRATEGY_WITHDRAW_DISCRIMINATOR, amount, signer_seeds)
+    }
+
+    /// 查询池子在外部策略中的仓位价值
+    pub fn position_value(&self) -> Result<u64> {
+        let ix = Instruction {
+            program_id: self.strategy_program.key(),
+            accounts: vec![
+                AccountMeta::new_readonly(self.market.key(), false),
+                AccountMeta::new_readonly(self.position.key(), false),
+            ],
+            data: STRATEGY_POSITION_VALUE_DISCRIMINATOR.to_vec(),
+        };
+
+        invoke(
+            &ix,
+            &[
+                self.market.clone(),
+                self.position.clone(),
+                self.strategy_program.clone(),
+            ],
+        )?;
+
+        let (program_id, data) = get_return_data()
+            .ok_or(LpStakingError::InvalidStrategyResponse)?;
+        require_keys_eq!(
+            program_id,
+            self.strategy_program.key(),
+            LpStakingError::InvalidStrategyResponse
+        );
+
+        u64::try_from_slice(&data).map_err(|_| LpStakingError::InvalidStrategyResponse.into())
+    }
+
+    fn invoke_transfer(
+        &self,
+        discriminator: [u8; 8],
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+
+        let ix = Instruction {
+            program_id: self.strategy_program.key(),
+            accounts: vec![
+                AccountMeta::new(self.market.key(), false),
+                AccountMeta::new(self.market_vault.key(), false),
+                AccountMeta::new(self.position.key(), false),
+                AccountMeta::new_readonly(self.pool_state.key(), true),
+                AccountMeta::new(self.pool_usdc_account.key(), false),
+                AccountMeta::new_readonly(self.token_program.key(), false),
+            ],
+            data,
+        };
+
+        invoke_signed(
+            &ix,
+            &[
+                self.market.clone(),
+                self.market_vault.clone(),
+                self.position.clone(),
+                self.pool_state.clone(),
+                self.pool_usdc_account.clone(),
+                self.token_program.clone(),
+                self.strategy_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+    
+    #[test]
+    fn test_discriminators_match_mock_lending() {
+        assert_eq!(
+            STRATEGY_DEPOSIT_DISCRIMINATOR,
+            mock_lending::instruction::Deposit::DISCRIMINATOR
+        );
+        assert_eq!(
+            STRATEGY_WITHDRAW_DISCRIMINATOR,
+            mock_lending::instruction::Withdraw::DISCRIMINATOR
+        );
+        assert_eq!(
+            STRATEGY_POSITION_VALUE_DISCRIMINATOR,
+            mock_lending::instruction::PositionValue::DISCRIMINATOR
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use crate::constants::{VE_GLOBAL_PAGE_SIZE, VE_MAX_LOCK_SLOTS, VE_SLOT_GRANULARITY};
+use crate::state::{SlopeChange, VeCheckpoint, VoteEscrowGlobal};
+
+/// 投票权精度因子（1e12）
+pub const VE_PRECISION: u128 = 1_000_000_000_000;
+
+/// 将到期 slot 向下对齐到 VE_SLOT_GRANULARITY
+pub fn align_unlock_slot(slot: u64) -> u64 {
+    slot / VE_SLOT_GRANULARITY * VE_SLOT_GRANULARITY
+}
+
+/// 计算锁仓在 slot 时的投票权检查点
+///
+/// 锁满 VE_MAX_LOCK_SLOTS 时投票权等于锁仓数量，之后线性衰减，到期为 0
+pub fn lock_point(amount: u64, end_slot: u64, slot: u64) -> VeCheckpoint {
+    if amount == 0 || slot >= end_slot {
+        return VeCheckpoint { slot, bias: 0, slope: 0 };
+    }
+    
+    let slope = (amount as u128) * VE_PRECISION / (VE_MAX_LOCK_SLOTS as u128);
+    VeCheckpoint {
+        slot,
+        bias: slope * ((end_slot - slot) as u128),
+        slope,
+    }
+}
+
+/// 按检查点的斜率衰减到 slot（不考虑期间的斜率变化）
+fn decay(point: &VeCheckpoint, slot: u64) -> u128 {
+    let elapsed = slot.saturating_sub(point.slot) as u128;
+    point.bias.saturating_sub(point.slope.saturating_mul(elapsed))
+}
+
+/// 查找 slot 时生效的最后一个检查点
+fn checkpoint_at(checkpoints: &[VeCheckpoint], slot: u64) -> Option<&VeCheckpoint> {
+    let index = checkpoints.partition_point(|c| c.slot <= slot);
+    index.checked_sub(1).map(|i| &checkpoints[i])
+}
+
+/// 用户在 slot 时的投票权（LP Token 单位）
+pub fn user_voting_power_at(checkpoints: &[VeCheckpoint], slot: u64) -> u64 {
+    checkpoint_at(checkpoints, slot)
+        .map(|point| (decay(point, slot) / VE_PRECISION) as u64)
+        .unwrap_or(0)
+}
+
+/// 将全局检查点推进到 slot，依次应用期间到期的斜率变化
+pub fn advance_global_point(
+    point: &VeCheckpoint,
+    slope_changes: &[SlopeChange],
+    slot: u64,
+) -> VeCheckpoint {
+    let mut current = *point;
+    let start = slope_changes.partition_point(|c| c.slot <= point.slot);
+    
+    for change in &slope_changes[start..] {
+        if change.slot > slot {
+            break;
+        }
+        current.bias = decay(&current, change.slot);
+        current.slope = current.slope.saturating_sub(change.slope);
+        current.slot = change.slot;
+    }
+    
+    current.bias = decay(&current, slot);
+    current.slot = slot;
+    current
+}
+
+/// 全局在 slot 时的投票权（LP Token 单位）
+pub fn total_voting_power_at(
+    checkpoints: &[VeCheckpoint],
+    slope_changes: &[SlopeChange],
+    slot: u64,
+) -> u64 {
+    checkpoint_at(checkpoints, slot)
+        .map(|point| (advance_global_point(point, slope_changes, slot).bias / VE_PRECISION) as u64)
+        .unwrap_or(0)
+}
+
+/// 写入检查点：与最后一个检查点同 slot 时覆盖，否则追加
+///
+/// 返回是否追加了新条目
+pub fn push_checkpoint(checkpoints: &mut Vec<VeCheckpoint>, point: VeCheckpoint) -> bool {
+    match checkpoints.last_mut() {
+        Some(last) if last.slot == point.slot => {
+            *last = point;
+            false
+        },
+        _ => {
+            checkpoints.push(point);
+            true
+        },
+    }
+}
+
+/// 将全局投票权推进到 slot，返回该 slot 的检查点（尚未写入）
+///
+/// 期间到期的斜率变化依次写入检查点后移除，历史查询只依赖检查点与未到期的斜率变化
+pub fn checkpoint_global(global: &mut VoteEscrowGlobal, slot: u64) -> VeCheckpoint {
+    let mut current = global.checkpoints.last().copied().unwrap_or(VeCheckpoint {
+        slot,
+        bias: 0,
+        slope: 0,
+    });
+    let applied = global.slope_changes.partition_point(|c| c.slot <= slot);
+    
+    for i in 0..applied {
+        let change = global.slope_changes[i];
+        current.bias = decay(&current, change.slot);
+        current.slope = current.slope.saturating_sub(change.slope);
+        current.slot = change.slot;
+        if change.slope > 0 {
+            push_checkpoint(&mut global.checkpoints, current);
+        }
+    }
+    global.slope_changes.drain(..applied);
+    
+    current.bias = decay(&current, slot);
+    current.slot = slot;
+    current
+}
+
+/// 将旧版本全局账户原地升级到当前版本
+///
+/// v1 账户保留了已计入检查点的斜率变化，逐个写成检查点后移除，历史查询结果不变；
+/// v2 账户的可查询最早 slot 字段与新字段重叠，清零后从第 0 页开始归档
+pub fn upgrade_global(global: &mut VoteEscrowGlobal) {
+    if global.version == 1 {
+        let last_slot = global.checkpoints.last().map(|c| c.slot).unwrap_or(0);
+        let stale = global.slope_changes.partition_point(|c| c.slot <= last_slot);
+        let changes = &global.slope_changes[..stale];
+        
+        let mut checkpoints = Vec::with_capacity(global.checkpoints.len() + stale);
+        for (i, point) in global.checkpoints.iter().enumerate() {
+            checkpoints.push(*point);
+            let next_slot = global.checkpoints.get(i + 1).map(|c| c.slot).unwrap_or(u64::MAX);
+            for change in changes {
+                if change.slot > point.slot && change.slot < next_slot && change.slope > 0 {
+                    checkpoints.push(advance_global_point(point, changes, change.slot));
+                }
+            }
+        }
+        global.checkpoints = checkpoints;
+        global.slope_changes.drain(..stale);
+    }
+    
+    global.archived_pages = 0;
+    global.reserved = [0; 28];
+    global.version = VoteEscrowGlobal::VERSION;
+}
+
+/// 检查点超过 VE_GLOBAL_PAGE_SIZE 时取出除最新检查点外的全部检查点（用于写入归档分页）
+pub fn take_archived_checkpoints(global: &mut VoteEscrowGlobal) -> Option<Vec<VeCheckpoint>> {
+    let len = global.checkpoints.len();
+    if len <= VE_GLOBAL_PAGE_SIZE {
+        return None;
+    }
+    Some(global.checkpoints.drain(..len - 1).collect())
+}
+
+/// 在 slot 处增加计划中的斜率变化（保持按 slot 升序）
+pub fn add_slope_change(slope_changes: &mut Vec<SlopeChange>, slot: u64, slope: u128) {
+    match slope_changes.binary_search_by_key(&slot, |c| c.slot) {
+        Ok(i) => slope_changes[i].slope = slope_changes[i].slope.saturating_add(slope),
+        Err(i) => slope_changes.insert(i, SlopeChange { slot, slope }),
+    }
+}
+
+/// 撤销 slot 处计划中的斜率变化（锁仓延期或增加数量时）
+pub fn remove_slope_change(slope_changes: &mut [SlopeChange], slot: u64, slope: u128) {
+    if let Ok(i) = slope_changes.binary_search_by_key(&slot, |c| c.slot) {
+        slope_changes[i].slope = slope_changes[i].slope.saturating_sub(slope);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const LP: u64 = 1_000_000_000;
+    
+    #[test]
+    fn test_max_lock_power_equals_amount_and_decays_linearly() {
+        let end = VE_MAX_LOCK_SLOTS;
+        let point = lock_point(100 * LP, end, 0);
+        let checkpoints = vec![point];
+        
+        // 斜率向下取整，误差不超过 1 个最小单位
+        assert!(user_voting_power_at(&checkpoints, 0).abs_diff(100 * LP) <= 1);
+        assert!(user_voting_power_at(&checkpoints, end / 2).abs_diff(50 * LP) <= 1);
+        assert_eq!(user_voting_power_at(&checkpoints, end), 0);
+        assert_eq!(user_voting_power_at(&checkpoints, end + 1), 0);
+    }
+    
+    #[test]
+    fn test_power_before_first_checkpoint_is_zero() {
+        let checkpoints = vec![lock_point(LP, VE_MAX_LOCK_SLOTS + 100, 100)];
+        assert_eq!(user_voting_power_at(&checkpoints, 99), 0);
+        assert!(user_voting_power_at(&checkpoints, 100) > 0);
+    }
+    
+    #[test]
+    fn test_global_power_applies_slope_changes() {
+        // 两个锁仓：A 在 g 到期，B 在 4g 到期
+        let g = VE_SLOT_GRANULARITY;
+        let a = lock_point(10 * LP, g, 0);
+        let b = lock_point(10 * LP, 4 * g, 0);
+        
+        let mut checkpoints = Vec::new();
+        push_checkpoint(&mut checkpoints, VeCheckpoint {
+            slot: 0,
+            bias: a.bias + b.bias,
+            slope: a.slope + b.slope,
+        });
+        let mut slope_changes = Vec::new();
+        add_slope_change(&mut slope_changes, 4 * g, b.slope);
+        add_slope_change(&mut slope_changes, g, a.slope);
+        assert_eq!(slope_changes[0].slot, g);
+        
+        // 任意历史 slot 的全局投票权应等于各用户之和
+        for slot in [0, g / 2, g, 2 * g, 4 * g, 5 * g] {
+            let expected = user_voting_power_at(&[a], slot) + user_voting_power_at(&[b], slot);
+            let total = total_voting_power_at(&checkpoints, &slope_changes, slot);
+            assert!(expected.abs_diff(total) <= 1, "slot {}: {} != {}", slot, total, expected);
+        }
+        assert_eq!(total_voting_power_at(&checkpoints, &slope_changes, 4 * g), 0);
+    }
+    
+    #[test]
+    fn test_extend_lock_moves_slope_change() {
+        let g = VE_SLOT_GRANULARITY;
+        let old = lock_point(LP, g, 0);
+        let mut slope_changes = Vec::new();
+        add_slope_change(&mut slope_changes, g, old.slope);
+        
+        remove_slope_change(&mut slope_changes, g, old.slope);
+        add_slope_change(&mut slope_changes, 2 * g, old.slope);
+        
+        assert_eq!(slope_changes[0].slope, 0);
+        assert_eq!(slope_changes[1], SlopeChange { slot: 2 * g, slope: old.slope });
+    }
+    
+    #[test]
+    fn test_push_checkpoint_overwrites_same_slot() {
+        let mut checkpoints = Vec::new();
+        assert!(push_checkpoint(&mut checkpoints, VeCheckpoint { slot: 5, bias: 1, slope: 0 }));
+        assert!(!push_checkpoint(&mut checkpoints, VeCheckpoint { slot: 5, bias: 2, slope: 0 }));
+        assert!(push_checkpoint(&mut checkpoints, VeCheckpoint { slot: 6, bias: 3, slope: 0 }));
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].bias, 2);
+    }
+    
+    #[test]
+    fn test_align_unlock_slot() {
+        assert_eq!(align_unlock_slot(VE_SLOT_GRANULARITY * 3 + 7), VE_SLOT_GRANULARITY * 3);
+        assert_eq!(align_unlock_slot(VE_SLOT_GRANULARITY - 1), 0);
+    }
+    
+    fn global() -> VoteEscrowGlobal {
+        VoteEscrowGlobal {
+            version: VoteEscrowGlobal::VERSION,
+            pool: Default::default(),
+            bump: 0,
+            archived_pages: 0,
+            reserved: [0; 28],
+            checkpoints: Vec::new(),
+            slope_changes: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn test_checkpoint_global_prunes_applied_slope_changes() {
+        // 两个锁仓：A 在 g 到期，B 在 4g 到期
+        let g = VE_SLOT_GRANULARITY;
+        let a = lock_point(10 * LP, g, 0);
+        let b = lock_point(10 * LP, 4 * g, 0);
+        
+        let mut global = global();
+        let mut point = checkpoint_global(&mut global, 0);
+        point.bias += a.bias + b.bias;
+        point.slope += a.slope + b.slope;
+        push_checkpoint(&mut global.checkpoints, point);
+        add_slope_change(&mut global.slope_changes, g, a.slope);
+        add_slope_change(&mut global.slope_changes, 4 * g, b.slope);
+        
+        // 推进到 2g：A 的斜率变化写入检查点后移除，只保留未到期的 B
+        let point = checkpoint_global(&mut global, 2 * g);
+        push_checkpoint(&mut global.checkpoints, point);
+        assert_eq!(global.slope_changes, vec![SlopeChange { slot: 4 * g, slope: b.slope }]);
+        assert_eq!(global.checkpoints.len(), 3);
+        assert_eq!(global.checkpoints[1].slot, g);
+        
+        // 移除已生效的斜率变化后，历史查询结果不变
+        for slot in [0, g / 2, g, 3 * g / 2, 2 * g, 3 * g, 4 * g, 5 * g] {
+            let expected = user_voting_power_at(&[a], slot) + user_voting_power_at(&[b], slot);
+            let total = total_voting_power_at(&global.checkpoints, &global.slope_changes, slot);
+            assert!(expected.abs_diff(total) <= 1, "slot {}: {} != {}", slot, total, expected);
+        }
+    }
+    
+    #[test]
+    fn test_archived_checkpoints_keep_latest() {
+        let mut global = global();
+        for slot in 0..VE_GLOBAL_PAGE_SIZE as u64 {
+            push_checkpoint(&mut global.checkpoints, VeCheckpoint { slot, bias: 1, slope: 0 });
+        }
+        assert!(take_archived_checkpoints(&mut global).is_none());
+        
+        let last = VE_GLOBAL_PAGE_SIZE as u64;
+        push_checkpoint(&mut global.checkpoints, VeCheckpoint { slot: last, bias: 1, slope: 0 });
+        let archived = take_archived_checkpoints(&mut global).unwrap();
+        assert_eq!(archived.len(), VE_GLOBAL_PAGE_SIZE);
+        assert_eq!(archived[0].slot, 0);
+        assert_eq!(global.checkpoints, vec![VeCheckpoint { slot: last, bias: 1, slope: 0 }]);
+    }
+    
+    #[test]
+    fn test_upgrade_v1_materializes_applied_slope_changes() {
+        // v1：检查点在 0 与 2g，g 处的斜率变化已计入 2g 的检查点但未移除
+        let g = VE_SLOT_GRANULARITY;
+        let a = lock_point(10 * LP, g, 0);
+        let b = lock_point(10 * LP, 4 * g, 0);
+        let mut slope_changes = Vec::new();
+        add_slope_change(&mut slope_changes, g, a.slope);
+        add_slope_change(&mut slope_changes, 4 * g, b.slope);
+        let start = VeCheckpoint { slot: 0, bias: a.bias + b.bias, slope: a.slope + b.slope };
+        let checkpoints = vec![start, advance_global_point(&start, &slope_changes, 2 * g)];
+        
+        let mut global = VoteEscrowGlobal {
+            version: 1,
+            checkpoints: checkpoints.clone(),
+            slope_changes: slope_changes.clone(),
+            ..global()
+        };
+        upgrade_global(&mut global);
+        assert_eq!(global.version, VoteEscrowGlobal::VERSION);
+        assert_eq!(global.slope_changes, vec![SlopeChange { slot: 4 * g, slope: b.slope }]);
+        assert_eq!(global.checkpoints.len(), 3);
+        
+        // 升级后历史查询结果不变
+        for slot in [0, g / 2, g, 3 * g / 2, 2 * g, 3 * g, 5 * g] {
+            let before = total_voting_power_at(&checkpoints, &slope_changes, slot);
+            let after = total_voting_power_at(&global.checkpoints, &global.slope_changes, slot);
+            assert_eq!(before, after, "slot {}", slot);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+declare_id!("8W7uetvnVF9rUYSzyxWML7sKBYynauNZfWTXBsBJjhba");
+
+/// PDA Seeds
+pub const MARKET_SEED: &[u8] = b"market";
+pub const MARKET_VAULT_SEED: &[u8] = b"market_vault";
+pub const POSITION_SEED: &[u8] = b"position";
+
+/// 模拟借贷程序（仅用于本地测试）
+///
+/// 实现 lp-staking 策略适配器约定的接口：
+/// `deposit(amount)` / `withdraw(amount)` / `position_value() -> u64`
+#[program]
+pub mod mock_lending {
+    use super::*;
+
+    /// 创建借贷市场及其资金金库
+    pub fn initialize_market(ctx: Context<InitializeMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        market.mint = ctx.accounts.mint.key();
+        market.vault = ctx.accounts.vault.key();
+        market.total_deposits = 0;
+        market.bump = ctx.bumps.market;
+        market.vault_bump = ctx.bumps.vault;
+        Ok(())
+    }
+
+    /// 为指定所有者（可以是其他程序的 PDA）开设仓位
+    pub fn open_position(ctx: Context<OpenPosition>, owner: Pubkey) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        position.market = ctx.accounts.market.key();
+        position.owner = owner;
+        position.amount = 0;
+        position.bump = ctx.bumps.position;
+        Ok(())
+    }
+
+    /// 存入资金
+    pub fn deposit(ctx: Context<Lend>, amount: u64) -> Result<()> {
+        require!(amount > 0, MockLendingError::InvalidAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let position = &mut ctx.accounts.position;
+        position.amount = position.amount
+            .checked_add(amount)
+            .ok_or(MockLendingError::MathOverflow)?;
+        let market = &mut ctx.accounts.market;
+        market.total_deposits = market.total_deposits
+            .checked_add(amount)
+            .ok_or(MockLendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// 取回资金（本金 + 已计入的利息）
+    pub fn withdraw(ctx: Context<Lend>, amount: u64) -> Result<()> {
+        require!(amount > 0, MockLendingError::InvalidAmount);
+        require!(
+            ctx.accounts.position.amount >= amount,
+            MockLendingError::InsufficientFunds
+        );
+
+        let market = &ctx.accounts.market;
+        let mint_key = market.mint;
+        let seeds = &[MARKET_SEED, mint_key.as_ref(), &[market.bump]];
+        let signer = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: ctx.accounts.market.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+
+        let position = &mut ctx.accounts.position;
+        position.amount -= amount;
+        let market = &mut ctx.accounts.market;
+        market.total_deposits = market.total_deposits.saturating_sub(amount);
+        Ok(())
+    }
+
+    /// 查询仓位当前价值（通过 return data 返回）
+    pub fn position_value(ctx: Context<PositionValue>) -> Result<u64> {
+        Ok(ctx.accounts.position.amount)
+    }
+
+    /// 模拟利息：捐赠者向金库转入代币并计入指定仓位
+    pub fn accrue_interest(ctx: Context<AccrueInterest>, amount: u64) -> Result<()> {
+        require!(amount > 0, MockLendingError::InvalidAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.donor_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.donor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let position = &mut ctx.accounts.position;
+        position.amount = position.amount
+            .checked_add(amount)
+            .ok_or(MockLendingError::MathOverflow)?;
+        let market = &mut ctx.accounts.market;
+        market.total_deposits = market.total_deposits
+            .checked_add(amount)
+            .ok_or(MockLendingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// 模拟坏账：直接削减仓位价值（资金留在金库中）
+    pub fn apply_loss(ctx: Context<ApplyLoss>, amount: u64) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        position.amount = position.amount.saturating_sub(amount);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Market {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub total_deposits: u64,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+impl Market {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1 + 1;
+}
+
+#[account]
+pub struct LendingPosition {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl LendingPosition {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct InitializeMarket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = Market::LEN,
+        seeds = [MARKET_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint,
+        token::authority = market,
+        seeds = [MARKET_VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct OpenPosition<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = LendingPosition::LEN,
+        seeds = [POSITION_SEED, market.key().as_ref(), owner.as_ref()],
+        bump
+    )]
+    pub position: Account<'info, LendingPosition>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// deposit / withdraw 共用的账户顺序，与 lp-staking 策略接口一致
+#[derive(Accounts)]
+pub struct Lend<'info> {
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.mint.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, address = market.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = market,
+        constraint = position.owner == depositor.key() @ MockLendingError::Unauthorized,
+    )]
+    pub position: Account<'info, LendingPosition>,
+
+    pub depositor: Signer<'info>,
+
+    #[account(mut, token::mint = market.mint)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct PositionValue<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(has_one = market)]
+    pub position: Account<'info, LendingPosition>,
+}
+
+#[derive(Accounts)]
+pub struct AccrueInterest<'info> {
+    pub donor: Signer<'info>,
+
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, address = market.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut, has_one = market)]
+    pub position: Account<'info, LendingPosition>,
+
+    #[account(mut, token::mint = market.mint, token::authority = donor)]
+    pub donor_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyLoss<'info> {
+    pub signer: Signer<'info>,
+
+    pub market: Account<'info, Market>,
+
+    #[account(mut, has_one = market)]
+    pub position: Account<'info, LendingPosition>,
+}
+
+#[error_code]
+pub enum MockLendingError {
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("Insufficient funds in position")]
+    InsufficientFunds,
+
+    #[msg("Unauthorized")]
+    Unauthorized,
+
+    #[msg("Math overflow")]
+    MathOverflow,
+}
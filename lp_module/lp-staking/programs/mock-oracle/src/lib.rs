@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+declare_id!("BCjFWhPfuc6KVnKxTxzcznEdM2SdPcGssfaBW7Yte2y7");
+
+/// 模拟价格预言机（仅用于本地测试）
+///
+/// 价格账户布局与 lp-staking 脱锚保护读取的格式一致：
+/// `[discriminator(8)][authority(32)][price(i64)][expo(i32)][publish_time(i64)]`，
+/// 实际价格 = price * 10^expo
+#[program]
+pub mod mock_oracle {
+    use super::*;
+
+    /// 创建价格账户（由 feed keypair 签名）
+    pub fn initialize_feed(ctx: Context<InitializeFeed>, price: i64, expo: i32) -> Result<()> {
+        let feed = &mut ctx.accounts.feed;
+        feed.authority = ctx.accounts.authority.key();
+        feed.price = price;
+        feed.expo = expo;
+        feed.publish_time = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// 更新价格；`publish_time` 为空时使用当前时间（测试中可传入旧时间模拟过期）
+    pub fn set_price(
+        ctx: Context<SetPrice>,
+        price: i64,
+        expo: i32,
+        publish_time: Option<i64>,
+    ) -> Result<()> {
+        let feed = &mut ctx.accounts.feed;
+        feed.price = price;
+        feed.expo = expo;
+        feed.publish_time = match publish_time {
+            Some(ts) => ts,
+            None => Clock::get()?.unix_timestamp,
+        };
+        Ok(())
+    }
+}
+
+#[account]
+pub struct PriceFeed {
+    pub authority: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl PriceFeed {
+    pub const LEN: usize = 8 + 32 + 8 + 4 + 8;
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeed<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(init, payer = authority, space = PriceFeed::LEN)]
+    pub feed: Account<'info, PriceFeed>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority)]
+    pub feed: Account<'info, PriceFeed>,
+}
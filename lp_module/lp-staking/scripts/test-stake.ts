@@ -47,7 +47,7 @@ async function main() {
     }
 
     // 执行质押
-    const tx = await program.methods.stake(stakeAmount).rpc();
+    const tx = await program.methods.stake(stakeAmount, null).rpc();
 
     console.log("\n✅ 质押成功!");
     console.log("   交易签名:", tx);
@@ -1,11 +1,15 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { LpStaking } from "../target/types/lp_staking";
+import { MockLending } from "../target/types/mock_lending";
+import { MockOracle } from "../target/types/mock_oracle";
 import { 
   TOKEN_PROGRAM_ID,
   createMint,
   getOrCreateAssociatedTokenAccount,
+  getAssociatedTokenAddressSync,
   mintTo,
+  transfer,
 } from "@solana/spl-token";
 import { assert } from "chai";
 import { PublicKey, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
@@ -15,11 +19,18 @@ function sleep(ms: number) {
   return new Promise(resolve => setTimeout(resolve, ms));
 }
 
+// 交易截止时间：当前时间 + 60 秒
+function deadline() {
+  return new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+}
+
 describe("lp-staking", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.LpStaking as Program<LpStaking>;
+  const lendingProgram = anchor.workspace.MockLending as Program<MockLending>;
+  const oracleProgram = anchor.workspace.MockOracle as Program<MockOracle>;
   const payer = provider.wallet as anchor.Wallet;
 
   let wrappedUsdcMint: anchor.web3.PublicKey;
@@ -123,10 +134,54 @@ describe("lp-staking", () => {
     );
     assert.equal(poolStateAccount.totalDeposited.toNumber(), 0);
     assert.equal(poolStateAccount.totalLpSupply.toNumber(), 0);
+    assert.equal(poolStateAccount.version, 3);
+
+    // 奖励金库创建时即达到免租最低余额
+    const vaultRent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const vaultBalance = await provider.connection.getBalance(rewardVault);
+    assert.isAtLeast(vaultBalance, vaultRent);
 
     console.log("✓ 池子初始化成功\n");
   });
 
+  it("迁移: 当前版本账户重复迁移应为空操作", async () => {
+    console.log("=== 测试: migratePool（幂等）===");
+
+    const configBefore = await program.account.rewardConfig.fetch(rewardConfig);
+    const poolBefore = await program.account.poolState.fetch(poolState);
+
+    await program.methods
+      .migratePool(
+        { fixedRate: {} },
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        {
+          activePositions: new anchor.BN(0),
+          stakerCount: new anchor.BN(0),
+          rewardsOwed: new anchor.BN(0),
+        }
+      )
+      .accounts({ authority: payer.publicKey })
+      .rpc();
+
+    const configAfter = await program.account.rewardConfig.fetch(rewardConfig);
+    assert.equal(configAfter.version, 2);
+    assert.equal(
+      configAfter.emissionRate.toString(),
+      configBefore.emissionRate.toString(),
+      "已存在的 RewardConfig 不应被迁移参数覆盖"
+    );
+    const poolAfter = await program.account.poolState.fetch(poolState);
+    assert.equal(
+      poolAfter.activePositions.toString(),
+      poolBefore.activePositions.toString(),
+      "当前版本池子的仓位计数不应被迁移参数覆盖"
+    );
+    console.log("✓ 迁移幂等\n");
+  });
+
   it("首次存入 USDC（1:1 比例）", async () => {
     console.log("=== 测试: 首次存入 10,000 USDC ===");
 
@@ -160,7 +215,7 @@ describe("lp-staking", () => {
     const depositAmount = new anchor.BN(10_000_000_000);
 
     const tx = await program.methods
-      .deposit(depositAmount)
+      .deposit(depositAmount, depositAmount, deadline(), null)
       .accounts({
         user: payer.publicKey,
         userUsdcAccount: userUsdcAta.address,
@@ -182,6 +237,17 @@ describe("lp-staking", () => {
       depositAmount.toString()
     );
 
+    // 仓位记录累计存入量与首次存入时间
+    const position = await program.account.userPosition.fetch(userPosition);
+    assert.equal(position.version, 2);
+    assert.equal(position.cumulativeDeposited.toString(), depositAmount.toString());
+    assert.isTrue(position.firstDepositTime.gtn(0));
+
+    // 当前版本仓位重复迁移应为空操作
+    await program.methods.migratePosition().accounts({ user: payer.publicKey }).rpc();
+    const migrated = await program.account.userPosition.fetch(userPosition);
+    assert.equal(migrated.cumulativeDeposited.toString(), depositAmount.toString());
+
     const userLpBalance = await provider.connection.getTokenAccountBalance(userLpAta.address);
     console.log("✓ 用户获得 LP Token:", userLpBalance.value.uiAmount);
     console.log("✓ 首次存入成功（1:1 比例）\n");
@@ -208,7 +274,7 @@ describe("lp-staking", () => {
     const poolStateBefore = await program.account.poolState.fetch(poolState);
 
     const tx = await program.methods
-      .deposit(depositAmount)
+      .deposit(depositAmount, new anchor.BN(0), deadline(), null)
       .accounts({
         user: payer.publicKey,
         userUsdcAccount: userUsdcAta.address,
@@ -257,7 +323,7 @@ describe("lp-staking", () => {
     const poolStateBefore = await program.account.poolState.fetch(poolState);
 
     const tx = await program.methods
-      .withdraw(withdrawAmount)
+      .withdraw(withdrawAmount, new anchor.BN(0), deadline())
       .accounts({
         user: payer.publicKey,
         userUsdcAccount: userUsdcAta.address,
@@ -297,7 +363,7 @@ describe("lp-staking", () => {
 
     try {
       await program.methods
-        .deposit(tooSmallAmount)
+        .deposit(tooSmallAmount, new anchor.BN(0), deadline(), null)
         .accounts({
           user: payer.publicKey,
           userUsdcAccount: userUsdcAta.address,
@@ -314,6 +380,249 @@ describe("lp-staking", () => {
     }
   });
 
+  it("边界测试: 滑点保护与交易截止时间", async () => {
+    console.log("=== 测试: 滑点与截止时间（应失败）===");
+
+    const userUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      payer.publicKey
+    );
+
+    const userLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      payer.publicKey
+    );
+
+    const depositAccounts = {
+      user: payer.publicKey,
+      userUsdcAccount: userUsdcAta.address,
+      poolUsdcAccount: poolUsdcAccount,
+      lpTokenMint: lpTokenMint,
+      userLpAccount: userLpAta.address,
+    };
+    const amount = new anchor.BN(1_000_000_000);
+
+    // 要求的最少 LP 远高于实际可得
+    try {
+      await program.methods
+        .deposit(amount, amount.muln(10), deadline(), null)
+        .accounts(depositAccounts)
+        .rpc();
+      assert.fail("应该抛出 SlippageExceeded 错误");
+    } catch (err: any) {
+      assert.include(err.toString(), "SlippageExceeded");
+      console.log("✓ 正确拒绝了超出滑点的存款");
+    }
+
+    // 截止时间已过
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(1_000_000), new anchor.BN(0), new anchor.BN(1))
+        .accounts(depositAccounts)
+        .rpc();
+      assert.fail("应该抛出 TransactionExpired 错误");
+    } catch (err: any) {
+      assert.include(err.toString(), "TransactionExpired");
+      console.log("✓ 正确拒绝了过期的提取\n");
+    }
+  });
+
+  it("熔断: 滚动窗口提取限额与守护者重置", async () => {
+    console.log("=== 测试: 提取限额 ===");
+
+    const userUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      payer.publicKey
+    );
+    const userLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      payer.publicKey
+    );
+    const withdrawAccounts = {
+      user: payer.publicKey,
+      userUsdcAccount: userUsdcAta.address,
+      poolUsdcAccount: poolUsdcAccount,
+      lpTokenMint: lpTokenMint,
+      userLpAccount: userLpAta.address,
+    };
+
+    const guardian = anchor.web3.Keypair.generate();
+    await program.methods.setGuardian(guardian.publicKey).rpc();
+
+    // 守护者设置每小时最多提取 TVL 的 1%
+    await program.methods
+      .setOutflowLimit(new anchor.BN(100), new anchor.BN(3600))
+      .accounts({ admin: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+
+    const pool = await program.account.poolState.fetch(poolState);
+    const withinLimit = pool.totalLpSupply.divn(200);
+    await program.methods
+      .withdraw(withinLimit, new anchor.BN(0), deadline())
+      .accounts(withdrawAccounts)
+      .rpc();
+
+    try {
+      await program.methods
+        .withdraw(pool.totalLpSupply.divn(50), new anchor.BN(0), deadline())
+        .accounts(withdrawAccounts)
+        .rpc();
+      assert.fail("超过窗口限额应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "OutflowLimitExceeded");
+    }
+
+    // 普通用户不能调整限额
+    const stranger = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .resetOutflowWindow()
+        .accounts({ admin: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("非管理员/守护者应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "Unauthorized");
+    }
+
+    // 守护者关闭熔断
+    await program.methods
+      .setOutflowLimit(new anchor.BN(0), new anchor.BN(0))
+      .accounts({ admin: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+    const poolAfter = await program.account.poolState.fetch(poolState);
+    assert.equal(poolAfter.outflowLimitBps.toNumber(), 0);
+
+    console.log("✅ 提取限额断言通过\n");
+  });
+
+  it("脱锚保护: 价格偏离或过期时拒绝存入并自动暂停", async () => {
+    console.log("=== 测试: 脱锚保护 ===");
+
+    const userUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      payer.publicKey
+    );
+    const userLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      payer.publicKey
+    );
+    const depositAccounts = {
+      user: payer.publicKey,
+      userUsdcAccount: userUsdcAta.address,
+      poolUsdcAccount: poolUsdcAccount,
+      lpTokenMint: lpTokenMint,
+      userLpAccount: userLpAta.address,
+    };
+    const amount = new anchor.BN(1_000_000);
+
+    // 1. 创建价格账户（$1.0000，8 位小数）并启用保护：偏离 1%、报价 60 秒内有效
+    const feed = anchor.web3.Keypair.generate();
+    await oracleProgram.methods
+      .initializeFeed(new anchor.BN(100_000_000), -8)
+      .accounts({ authority: payer.publicKey, feed: feed.publicKey })
+      .signers([feed])
+      .rpc();
+    await program.methods
+      .configurePriceGuard(new anchor.BN(100), new anchor.BN(60))
+      .accounts({ priceFeed: feed.publicKey })
+      .rpc();
+
+    // 2. 启用后必须传入价格账户
+    try {
+      await program.methods
+        .deposit(amount, new anchor.BN(0), deadline(), null)
+        .accounts(depositAccounts)
+        .rpc();
+      assert.fail("未传入价格账户应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidPriceFeed");
+    }
+    await program.methods
+      .deposit(amount, new anchor.BN(0), deadline(), null)
+      .accounts({ ...depositAccounts, priceFeed: feed.publicKey })
+      .rpc();
+
+    // 3. 报价过期时拒绝存入
+    const staleTime = new anchor.BN(Math.floor(Date.now() / 1000) - 3600);
+    await oracleProgram.methods
+      .setPrice(new anchor.BN(100_000_000), -8, staleTime)
+      .accounts({ authority: payer.publicKey, feed: feed.publicKey })
+      .rpc();
+    try {
+      await program.methods
+        .deposit(amount, new anchor.BN(0), deadline(), null)
+        .accounts({ ...depositAccounts, priceFeed: feed.publicKey })
+        .rpc();
+      assert.fail("报价过期应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "StalePrice");
+    }
+
+    // 4. 脱锚到 $0.97：存入被拒绝，keeper 调用 checkPeg 自动暂停
+    await oracleProgram.methods
+      .setPrice(new anchor.BN(97_000_000), -8, null)
+      .accounts({ authority: payer.publicKey, feed: feed.publicKey })
+      .rpc();
+    try {
+      await program.methods
+        .deposit(amount, new anchor.BN(0), deadline(), null)
+        .accounts({ ...depositAccounts, priceFeed: feed.publicKey })
+        .rpc();
+      assert.fail("脱锚时应拒绝存入");
+    } catch (err: any) {
+      assert.include(err.toString(), "PriceOutOfPeg");
+    }
+
+    const keeper = anchor.web3.Keypair.generate();
+    await program.methods
+      .checkPeg()
+      .accounts({ caller: keeper.publicKey, priceFeed: feed.publicKey })
+      .signers([keeper])
+      .rpc();
+    assert.isTrue((await program.account.poolState.fetch(poolState)).paused);
+
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(1_000), new anchor.BN(0), deadline())
+        .accounts(depositAccounts)
+        .rpc();
+      assert.fail("暂停期间应拒绝提取");
+    } catch (err: any) {
+      assert.include(err.toString(), "PoolPaused");
+    }
+
+    // 5. 恢复锚定后由管理员解除暂停，并关闭保护
+    await oracleProgram.methods
+      .setPrice(new anchor.BN(100_000_000), -8, null)
+      .accounts({ authority: payer.publicKey, feed: feed.publicKey })
+      .rpc();
+    await program.methods.setPaused(false).accounts({ admin: payer.publicKey }).rpc();
+    await program.methods
+      .configurePriceGuard(new anchor.BN(0), new anchor.BN(0))
+      .accounts({ priceFeed: null })
+      .rpc();
+
+    const pool = await program.account.poolState.fetch(poolState);
+    assert.isFalse(pool.paused);
+    assert.equal(pool.priceFeed.toString(), PublicKey.default.toString());
+    console.log("✅ 脱锚保护断言通过\n");
+  });
+
   it("Phase 3 测试", async () => {
     // 2. 获取池子状态并断言已初始化
     const poolStateAccount = await program.account.poolState.fetch(poolState);
@@ -337,14 +646,12 @@ describe("lp-staking", () => {
     const vaultBalance = await provider.connection.getBalance(rewardVault);
     if (vaultBalance < 5 * LAMPORTS_PER_SOL) {
       console.log("⏳ 充值 Reward Vault (10 SOL)...");
-      const transferIx = SystemProgram.transfer({
-        fromPubkey: payer.publicKey,
-        toPubkey: rewardVault,
-        lamports: 10 * LAMPORTS_PER_SOL,
-      });
-      const transferTx = new anchor.web3.Transaction().add(transferIx);
-      await provider.sendAndConfirm(transferTx);
+      await program.methods
+        .fundRewards(new anchor.BN(10 * LAMPORTS_PER_SOL))
+        .rpc();
       const newVaultBalance = await provider.connection.getBalance(rewardVault);
+      const config = await program.account.rewardConfig.fetch(rewardConfig);
+      assert.isAtLeast(config.totalFunded.toNumber(), 10 * LAMPORTS_PER_SOL);
       console.log("✅ 充值完成，新余额:", newVaultBalance / LAMPORTS_PER_SOL, "SOL\n");
       if (newVaultBalance < 5 * LAMPORTS_PER_SOL) {
         throw new Error("Reward Vault 余额仍不足，请手动充值至少 5 SOL");
@@ -355,6 +662,8 @@ describe("lp-staking", () => {
         5 * LAMPORTS_PER_SOL,
         "Reward Vault 余额不足，请将至少 5 SOL 转入 rewardVault PDA"
       );
+      // 同步直接转入金库但尚未记账的余额
+      await program.methods.fundRewards(new anchor.BN(0)).rpc();
     }
     console.log("✅ Reward Vault 余额充足\n");
 
@@ -367,7 +676,7 @@ describe("lp-staking", () => {
 
     const userPosBeforeStake = await program.account.userPosition.fetch(userPosition);
     try {
-      const stakeTx = await program.methods.stake(stakeAmount).rpc();
+      const stakeTx = await program.methods.stake(stakeAmount, null).rpc();
       console.log("交易:", stakeTx);
     } catch (err: any) {
       assert.fail("质押交易失败: " + (err?.message ?? err));
@@ -397,7 +706,7 @@ describe("lp-staking", () => {
     // 这里我们进行一次小额质押来触发奖励计算
     console.log("触发奖励更新（通过小额质押 1 LP）...");
     const smallStakeAmount = new anchor.BN(1_000_000_000); // 1 LP
-    const triggerTx = await program.methods.stake(smallStakeAmount).rpc();
+    const triggerTx = await program.methods.stake(smallStakeAmount, null).rpc();
     console.log("✓ 触发交易:", triggerTx);
 
     const userPosAfterWait = await program.account.userPosition.fetch(userPosition);
@@ -492,4 +801,1563 @@ describe("lp-staking", () => {
 
 
   });
+
+  it("Crank: updatePool 推进累计奖励", async () => {
+    console.log("=== 测试: updatePool ===");
+
+    const stakeAmount = new anchor.BN(1_000_000_000);
+    await program.methods.stake(stakeAmount, null).rpc();
+
+    const configBefore = await program.account.rewardConfig.fetch(rewardConfig);
+    await sleep(2000);
+
+    // 任意账户都可以调用
+    const keeper = anchor.web3.Keypair.generate();
+    await program.methods
+      .updatePool()
+      .accounts({ caller: keeper.publicKey })
+      .signers([keeper])
+      .rpc();
+
+    const configAfter = await program.account.rewardConfig.fetch(rewardConfig);
+    assert.isTrue(
+      configAfter.accRewardPerShare.gt(configBefore.accRewardPerShare),
+      "updatePool 后 accRewardPerShare 应增加"
+    );
+    assert.isTrue(configAfter.lastUpdateSlot.gt(configBefore.lastUpdateSlot));
+
+    await program.methods.unstake(stakeAmount).rpc();
+    console.log("✅ updatePool 断言通过\n");
+  });
+
+  it("未分配奖励: 无人质押期间的排放可提取到国库", async () => {
+    console.log("=== 测试: 未分配奖励 ===");
+
+    const poolBefore = await program.account.poolState.fetch(poolState);
+    assert.equal(poolBefore.totalStaked.toNumber(), 0, "此时应无人质押");
+
+    const configBefore = await program.account.rewardConfig.fetch(rewardConfig);
+    assert.isFalse(configBefore.carryOverUndistributed, "默认不结转");
+    await sleep(1000);
+    await program.methods.updatePool().accounts({ caller: payer.publicKey }).rpc();
+
+    const configAfter = await program.account.rewardConfig.fetch(rewardConfig);
+    assert.isTrue(
+      configAfter.undistributed.gt(configBefore.undistributed),
+      "无人质押时排放应计入未分配奖励"
+    );
+    assert.equal(
+      configAfter.accRewardPerShare.toString(),
+      configBefore.accRewardPerShare.toString()
+    );
+
+    // 未设置国库时不能提取
+    try {
+      await program.methods
+        .withdrawUndistributed(configAfter.undistributed)
+        .accounts({ treasury: payer.publicKey })
+        .rpc();
+      assert.fail("未设置国库时应失败");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidTreasury");
+    }
+
+    const treasury = anchor.web3.Keypair.generate();
+    await program.methods.setTreasury(treasury.publicKey).rpc();
+
+    // 超过未分配奖励的数量应失败
+    try {
+      await program.methods
+        .withdrawUndistributed(configAfter.undistributed.muln(1_000))
+        .accounts({ treasury: treasury.publicKey })
+        .rpc();
+      assert.fail("超额提取应失败");
+    } catch (err) {
+      assert.include(err.toString(), "InsufficientUndistributed");
+    }
+
+    const amount = configAfter.undistributed;
+    await program.methods
+      .withdrawUndistributed(amount)
+      .accounts({ treasury: treasury.publicKey })
+      .rpc();
+
+    const treasuryBalance = await provider.connection.getBalance(treasury.publicKey);
+    assert.equal(treasuryBalance, amount.toNumber());
+
+    // 开启结转后，下一次质押会把剩余未分配奖励一并分配
+    await program.methods.setCarryOverUndistributed(true).rpc();
+    const stakeAmount = new anchor.BN(1_000_000_000);
+    await program.methods.stake(stakeAmount, null).rpc();
+    await sleep(1000);
+    await program.methods.updatePool().accounts({ caller: payer.publicKey }).rpc();
+
+    const configCarried = await program.account.rewardConfig.fetch(rewardConfig);
+    assert.equal(configCarried.undistributed.toNumber(), 0, "结转后未分配奖励应清零");
+
+    await program.methods.unstake(stakeAmount).rpc();
+    await program.methods.setCarryOverUndistributed(false).rpc();
+    console.log("✅ 未分配奖励断言通过\n");
+  });
+
+  it("领取到指定地址与代理批量收获", async () => {
+    console.log("=== 测试: claimTo / harvestMany ===");
+
+    const stakeAmount = new anchor.BN(1_000_000_000);
+    await program.methods.stake(stakeAmount, null).rpc();
+    await sleep(2000);
+
+    // 1. 领取到冷钱包
+    const coldWallet = anchor.web3.Keypair.generate();
+    await program.methods
+      .claimTo()
+      .accounts({ recipient: coldWallet.publicKey })
+      .rpc();
+    const coldBalance = await provider.connection.getBalance(coldWallet.publicKey);
+    assert.isTrue(coldBalance > 0, "奖励应转入指定地址");
+    console.log("✓ 冷钱包收到:", coldBalance, "lamports");
+
+    // 2. 未授权的代理不能收获
+    const delegate = anchor.web3.Keypair.generate();
+    await sleep(2000);
+    try {
+      await program.methods
+        .harvestMany()
+        .accounts({ delegate: delegate.publicKey })
+        .remainingAccounts([
+          { pubkey: userPosition, isWritable: true, isSigner: false },
+          { pubkey: payer.publicKey, isWritable: true, isSigner: false },
+          { pubkey: payer.publicKey, isWritable: true, isSigner: false },
+        ])
+        .signers([delegate])
+        .rpc();
+      assert.fail("未授权代理应被拒绝");
+    } catch (err: any) {
+      assert.include(err.toString(), "Unauthorized");
+    }
+
+    // 3. 设置代理后批量收获，奖励进入 owner 钱包
+    await program.methods.setHarvestDelegate(delegate.publicKey).rpc();
+    const position = await program.account.userPosition.fetch(userPosition);
+    assert.equal(position.harvestDelegate.toString(), delegate.publicKey.toString());
+
+    const ownerBefore = await provider.connection.getBalance(payer.publicKey);
+    await program.methods
+      .harvestMany()
+      .accounts({ delegate: delegate.publicKey })
+      .remainingAccounts([
+        { pubkey: userPosition, isWritable: true, isSigner: false },
+        { pubkey: payer.publicKey, isWritable: true, isSigner: false },
+        { pubkey: payer.publicKey, isWritable: true, isSigner: false },
+      ])
+      .signers([delegate])
+      .rpc();
+    const ownerAfter = await provider.connection.getBalance(payer.publicKey);
+    assert.isTrue(ownerAfter > ownerBefore, "代理收获的奖励应转入 owner");
+
+    await program.methods.setHarvestDelegate(null).rpc();
+    await program.methods.unstake(stakeAmount).rpc();
+    console.log("✅ 领取到指定地址与代理收获断言通过\n");
+  });
+
+  it("推荐奖励: 记录推荐人并额外支付推荐奖励", async () => {
+    console.log("=== 测试: 推荐奖励 ===");
+
+    const referee = anchor.web3.Keypair.generate();
+    const referrer = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: referee.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        }),
+        SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: referrer.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        })
+      )
+    );
+
+    const refereeUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      referee.publicKey
+    );
+    const refereeLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      referee.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      refereeUsdcAta.address,
+      payer.publicKey,
+      110_000_000
+    );
+
+    // 1. 管理员设置 10% 推荐比例
+    await program.methods.setReferralBps(new anchor.BN(1_000)).rpc();
+
+    // 2. 被推荐人存入并质押，记录推荐人
+    await program.methods
+      .depositAndStake(new anchor.BN(100_000_000), new anchor.BN(0), deadline(), referrer.publicKey)
+      .accounts({
+        user: referee.publicKey,
+        userUsdcAccount: refereeUsdcAta.address,
+        poolUsdcAccount: poolUsdcAccount,
+        lpTokenMint: lpTokenMint,
+        userLpAccount: refereeLpAta.address,
+      })
+      .signers([referee])
+      .rpc();
+
+    const [refereePosition] = PublicKey.findProgramAddressSync(
+      [USER_POSITION_SEED, referee.publicKey.toBuffer(), poolState.toBuffer()],
+      program.programId
+    );
+    const position = await program.account.userPosition.fetch(refereePosition);
+    assert.equal(position.referrer.toString(), referrer.publicKey.toString());
+
+    // 3. 推荐人一经记录不可更改
+    await program.methods
+      .deposit(new anchor.BN(10_000_000), new anchor.BN(0), deadline(), payer.publicKey)
+      .accounts({
+        user: referee.publicKey,
+        userUsdcAccount: refereeUsdcAta.address,
+        poolUsdcAccount: poolUsdcAccount,
+        lpTokenMint: lpTokenMint,
+        userLpAccount: refereeLpAta.address,
+      })
+      .signers([referee])
+      .rpc();
+    const unchanged = await program.account.userPosition.fetch(refereePosition);
+    assert.equal(unchanged.referrer.toString(), referrer.publicKey.toString());
+
+    // 4. 领取奖励时推荐人额外获得 10%
+    await sleep(2000);
+    const referrerBefore = await provider.connection.getBalance(referrer.publicKey);
+    const refereeBefore = await provider.connection.getBalance(referee.publicKey);
+    await program.methods
+      .claim()
+      .accounts({ user: referee.publicKey, referrer: referrer.publicKey })
+      .signers([referee])
+      .rpc();
+    const referralReward = (await provider.connection.getBalance(referrer.publicKey)) - referrerBefore;
+    const refereeReward = (await provider.connection.getBalance(referee.publicKey)) - refereeBefore;
+    assert.isTrue(referralReward > 0, "推荐人应收到推荐奖励");
+    assert.equal(referralReward, Math.floor(refereeReward * 0.1), "推荐奖励应为被推荐人奖励的 10%");
+    console.log("✓ 被推荐人奖励:", refereeReward, "推荐奖励:", referralReward);
+
+    // 5. 不传推荐人账户时领取失败
+    await sleep(1000);
+    try {
+      await program.methods
+        .claim()
+        .accounts({ user: referee.publicKey, referrer: null })
+        .signers([referee])
+        .rpc();
+      assert.fail("缺少推荐人账户时应被拒绝");
+    } catch (err: any) {
+      assert.include(err.toString(), "ReferrerMismatch");
+    }
+
+    await program.methods.setReferralBps(new anchor.BN(0)).rpc();
+    console.log("✅ 推荐奖励断言通过\n");
+  });
+
+  it("投票托管: 锁仓获得投票权并按历史 slot 查询", async () => {
+    console.log("=== 测试: lockStake / votingPowerAt / tokenizeStake ===");
+
+    const voter = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: voter.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        })
+      )
+    );
+    const voterUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      voter.publicKey
+    );
+    const voterLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      voter.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      voterUsdcAta.address,
+      payer.publicKey,
+      50_000_000
+    );
+    await program.methods
+      .depositAndStake(new anchor.BN(50_000_000), new anchor.BN(0), deadline(), null)
+      .accounts({
+        user: voter.publicKey,
+        userUsdcAccount: voterUsdcAta.address,
+        poolUsdcAccount: poolUsdcAccount,
+        lpTokenMint: lpTokenMint,
+        userLpAccount: voterLpAta.address,
+      })
+      .signers([voter])
+      .rpc();
+
+    const [voterPosition] = PublicKey.findProgramAddressSync(
+      [USER_POSITION_SEED, voter.publicKey.toBuffer(), poolState.toBuffer()],
+      program.programId
+    );
+    const [voteEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vote_escrow"), voter.publicKey.toBuffer(), poolState.toBuffer()],
+      program.programId
+    );
+    const [voteEscrowGlobal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vote_escrow_global"), poolState.toBuffer()],
+      program.programId
+    );
+
+    // 1. 锁仓全部质押约 2 周
+    const position = await program.account.userPosition.fetch(voterPosition);
+    const slotBefore = await provider.connection.getSlot();
+    const unlockSlot = new anchor.BN(slotBefore + 3_024_000);
+    await program.methods
+      .lockStake(position.stakedAmount, unlockSlot)
+      .accounts({ user: voter.publicKey })
+      .signers([voter])
+      .rpc();
+
+    const locked = await program.account.userPosition.fetch(voterPosition);
+    assert.equal(locked.lockedAmount.toString(), position.stakedAmount.toString());
+
+    // 2. 锁仓期内不可解除质押
+    try {
+      await program.methods
+        .unstake(new anchor.BN(1_000_000))
+        .accounts({ user: voter.publicKey })
+        .signers([voter])
+        .rpc();
+      assert.fail("锁仓期内解除质押应被拒绝");
+    } catch (err: any) {
+      assert.include(err.toString(), "StakeLocked");
+    }
+
+    // 3. 投票权随时间衰减，锁仓前的 slot 投票权为 0
+    const escrow = await program.account.voteEscrow.fetch(voteEscrow);
+    const lockSlot = escrow.checkpoints[0].slot.toNumber();
+    const powerAtLock: anchor.BN = await program.methods
+      .votingPowerAt(new anchor.BN(lockSlot))
+      .accounts({ voteEscrow })
+      .view();
+    const powerLater: anchor.BN = await program.methods
+      .votingPowerAt(new anchor.BN(lockSlot + 1_000_000))
+      .accounts({ voteEscrow })
+      .view();
+    const powerBefore: anchor.BN = await program.methods
+      .votingPowerAt(new anchor.BN(lockSlot - 1))
+      .accounts({ voteEscrow })
+      .view();
+    assert.isTrue(powerAtLock.gt(new anchor.BN(0)));
+    assert.isTrue(powerLater.lt(powerAtLock), "投票权应线性衰减");
+    assert.equal(powerBefore.toNumber(), 0);
+
+    const totalAtLock: anchor.BN = await program.methods
+      .totalVotingPowerAt(new anchor.BN(lockSlot))
+      .accounts({ voteEscrowGlobal, voteEscrowPage: null })
+      .view();
+    assert.isTrue(totalAtLock.gte(powerAtLock), "全局投票权应不小于用户投票权");
+    console.log("✓ 锁仓时投票权:", powerAtLock.toString(), "全局:", totalAtLock.toString());
+
+    // 4. 锁仓中的质押可转换为仓位代币：先结算奖励，被转换部分的投票权移除，仓位代币继承到期 slot
+    const tokenizeAmount = position.stakedAmount.divn(2);
+    const lockedMint = anchor.web3.Keypair.generate();
+    const pendingBefore = (await program.account.userPosition.fetch(voterPosition)).pendingReward;
+    const globalEscrow = await program.account.voteEscrowGlobal.fetch(voteEscrowGlobal);
+    const pageIndex = Buffer.alloc(4);
+    pageIndex.writeUInt32LE(globalEscrow.archivedPages);
+    const [voteEscrowPage] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vote_escrow_page"), poolState.toBuffer(), pageIndex],
+      program.programId
+    );
+    await program.methods
+      .tokenizeStake(tokenizeAmount)
+      .accounts({
+        user: voter.publicKey,
+        voteEscrow,
+        voteEscrowGlobal,
+        voteEscrowPage,
+        lpTokenMint,
+        userLpAccount: voterLpAta.address,
+        positionMint: lockedMint.publicKey,
+      })
+      .signers([voter, lockedMint])
+      .rpc();
+
+    const [lockedTokenized] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tokenized_position"), lockedMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const tokenizedLocked = await program.account.tokenizedPosition.fetch(lockedTokenized);
+    assert.equal(tokenizedLocked.stakedAmount.toString(), tokenizeAmount.toString());
+    assert.equal(tokenizedLocked.lockEndSlot.toString(), locked.lockEndSlot.toString());
+
+    const remaining = await program.account.userPosition.fetch(voterPosition);
+    const expectedRemaining = position.stakedAmount.sub(tokenizeAmount).toString();
+    assert.equal(remaining.stakedAmount.toString(), expectedRemaining);
+    assert.equal(remaining.lockedAmount.toString(), expectedRemaining);
+    assert.isTrue(remaining.pendingReward.gte(pendingBefore), "转换前应先结算奖励");
+
+    const slotAfter = await provider.connection.getSlot();
+    const powerAfter: anchor.BN = await program.methods
+      .votingPowerAt(new anchor.BN(slotAfter))
+      .accounts({ voteEscrow })
+      .view();
+    assert.isTrue(powerAfter.lt(powerAtLock.divn(2).addn(1)), "被转换部分的投票权应移除");
+
+    // 5. 仓位代币在锁仓到期前不可退出
+    try {
+      await program.methods
+        .exitNft()
+        .accounts({
+          holder: voter.publicKey,
+          positionMint: lockedMint.publicKey,
+          holderNftAccount: getAssociatedTokenAddressSync(lockedMint.publicKey, voter.publicKey),
+          holderLpAccount: voterLpAta.address,
+        })
+        .signers([voter])
+        .rpc();
+      assert.fail("锁仓期内退出仓位代币应被拒绝");
+    } catch (err: any) {
+      assert.include(err.toString(), "StakeLocked");
+    }
+    console.log("✅ 投票托管断言通过\n");
+  });
+
+  it("质押快照: 按快照编号证明历史质押量", async () => {
+    console.log("=== 测试: takeSnapshot / proveStake ===");
+
+    const stakeAmount = new anchor.BN(1_000_000_000);
+    await program.methods.stake(stakeAmount, null).rpc();
+
+    // 1. 创建快照
+    const pool = await program.account.poolState.fetch(poolState);
+    const snapshotId = pool.snapshotCount;
+    const [snapshot] = PublicKey.findProgramAddressSync(
+      [Buffer.from("snapshot"), poolState.toBuffer(), snapshotId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods.takeSnapshot().accounts({ snapshot }).rpc();
+
+    const positionAtSnapshot = await program.account.userPosition.fetch(userPosition);
+    const poolAtSnapshot = await program.account.poolState.fetch(poolState);
+    const snapshotAccount = await program.account.snapshot.fetch(snapshot);
+    assert.equal(snapshotAccount.version, 2);
+    assert.equal(snapshotAccount.totalStaked.toString(), poolAtSnapshot.totalStaked.toString());
+
+    // 2. 快照之后的质押变化不影响快照证明
+    await sleep(1000);
+    await program.methods.unstake(stakeAmount).rpc();
+
+    const [userStakeHistory] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stake_history"), payer.publicKey.toBuffer(), poolState.toBuffer()],
+      program.programId
+    );
+    const proof = await program.methods
+      .proveStake(snapshotId)
+      .accounts({ snapshot, userStakeHistory, globalStakeHistory: null })
+      .view();
+
+    assert.equal(proof.owner.toString(), payer.publicKey.toString());
+    assert.equal(proof.stakedAmount.toString(), positionAtSnapshot.stakedAmount.toString());
+    assert.equal(proof.totalStaked.toString(), poolAtSnapshot.totalStaked.toString());
+
+    const history = await program.account.stakeHistory.fetch(userStakeHistory);
+    const latest = history.checkpoints[history.checkpoints.length - 1];
+    const position = await program.account.userPosition.fetch(userPosition);
+    assert.equal(latest.amount.toString(), position.stakedAmount.toString());
+
+    // 3. 全局质押量历史分页可以查询快照 slot 的总质押量
+    const { stakeHistoryPage } = await program.account.poolState.fetch(poolState);
+    const totalAtSnapshot = await program.methods
+      .totalStakedAt(stakeHistoryPage, snapshotAccount.slot)
+      .view();
+    assert.equal(totalAtSnapshot.toString(), poolAtSnapshot.totalStaked.toString());
+    console.log("✓ 快照", snapshotId.toString(), "质押量:", proof.stakedAmount.toString());
+    console.log("✅ 质押快照断言通过\n");
+  });
+
+  it("奖励记账: 累计支付不超过累计排放与注资", async () => {
+    console.log("=== 测试: 奖励记账不变量 ===");
+
+    const stakeAmount = new anchor.BN(1_000_000_000);
+    await program.methods.stake(stakeAmount, null).rpc();
+    await sleep(1000);
+
+    const before = await program.account.rewardConfig.fetch(rewardConfig);
+    await program.methods.claim().rpc();
+    const after = await program.account.rewardConfig.fetch(rewardConfig);
+
+    assert.isTrue(after.totalClaimed.gt(before.totalClaimed), "领取后 totalClaimed 应增加");
+    assert.isTrue(after.totalClaimed.lte(after.totalEmitted));
+    assert.isTrue(after.totalEmitted.lte(after.totalFunded));
+    assert.isTrue(
+      after.rewardDust.lt(new anchor.BN("1000000000000")),
+      "满 1 lamport 的尘埃应已转入未分配奖励"
+    );
+
+    await program.methods.unstake(stakeAmount).rpc();
+    console.log("✅ 奖励记账断言通过\n");
+  });
+
+  it("协议抽成: 计提排放抽成并提取到国库", async () => {
+    console.log("=== 测试: 协议抽成 ===");
+
+    await program.methods.setProtocolFeeBps(new anchor.BN(1_000)).rpc();
+
+    const stakeAmount = new anchor.BN(1_000_000_000);
+    await program.methods.stake(stakeAmount, null).rpc();
+    await sleep(1000);
+    await program.methods.updatePool().accounts({ caller: payer.publicKey }).rpc();
+
+    const config = await program.account.rewardConfig.fetch(rewardConfig);
+    assert.isTrue(config.protocolFeesAccrued.gtn(0), "排放后应计提协议奖励");
+
+    const { treasury } = await program.account.poolState.fetch(poolState);
+    const treasuryBefore = await provider.connection.getBalance(treasury);
+    await program.methods.collectProtocolRewards().accounts({ treasury }).rpc();
+    const treasuryAfter = await provider.connection.getBalance(treasury);
+
+    const configAfter = await program.account.rewardConfig.fetch(rewardConfig);
+    assert.isAtLeast(treasuryAfter - treasuryBefore, config.protocolFeesAccrued.toNumber());
+    assert.equal(configAfter.protocolFeesAccrued.toNumber(), 0);
+
+    // 超过 100% 的比例应被拒绝
+    try {
+      await program.methods.setProtocolFeeBps(new anchor.BN(10_001)).rpc();
+      assert.fail("超过 10000 bps 应失败");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidProtocolFeeBps");
+    }
+
+    await program.methods.setProtocolFeeBps(new anchor.BN(0)).rpc();
+    await program.methods.unstake(stakeAmount).rpc();
+    console.log("✅ 协议抽成断言通过\n");
+  });
+
+  it("组合指令: 存入并质押 / 解除质押并提取", async () => {
+    console.log("=== 测试: depositAndStake / unstakeAndWithdraw ===");
+
+    const userUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      payer.publicKey
+    );
+
+    const userLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      payer.publicKey
+    );
+
+    const tokenAccounts = {
+      user: payer.publicKey,
+      userUsdcAccount: userUsdcAta.address,
+      poolUsdcAccount: poolUsdcAccount,
+      lpTokenMint: lpTokenMint,
+      userLpAccount: userLpAta.address,
+    };
+
+    // 1. 存入 1,000 USDC 并一次性质押
+    const posBefore = await program.account.userPosition.fetch(userPosition);
+    await program.methods
+      .depositAndStake(new anchor.BN(1_000_000_000), new anchor.BN(0), deadline(), null)
+      .accounts(tokenAccounts)
+      .rpc();
+
+    const posAfterStake = await program.account.userPosition.fetch(userPosition);
+    const stakedDelta = posAfterStake.stakedAmount.sub(posBefore.stakedAmount);
+    assert.isTrue(stakedDelta.gt(new anchor.BN(0)), "存入后应自动质押新铸造的 LP");
+    assert.equal(
+      posAfterStake.lpBalance.toString(),
+      posBefore.lpBalance.toString(),
+      "未质押 LP 余额不应变化"
+    );
+    console.log("✓ 存入并质押 LP:", stakedDelta.toString());
+
+    // 2. 解除质押并提取同等数量
+    await program.methods
+      .unstakeAndWithdraw(stakedDelta, new anchor.BN(0), deadline())
+      .accounts(tokenAccounts)
+      .rpc();
+
+    const posAfterWithdraw = await program.account.userPosition.fetch(userPosition);
+    assert.equal(
+      posAfterWithdraw.stakedAmount.toString(),
+      posBefore.stakedAmount.toString()
+    );
+    assert.equal(
+      posAfterWithdraw.lpBalance.toString(),
+      posBefore.lpBalance.toString()
+    );
+    console.log("✅ 组合指令断言通过\n");
+  });
+
+  it("StableSwap: 双稳定币池添加流动性、兑换、单币种与平衡移除", async () => {
+    console.log("=== 测试: StableSwap ===");
+
+    // 1. 创建 wrappedUSDT 并初始化 StableSwap 池（A=100，手续费 4 bps）
+    const wrappedUsdtMint = await createMint(
+      provider.connection,
+      payer.payer,
+      payer.publicKey,
+      null,
+      6
+    );
+    const [stableSwap] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stable_swap"), poolState.toBuffer()],
+      program.programId
+    );
+    const [vaultA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stable_vault"), stableSwap.toBuffer(), wrappedUsdcMint.toBuffer()],
+      program.programId
+    );
+    const [vaultB] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stable_vault"), stableSwap.toBuffer(), wrappedUsdtMint.toBuffer()],
+      program.programId
+    );
+    const [stableLpMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stable_lp_mint"), stableSwap.toBuffer()],
+      program.programId
+    );
+
+    // 超出上限的手续费应被拒绝
+    try {
+      await program.methods
+        .initializeStableSwap(new anchor.BN(100), new anchor.BN(101))
+        .accounts({ tokenBMint: wrappedUsdtMint })
+        .rpc();
+      assert.fail("手续费超出上限应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidStableSwapParams");
+    }
+
+    await program.methods
+      .initializeStableSwap(new anchor.BN(100), new anchor.BN(4))
+      .accounts({ tokenBMint: wrappedUsdtMint })
+      .rpc();
+
+    const userUsdc = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      payer.publicKey
+    );
+    const userUsdt = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdtMint,
+      payer.publicKey
+    );
+    const userStableLp = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      stableLpMint,
+      payer.publicKey
+    );
+    await mintTo(provider.connection, payer.payer, wrappedUsdcMint, userUsdc.address, payer.payer, 3_000_000_000);
+    await mintTo(provider.connection, payer.payer, wrappedUsdtMint, userUsdt.address, payer.payer, 3_000_000_000);
+
+    const liquidityAccounts = {
+      vaultA,
+      vaultB,
+      lpMint: stableLpMint,
+      userTokenA: userUsdc.address,
+      userTokenB: userUsdt.address,
+      userLpAccount: userStableLp.address,
+    };
+
+    // 2. 平衡存入：首次按不变量 D 铸造，约等于存入总额
+    await program.methods
+      .stableAddLiquidity(new anchor.BN(1_000_000_000), new anchor.BN(1_000_000_000), new anchor.BN(0))
+      .accounts(liquidityAccounts)
+      .rpc();
+    let state = await program.account.stableSwapState.fetch(stableSwap);
+    const firstLp = state.lpSupply;
+    assert.isTrue(firstLp.sub(new anchor.BN(2_000_000_000)).abs().lten(1));
+    console.log("✓ 平衡存入铸造 LP:", firstLp.toString());
+
+    // 3. 非平衡存入：收取手续费，铸造少于平衡存入同等总额
+    await program.methods
+      .stableAddLiquidity(new anchor.BN(500_000_000), new anchor.BN(0), new anchor.BN(0))
+      .accounts(liquidityAccounts)
+      .rpc();
+    state = await program.account.stableSwapState.fetch(stableSwap);
+    const imbalancedLp = state.lpSupply.sub(firstLp);
+    assert.isTrue(imbalancedLp.lt(new anchor.BN(500_000_000)));
+    assert.isTrue(imbalancedLp.gt(new anchor.BN(495_000_000)));
+    console.log("✓ 非平衡存入铸造 LP:", imbalancedLp.toString());
+
+    // 4. 兑换：USDC -> USDT，接近 1:1；滑点保护生效
+    try {
+      await program.methods
+        .stableSwap({ a: {} }, new anchor.BN(10_000_000), new anchor.BN(10_000_000))
+        .accounts({ vaultA, vaultB, userTokenA: userUsdc.address, userTokenB: userUsdt.address })
+        .rpc();
+      assert.fail("输出低于最小值应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "SlippageExceeded");
+    }
+
+    const usdtBefore = (await provider.connection.getTokenAccountBalance(userUsdt.address)).value.amount;
+    await program.methods
+      .stableSwap({ a: {} }, new anchor.BN(10_000_000), new anchor.BN(9_950_000))
+      .accounts({ vaultA, vaultB, userTokenA: userUsdc.address, userTokenB: userUsdt.address })
+      .rpc();
+    const usdtAfter = (await provider.connection.getTokenAccountBalance(userUsdt.address)).value.amount;
+    const swapOut = new anchor.BN(usdtAfter).sub(new anchor.BN(usdtBefore));
+    assert.isTrue(swapOut.lt(new anchor.BN(10_000_000)));
+    console.log("✓ 兑换 10 USDC 获得 USDT:", swapOut.toString());
+
+    // 5. 单币种移除：销毁 1% LP，全部以 USDT 取出
+    state = await program.account.stableSwapState.fetch(stableSwap);
+    const removeLp = state.lpSupply.divn(100);
+    await program.methods
+      .stableRemoveLiquidityOne(removeLp, { b: {} }, new anchor.BN(0))
+      .accounts(liquidityAccounts)
+      .rpc();
+    const after = await program.account.stableSwapState.fetch(stableSwap);
+    assert.equal(after.lpSupply.toString(), state.lpSupply.sub(removeLp).toString());
+    assert.equal(after.reserveA.toString(), state.reserveA.toString(), "币种 A 余额不应变化");
+    assert.isTrue(after.reserveB.lt(state.reserveB));
+
+    const vaultBBalance = (await provider.connection.getTokenAccountBalance(vaultB)).value.amount;
+    assert.equal(vaultBBalance, after.reserveB.toString(), "记账余额应与金库一致");
+
+    // 6. StableSwap LP 质押：按独立的排放速率获得 SOL 奖励，解除质押后 LP 全额返还
+    const [stableStakePosition] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stable_stake"), payer.publicKey.toBuffer(), stableSwap.toBuffer()],
+      program.programId
+    );
+    const [stableStakeVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stable_stake_vault"), stableSwap.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .fundStableRewards(new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({})
+      .rpc();
+    await program.methods
+      .setStableRewardRate(new anchor.BN(1_000))
+      .accounts({})
+      .rpc();
+    await program.methods
+      .stableStake(after.lpSupply)
+      .accounts({ lpMint: stableLpMint, userLpAccount: userStableLp.address })
+      .rpc();
+
+    let staking = await program.account.stableSwapState.fetch(stableSwap);
+    assert.equal(staking.version, 2);
+    assert.equal(staking.totalStaked.toString(), after.lpSupply.toString());
+    const escrowed = (await provider.connection.getTokenAccountBalance(stableStakeVault)).value.amount;
+    assert.equal(escrowed, after.lpSupply.toString(), "质押的 LP 应托管在金库中");
+
+    await sleep(2000);
+    await program.methods.stableClaim().accounts({}).rpc();
+    const claimed = await program.account.stableSwapState.fetch(stableSwap);
+    assert.isTrue(claimed.totalEmitted.gtn(0), "质押期间应排放奖励");
+
+    await program.methods
+      .stableUnstake(after.lpSupply)
+      .accounts({ lpMint: stableLpMint, userLpAccount: userStableLp.address })
+      .rpc();
+    staking = await program.account.stableSwapState.fetch(stableSwap);
+    const stakePosition = await program.account.stableStakePosition.fetch(stableStakePosition);
+    assert.equal(staking.totalStaked.toNumber(), 0);
+    assert.equal(stakePosition.stakedAmount.toNumber(), 0);
+    assert.isTrue(staking.totalEmitted.lte(staking.totalFunded), "排放不应超过注资");
+    const returned = (await provider.connection.getTokenAccountBalance(userStableLp.address)).value.amount;
+    assert.equal(returned, after.lpSupply.toString());
+
+    // 7. 全部退出：单币种销毁全部 LP 被拒绝，平衡移除取出两个币种的全部余额
+    try {
+      await program.methods
+        .stableRemoveLiquidityOne(after.lpSupply, { a: {} }, new anchor.BN(0))
+        .accounts(liquidityAccounts)
+        .rpc();
+      assert.fail("单币种销毁全部 LP 应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "StableSwapFullExitOneSided");
+    }
+
+    await program.methods
+      .stableRemoveLiquidity(after.lpSupply, after.reserveA, after.reserveB)
+      .accounts(liquidityAccounts)
+      .rpc();
+    const empty = await program.account.stableSwapState.fetch(stableSwap);
+    assert.equal(empty.lpSupply.toNumber(), 0);
+    assert.equal(empty.reserveA.toNumber(), 0);
+    assert.equal(empty.reserveB.toNumber(), 0);
+    console.log("✅ StableSwap 断言通过\n");
+  });
+
+  it("闪电贷: 同一交易内借出并归还，手续费计入池子资产", async () => {
+    console.log("=== 测试: 闪电贷 ===");
+
+    const userUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      payer.publicKey
+    );
+    const userLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      payer.publicKey
+    );
+
+    // 1. 设置手续费：超出上限应失败
+    try {
+      await program.methods.setFlashLoanFee(new anchor.BN(101)).rpc();
+      assert.fail("手续费超出上限应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidFlashLoanFee");
+    }
+    await program.methods.setFlashLoanFee(new anchor.BN(9)).rpc();
+
+    const amount = new anchor.BN(100_000_000);
+    const expectedFee = new anchor.BN(90_000); // 100 USDC * 0.09%
+    const loanIx = await program.methods
+      .flashLoan(amount)
+      .accounts({ borrower: payer.publicKey, borrowerUsdcAccount: userUsdcAta.address })
+      .instruction();
+    const repayIx = await program.methods
+      .flashRepay()
+      .accounts({ repayer: payer.publicKey, repayerUsdcAccount: userUsdcAta.address })
+      .instruction();
+
+    // 2. 没有后续 flash_repay 的借款应被拒绝
+    try {
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(loanIx));
+      assert.fail("缺少归还指令应失败");
+    } catch (err: any) {
+      assert.include(err.logs.join("\n"), "MissingFlashRepay");
+    }
+
+    // 3. 借款未归还期间禁止存入
+    const depositIx = await program.methods
+      .deposit(new anchor.BN(1_000_000_000), new anchor.BN(0), deadline(), null)
+      .accounts({
+        user: payer.publicKey,
+        userUsdcAccount: userUsdcAta.address,
+        poolUsdcAccount: poolUsdcAccount,
+        lpTokenMint: lpTokenMint,
+        userLpAccount: userLpAta.address,
+      })
+      .instruction();
+    try {
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(loanIx, depositIx, repayIx)
+      );
+      assert.fail("借款期间存入应失败");
+    } catch (err: any) {
+      assert.include(err.logs.join("\n"), "FlashLoanActive");
+    }
+
+    // 4. 借出并归还：池子余额与 total_deposited 增加手续费
+    const poolBefore = await program.account.poolState.fetch(poolState);
+    const balanceBefore = (await provider.connection.getTokenAccountBalance(poolUsdcAccount)).value.amount;
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(loanIx, repayIx));
+
+    const poolAfter = await program.account.poolState.fetch(poolState);
+    const balanceAfter = (await provider.connection.getTokenAccountBalance(poolUsdcAccount)).value.amount;
+    assert.equal(
+      poolAfter.totalDeposited.sub(poolBefore.totalDeposited).toString(),
+      expectedFee.toString()
+    );
+    assert.equal(
+      new anchor.BN(balanceAfter).sub(new anchor.BN(balanceBefore)).toString(),
+      expectedFee.toString()
+    );
+    assert.equal(poolAfter.flashLoanAmount.toString(), "0");
+    console.log("✅ 闪电贷断言通过，手续费:", expectedFee.toString(), "\n");
+  });
+
+  it("忠诚度加成: 连续质押时长提升奖励权重", async () => {
+    console.log("=== 测试: 忠诚度加成 ===");
+
+    // 参数校验：开启时 ramp 必须为正
+    try {
+      await program.methods.setLoyaltyCurve(new anchor.BN(5_000), new anchor.BN(0)).rpc();
+      assert.fail("ramp 为 0 应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidLoyaltyCurve");
+    }
+
+    // 1. 开启曲线：最多 +50%，4 秒达到上限
+    await program.methods.setLoyaltyCurve(new anchor.BN(5_000), new anchor.BN(4)).rpc();
+    await sleep(5_000);
+    await program.methods
+      .refreshLoyalty()
+      .accounts({ caller: payer.publicKey, userPosition })
+      .rpc();
+
+    let position = await program.account.userPosition.fetch(userPosition);
+    let pool = await program.account.poolState.fetch(poolState);
+    assert.isTrue(position.stakedAmount.gtn(0), "主测试账户应有质押");
+    assert.isTrue(position.loyaltyBonus.gtn(0));
+    assert.isTrue(position.loyaltyBonus.lte(position.stakedAmount.divn(2)));
+    assert.equal(pool.totalLoyaltyBonus.toString(), position.loyaltyBonus.toString());
+    console.log("✓ 忠诚度加成:", position.loyaltyBonus.toString());
+
+    // 2. 部分解除质押：连续质押时长按比例缩减，加成随之降低
+    const bonusBefore = position.loyaltyBonus;
+    const unstakeAmount = position.stakedAmount.divn(2);
+    await program.methods.unstake(unstakeAmount).rpc();
+    position = await program.account.userPosition.fetch(userPosition);
+    assert.isTrue(position.loyaltyBonus.lt(bonusBefore));
+
+    // 3. 关闭曲线并刷新：加成清零，恢复质押
+    await program.methods.setLoyaltyCurve(new anchor.BN(0), new anchor.BN(0)).rpc();
+    await program.methods
+      .refreshLoyalty()
+      .accounts({ caller: payer.publicKey, userPosition })
+      .rpc();
+    await program.methods.stake(unstakeAmount, null).rpc();
+
+    position = await program.account.userPosition.fetch(userPosition);
+    pool = await program.account.poolState.fetch(poolState);
+    assert.equal(position.loyaltyBonus.toString(), "0");
+    assert.equal(pool.totalLoyaltyBonus.toString(), "0");
+    console.log("✅ 忠诚度加成断言通过\n");
+  });
+
+  it("代存与操作员: depositFor / stakeFor 与授权操作员代为管理仓位", async () => {
+    console.log("=== 测试: 代存与操作员 ===");
+
+    const beneficiary = anchor.web3.Keypair.generate();
+    const operator = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: beneficiary.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        })
+      )
+    );
+
+    const payerUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      payer.publicKey
+    );
+    const payerLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      payer.publicKey
+    );
+    const beneficiaryLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      beneficiary.publicKey
+    );
+    const [beneficiaryPosition] = PublicKey.findProgramAddressSync(
+      [USER_POSITION_SEED, beneficiary.publicKey.toBuffer(), poolState.toBuffer()],
+      program.programId
+    );
+
+    // 1. 代存：付款人出资，LP 记入受益人的新仓位
+    await program.methods
+      .depositFor(new anchor.BN(10_000_000), new anchor.BN(0), deadline())
+      .accounts({
+        payer: payer.publicKey,
+        beneficiary: beneficiary.publicKey,
+        payerUsdcAccount: payerUsdcAta.address,
+        poolUsdcAccount: poolUsdcAccount,
+        lpTokenMint: lpTokenMint,
+        beneficiaryLpAccount: beneficiaryLpAta.address,
+      })
+      .rpc();
+
+    let position = await program.account.userPosition.fetch(beneficiaryPosition);
+    assert.equal(position.owner.toString(), beneficiary.publicKey.toString());
+    assert.isTrue(position.lpBalance.gtn(0));
+    const lpBalanceBefore = position.lpBalance;
+    console.log("✓ 代存 LP:", lpBalanceBefore.toString());
+
+    // 2. 代质押：付款人存入后把 LP 转给受益人并直接质押
+    const payerBefore = await program.account.userPosition.fetch(userPosition);
+    await program.methods
+      .deposit(new anchor.BN(10_000_000), new anchor.BN(0), deadline(), null)
+      .accounts({
+        user: payer.publicKey,
+        userUsdcAccount: payerUsdcAta.address,
+        poolUsdcAccount: poolUsdcAccount,
+        lpTokenMint: lpTokenMint,
+        userLpAccount: payerLpAta.address,
+      })
+      .rpc();
+    const payerAfterDeposit = await program.account.userPosition.fetch(userPosition);
+    const stakeAmount = payerAfterDeposit.lpBalance.sub(payerBefore.lpBalance);
+
+    await program.methods
+      .stakeFor(stakeAmount)
+      .accounts({
+        payer: payer.publicKey,
+        beneficiary: beneficiary.publicKey,
+        payerLpAccount: payerLpAta.address,
+        beneficiaryLpAccount: beneficiaryLpAta.address,
+      })
+      .rpc();
+
+    position = await program.account.userPosition.fetch(beneficiaryPosition);
+    const payerAfter = await program.account.userPosition.fetch(userPosition);
+    assert.equal(position.stakedAmount.toString(), stakeAmount.toString());
+    assert.equal(position.lpBalance.toString(), lpBalanceBefore.toString());
+    assert.equal(payerAfter.lpBalance.toString(), payerBefore.lpBalance.toString());
+    console.log("✓ 代质押 LP:", stakeAmount.toString());
+
+    // 3. 受益人授权操作员；操作员代为解除质押与重新质押
+    const [operatorApproval] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("operator_approval"),
+        beneficiary.publicKey.toBuffer(),
+        poolState.toBuffer(),
+        operator.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const [beneficiaryStakeHistory] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stake_history"), beneficiary.publicKey.toBuffer(), poolState.toBuffer()],
+      program.programId
+    );
+    const operatorAccounts = {
+      operator: operator.publicKey,
+      payer: payer.publicKey,
+      userPosition: beneficiaryPosition,
+      operatorApproval,
+      userStakeHistory: beneficiaryStakeHistory,
+    };
+
+    try {
+      await program.methods
+        .operatorUnstake(stakeAmount)
+        .accounts(operatorAccounts)
+        .signers([operator])
+        .rpc();
+      assert.fail("未授权的操作员应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "AccountNotInitialized");
+    }
+
+    await program.methods
+      .approveOperator(operator.publicKey)
+      .accounts({ user: beneficiary.publicKey })
+      .signers([beneficiary])
+      .rpc();
+
+    await program.methods
+      .operatorUnstake(stakeAmount)
+      .accounts(operatorAccounts)
+      .signers([operator])
+      .rpc();
+    position = await program.account.userPosition.fetch(beneficiaryPosition);
+    assert.equal(position.stakedAmount.toString(), "0");
+    assert.equal(position.lpBalance.toString(), lpBalanceBefore.add(stakeAmount).toString());
+
+    await program.methods
+      .operatorStake(stakeAmount)
+      .accounts(operatorAccounts)
+      .signers([operator])
+      .rpc();
+    position = await program.account.userPosition.fetch(beneficiaryPosition);
+    assert.equal(position.stakedAmount.toString(), stakeAmount.toString());
+    console.log("✓ 操作员代为管理仓位");
+
+    // 4. 撤销授权后操作员无法再操作
+    await program.methods
+      .revokeOperator()
+      .accounts({ user: beneficiary.publicKey, operatorApproval })
+      .signers([beneficiary])
+      .rpc();
+    try {
+      await program.methods
+        .operatorUnstake(stakeAmount)
+        .accounts(operatorAccounts)
+        .signers([operator])
+        .rpc();
+      assert.fail("撤销授权后应失败");
+    } catch (err: any) {
+      assert.include(err.toString(), "AccountNotInitialized");
+    }
+    console.log("✅ 代存与操作员断言通过\n");
+  });
+
+  it("只读报价: 通过 return data 返回结果", async () => {
+    console.log("=== 测试: quote 指令 ===");
+
+    const pool = await program.account.poolState.fetch(poolState);
+    const amount = new anchor.BN(1_000_000_000);
+
+    // LP 报价应与池子比例一致
+    const lpQuote: anchor.BN = await program.methods.quoteDeposit(amount).view();
+    const expectedLp = amount.mul(pool.totalLpSupply).div(pool.totalDeposited);
+    assert.equal(lpQuote.toString(), expectedLp.toString());
+
+    // 往返报价不应多于存入金额（取整偏向池子）
+    const usdcQuote: anchor.BN = await program.methods.quoteWithdraw(lpQuote).view();
+    assert.isTrue(usdcQuote.lte(amount));
+    console.log("✓ 存入报价 LP:", lpQuote.toString(), "赎回报价 USDC:", usdcQuote.toString());
+
+    const pending: anchor.BN = await program.methods
+      .quotePendingReward()
+      .accounts({ userPosition })
+      .view();
+    const position = await program.account.userPosition.fetch(userPosition);
+    assert.isTrue(pending.gte(position.pendingReward));
+    console.log("✓ 待领取奖励报价:", pending.toString());
+
+    const emission = await program.methods.quoteEmission().view();
+    assert.equal(emission.rewardPerSlot.toNumber(), 1_000_000);
+    assert.equal(emission.totalStaked.toString(), pool.totalStaked.toString());
+    console.log("✅ 只读报价断言通过\n");
+  });
+
+  it("池子统计: 累计存取量、仓位数与领取总额", async () => {
+    console.log("=== 测试: quotePoolStats ===");
+
+    const userUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      payer.publicKey
+    );
+    const userLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      payer.publicKey
+    );
+    const tokenAccounts = {
+      user: payer.publicKey,
+      userUsdcAccount: userUsdcAta.address,
+      poolUsdcAccount: poolUsdcAccount,
+      lpTokenMint: lpTokenMint,
+      userLpAccount: userLpAta.address,
+    };
+
+    const before = await program.methods.quotePoolStats().view();
+    assert.isTrue(before.activePositions.gten(1));
+    assert.isTrue(before.stakerCount.gten(1), "主测试账户已质押");
+
+    // 1. 存入并提取：累计量分别增加
+    const amount = new anchor.BN(10_000_000);
+    await program.methods
+      .deposit(amount, new anchor.BN(0), deadline(), null)
+      .accounts(tokenAccounts)
+      .rpc();
+    const lpQuote: anchor.BN = await program.methods.quoteDeposit(amount).view();
+    await program.methods
+      .withdraw(lpQuote, new anchor.BN(0), deadline())
+      .accounts(tokenAccounts)
+      .rpc();
+
+    const after = await program.methods.quotePoolStats().view();
+    assert.equal(
+      after.cumulativeDeposited.sub(before.cumulativeDeposited).toString(),
+      amount.toString()
+    );
+    assert.isTrue(after.cumulativeWithdrawn.gt(before.cumulativeWithdrawn));
+    assert.equal(after.activePositions.toString(), before.activePositions.toString(), "已有仓位不重复计数");
+    assert.isTrue(after.lastActivitySlot.gte(before.lastActivitySlot));
+
+    // 2. 领取奖励：累计领取总额增加
+    await program.methods.claim().rpc();
+    const afterClaim = await program.methods.quotePoolStats().view();
+    assert.isTrue(afterClaim.totalRewardsClaimed.gt(after.totalRewardsClaimed));
+
+    // 仓位累计统计与池子统计同步增加
+    const position = await program.account.userPosition.fetch(userPosition);
+    assert.isTrue(position.cumulativeWithdrawn.gtn(0));
+    assert.isTrue(position.totalRewardsClaimed.gtn(0));
+    assert.isTrue(position.claimCount >= 1);
+    console.log("✅ 池子统计断言通过:", afterClaim.totalRewardsClaimed.toString(), "lamports 已领取\n");
+  });
+
+  it("关闭空仓位并重新存入", async () => {
+    console.log("=== 测试: closePosition ===");
+
+    // 使用新用户，避免影响主测试账户
+    const user = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: user.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        })
+      )
+    );
+
+    const userUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      user.publicKey
+    );
+    const userLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      user.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      userUsdcAta.address,
+      payer.publicKey,
+      20_000_000
+    );
+
+    const [position] = PublicKey.findProgramAddressSync(
+      [USER_POSITION_SEED, user.publicKey.toBuffer(), poolState.toBuffer()],
+      program.programId
+    );
+    const tokenAccounts = {
+      user: user.publicKey,
+      userUsdcAccount: userUsdcAta.address,
+      poolUsdcAccount: poolUsdcAccount,
+      lpTokenMint: lpTokenMint,
+      userLpAccount: userLpAta.address,
+    };
+
+    // 1. 存入后仓位非空，关闭应失败
+    await program.methods
+      .deposit(new anchor.BN(10_000_000), new anchor.BN(0), deadline(), null)
+      .accounts(tokenAccounts)
+      .signers([user])
+      .rpc();
+
+    try {
+      await program.methods
+        .closePosition()
+        .accounts({ user: user.publicKey })
+        .signers([user])
+        .rpc();
+      assert.fail("应该抛出 PositionNotEmpty 错误");
+    } catch (err: any) {
+      assert.include(err.toString(), "PositionNotEmpty");
+    }
+
+    // 2. 全部赎回后关闭，租金退还
+    const pos = await program.account.userPosition.fetch(position);
+    await program.methods
+      .withdraw(pos.lpBalance, new anchor.BN(0), deadline())
+      .accounts(tokenAccounts)
+      .signers([user])
+      .rpc();
+    await program.methods
+      .closePosition()
+      .accounts({ user: user.publicKey })
+      .signers([user])
+      .rpc();
+
+    assert.isNull(await program.account.userPosition.fetchNullable(position));
+    console.log("✓ 仓位已关闭");
+
+    // 3. 再次存入会从零重新初始化仓位
+    await program.methods
+      .deposit(new anchor.BN(5_000_000), new anchor.BN(0), deadline(), null)
+      .accounts(tokenAccounts)
+      .signers([user])
+      .rpc();
+
+    const reopened = await program.account.userPosition.fetch(position);
+    assert.equal(reopened.owner.toString(), user.publicKey.toString());
+    assert.equal(reopened.stakedAmount.toNumber(), 0);
+    assert.equal(reopened.pendingReward.toNumber(), 0);
+    console.log("✅ 关闭仓位断言通过\n");
+  });
+
+  it("仓位代币: 质押铸造、转让后由新持有者领取与退出", async () => {
+    console.log("=== 测试: stakeNft / claimNft / exitNft ===");
+
+    const userLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      payer.publicKey
+    );
+
+    // 1. 质押并铸造仓位代币
+    const stakeAmount = new anchor.BN(1_000_000_000);
+    const positionMint = anchor.web3.Keypair.generate();
+    const posBefore = await program.account.userPosition.fetch(userPosition);
+    const poolBefore = await program.account.poolState.fetch(poolState);
+    await program.methods
+      .stakeNft(stakeAmount)
+      .accounts({
+        lpTokenMint,
+        userLpAccount: userLpAta.address,
+        positionMint: positionMint.publicKey,
+      })
+      .signers([positionMint])
+      .rpc();
+
+    const [tokenizedPosition] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tokenized_position"), positionMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const tokenized = await program.account.tokenizedPosition.fetch(tokenizedPosition);
+    assert.equal(tokenized.stakedAmount.toString(), stakeAmount.toString());
+    const posAfter = await program.account.userPosition.fetch(userPosition);
+    assert.equal(
+      posAfter.lpBalance.toString(),
+      posBefore.lpBalance.sub(stakeAmount).toString()
+    );
+
+    // 代币化仓位计入质押仓位数量，并记录自己的质押量历史
+    const poolAfterStake = await program.account.poolState.fetch(poolState);
+    assert.equal(
+      poolAfterStake.stakerCount.toString(),
+      poolBefore.stakerCount.addn(1).toString()
+    );
+    const [positionHistory] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stake_history"), tokenizedPosition.toBuffer(), poolState.toBuffer()],
+      program.programId
+    );
+    const historyAfterStake = await program.account.stakeHistory.fetch(positionHistory);
+    assert.equal(historyAfterStake.owner.toString(), tokenizedPosition.toString());
+    assert.equal(
+      historyAfterStake.checkpoints[historyAfterStake.checkpoints.length - 1].amount.toString(),
+      stakeAmount.toString()
+    );
+
+    // 2. 将仓位代币转让给新持有者
+    const buyer = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: buyer.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        })
+      )
+    );
+    const buyerNftAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      positionMint.publicKey,
+      buyer.publicKey
+    );
+    await transfer(
+      provider.connection,
+      payer.payer,
+      getAssociatedTokenAddressSync(positionMint.publicKey, payer.publicKey),
+      buyerNftAta.address,
+      payer.payer,
+      1
+    );
+
+    // 3. 原质押者不再持有仓位代币，不能领取
+    await sleep(2000);
+    try {
+      await program.methods
+        .claimNft()
+        .accounts({
+          tokenizedPosition,
+          holderNftAccount: getAssociatedTokenAddressSync(positionMint.publicKey, payer.publicKey),
+        })
+        .rpc();
+      assert.fail("未持有仓位代币时应被拒绝");
+    } catch (err: any) {
+      assert.include(err.toString(), "NotPositionHolder");
+    }
+
+    // 4. 新持有者领取奖励
+    const buyerBefore = await provider.connection.getBalance(buyer.publicKey);
+    await program.methods
+      .claimNft()
+      .accounts({
+        holder: buyer.publicKey,
+        tokenizedPosition,
+        holderNftAccount: buyerNftAta.address,
+      })
+      .signers([buyer])
+      .rpc();
+    assert.isTrue(
+      (await provider.connection.getBalance(buyer.publicKey)) > buyerBefore,
+      "新持有者应收到奖励"
+    );
+
+    // 5. 销毁仓位代币退出，LP 记入新持有者仓位
+    const buyerLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      lpTokenMint,
+      buyer.publicKey
+    );
+    await program.methods
+      .exitNft()
+      .accounts({
+        holder: buyer.publicKey,
+        positionMint: positionMint.publicKey,
+        holderNftAccount: buyerNftAta.address,
+        holderLpAccount: buyerLpAta.address,
+      })
+      .signers([buyer])
+      .rpc();
+
+    assert.isNull(await program.account.tokenizedPosition.fetchNullable(tokenizedPosition));
+    const [buyerPosition] = PublicKey.findProgramAddressSync(
+      [USER_POSITION_SEED, buyer.publicKey.toBuffer(), poolState.toBuffer()],
+      program.programId
+    );
+    const buyerPos = await program.account.userPosition.fetch(buyerPosition);
+    assert.equal(buyerPos.lpBalance.toString(), stakeAmount.toString());
+    const buyerLp = await provider.connection.getTokenAccountBalance(buyerLpAta.address);
+    assert.equal(buyerLp.value.amount, stakeAmount.toString());
+
+    const poolAfterExit = await program.account.poolState.fetch(poolState);
+    assert.equal(poolAfterExit.stakerCount.toString(), poolBefore.stakerCount.toString());
+    const historyAfterExit = await program.account.stakeHistory.fetch(positionHistory);
+    assert.equal(
+      historyAfterExit.checkpoints[historyAfterExit.checkpoints.length - 1].amount.toString(),
+      "0"
+    );
+    console.log("✅ 仓位代币断言通过\n");
+  });
+
+  it("收益策略: 分配、收获与召回", async () => {
+    console.log("=== 测试: 收益策略 ===");
+
+    const [market] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), wrappedUsdcMint.toBuffer()],
+      lendingProgram.programId
+    );
+    const [marketVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market_vault"), market.toBuffer()],
+      lendingProgram.programId
+    );
+    const [lendingPosition] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), market.toBuffer(), poolState.toBuffer()],
+      lendingProgram.programId
+    );
+    const [strategyState] = PublicKey.findProgramAddressSync(
+      [Buffer.from("strategy_state"), poolState.toBuffer()],
+      program.programId
+    );
+
+    // 1. 初始化模拟借贷市场，并为池子 PDA 开设仓位
+    await lendingProgram.methods
+      .initializeMarket()
+      .accounts({ payer: payer.publicKey, mint: wrappedUsdcMint })
+      .rpc();
+    await lendingProgram.methods
+      .openPosition(poolState)
+      .accounts({ payer: payer.publicKey, market })
+      .rpc();
+
+    // 2. 配置策略（最多分配 50% 资产）
+    await program.methods
+      .initializeStrategy(new anchor.BN(5_000))
+      .accounts({
+        authority: payer.publicKey,
+        strategyProgram: lendingProgram.programId,
+        strategyMarket: market,
+        strategyVault: marketVault,
+        strategyPosition: lendingPosition,
+      })
+      .rpc();
+
+    const strategyAccounts = {
+      authority: payer.publicKey,
+      poolUsdcAccount: poolUsdcAccount,
+      strategyProgram: lendingProgram.programId,
+      strategyMarket: market,
+      strategyVault: marketVault,
+      strategyPosition: lendingPosition,
+    };
+
+    // 3. 超出上限的分配应失败
+    const poolBefore = await program.account.poolState.fetch(poolState);
+    try {
+      await program.methods
+        .allocateToStrategy(poolBefore.totalDeposited)
+        .accounts(strategyAccounts)
+        .rpc();
+      assert.fail("应该抛出 AllocationLimitExceeded 错误");
+    } catch (err: any) {
+      assert.include(err.toString(), "AllocationLimitExceeded");
+    }
+
+    // 4. 分配 1,000 USDC
+    const allocateAmount = new anchor.BN(1_000_000_000);
+    await program.methods
+      .allocateToStrategy(allocateAmount)
+      .accounts(strategyAccounts)
+      .rpc();
+
+    let strategy = await program.account.strategyState.fetch(strategyState);
+    assert.equal(strategy.allocated.toString(), allocateAmount.toString());
+    console.log("✓ 已分配:", strategy.allocated.toString());
+
+    // 5. 模拟产生 10 USDC 利息，收获后计入池子总资产
+    const interest = new anchor.BN(10_000_000);
+    const userUsdcAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer.payer,
+      wrappedUsdcMint,
+      payer.publicKey
+    );
+    await lendingProgram.methods
+      .accrueInterest(interest)
+      .accounts({
+        donor: payer.publicKey,
+        market,
+        vault: marketVault,
+        position: lendingPosition,
+        donorTokenAccount: userUsdcAta.address,
+      })
+      .rpc();
+
+    await program.methods.harvestStrategy().accounts(strategyAccounts).rpc();
+
+    const poolAfterHarvest = await program.account.poolState.fetch(poolState);
+    assert.equal(
+      poolAfterHarvest.totalDeposited.toString(),
+      poolBefore.totalDeposited.add(interest).toString(),
+      "收获后 totalDeposited 应增加利息部分"
+    );
+    console.log("✓ 收获利息:", interest.toString());
+
+    // 6. 召回全部本金
+    await program.methods
+      .recallFromStrategy(allocateAmount)
+      .accounts(strategyAccounts)
+      .rpc();
+
+    strategy = await program.account.strategyState.fetch(strategyState);
+    assert.equal(strategy.allocated.toNumber(), 0);
+    console.log("✅ 收益策略断言通过\n");
+  });
 });